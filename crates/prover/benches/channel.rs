@@ -0,0 +1,25 @@
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use stwo_prover::core::channel::{Channel, Sha256Channel};
+
+const N_FELTS: usize = 10000;
+
+fn channel_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("channel");
+    group.bench_function(&format!("sha256 draw_felt x{N_FELTS}"), |b| {
+        b.iter(|| {
+            let mut channel = Sha256Channel::default();
+            for _ in 0..N_FELTS {
+                black_box(channel.draw_felt());
+            }
+        })
+    });
+    group.bench_function(&format!("sha256 draw_felts {N_FELTS}"), |b| {
+        b.iter(|| black_box(Sha256Channel::default().draw_felts(N_FELTS)))
+    });
+}
+
+criterion_group!(
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = channel_benches);
+criterion_main!(benches);
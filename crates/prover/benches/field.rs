@@ -2,10 +2,14 @@ use criterion::{criterion_group, criterion_main, Criterion};
 use num_traits::One;
 use rand::rngs::SmallRng;
 use rand::{Rng, SeedableRng};
+use stwo_prover::core::backend::simd::column::{CM31Column, SecureColumn};
 use stwo_prover::core::backend::simd::m31::{PackedBaseField, N_LANES};
+use stwo_prover::core::backend::simd::SimdBackend;
+use stwo_prover::core::backend::Column;
 use stwo_prover::core::fields::cm31::CM31;
 use stwo_prover::core::fields::m31::{BaseField, M31};
 use stwo_prover::core::fields::qm31::SecureField;
+use stwo_prover::core::fields::{FieldExpOps, FieldOps};
 
 pub const N_ELEMENTS: usize = 1 << 16;
 pub const N_STATE_ELEMENTS: usize = 8;
@@ -142,9 +146,37 @@ pub fn simd_m31_operations_bench(c: &mut Criterion) {
     });
 }
 
+pub fn batch_inverse_bench(c: &mut Criterion) {
+    let mut rng = SmallRng::seed_from_u64(0);
+    let cm31_elements: Vec<CM31> = (0..N_ELEMENTS).map(|_| rng.gen()).collect();
+    let qm31_elements: Vec<SecureField> = (0..N_ELEMENTS).map(|_| rng.gen()).collect();
+    let cm31_column = cm31_elements.iter().copied().collect::<CM31Column>();
+    let qm31_column = qm31_elements.iter().copied().collect::<SecureColumn>();
+
+    c.bench_function("CM31 batch_inverse", |b| {
+        let mut dst = vec![CM31::default(); N_ELEMENTS];
+        b.iter(|| CM31::batch_inverse(&cm31_elements, &mut dst))
+    });
+
+    c.bench_function("CM31 batch_inverse simd", |b| {
+        let mut dst = CM31Column::zeros(N_ELEMENTS);
+        b.iter(|| <SimdBackend as FieldOps<CM31>>::batch_inverse(&cm31_column, &mut dst))
+    });
+
+    c.bench_function("SecureField batch_inverse", |b| {
+        let mut dst = vec![SecureField::default(); N_ELEMENTS];
+        b.iter(|| SecureField::batch_inverse(&qm31_elements, &mut dst))
+    });
+
+    c.bench_function("SecureField batch_inverse simd", |b| {
+        let mut dst = SecureColumn::zeros(N_ELEMENTS);
+        b.iter(|| <SimdBackend as FieldOps<SecureField>>::batch_inverse(&qm31_column, &mut dst))
+    });
+}
+
 criterion_group!(
     name = benches;
     config = Criterion::default().sample_size(10);
     targets = m31_operations_bench, cm31_operations_bench, qm31_operations_bench, 
-        simd_m31_operations_bench);
+        simd_m31_operations_bench, batch_inverse_bench);
 criterion_main!(benches);
@@ -1,9 +1,13 @@
 use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use itertools::Itertools;
+use stwo_prover::core::backend::simd::fri::fold_line_pairs;
 use stwo_prover::core::backend::CpuBackend;
+use stwo_prover::core::circle::Coset;
 use stwo_prover::core::fields::m31::BaseField;
 use stwo_prover::core::fields::qm31::SecureField;
 use stwo_prover::core::fields::secure_column::SecureColumnByCoords;
-use stwo_prover::core::fri::FriOps;
+use stwo_prover::core::fields::FieldExpOps;
+use stwo_prover::core::fri::{fold_line, FriOps};
 use stwo_prover::core::poly::circle::{CanonicCoset, PolyOps};
 use stwo_prover::core::poly::line::{LineDomain, LineEvaluation};
 
@@ -31,5 +35,50 @@ fn folding_benchmark(c: &mut Criterion) {
     });
 }
 
-criterion_group!(benches, folding_benchmark);
+fn query_folding_benchmark(c: &mut Criterion) {
+    const LOG_SIZE: u32 = 12;
+    const N_QUERIES: usize = 100;
+    let domain = LineDomain::new(Coset::half_odds(LOG_SIZE));
+    let alpha = SecureField::from_u32_unchecked(2213980, 2213981, 2213982, 2213983);
+    let sublines = (0..N_QUERIES)
+        .map(|i| {
+            let subline_domain = LineDomain::new(Coset::new(domain.coset().index_at(i * 37), 1));
+            let values = [SecureField::from_u32_unchecked(i as u32, 1, 2, 3); 2]
+                .into_iter()
+                .collect();
+            LineEvaluation::new(subline_domain, values)
+        })
+        .collect_vec();
+
+    let mut group = c.benchmark_group("fold_queries");
+    group.bench_function(format!("per_query_{N_QUERIES}"), |b| {
+        b.iter(|| {
+            black_box(
+                sublines
+                    .iter()
+                    .map(|e| fold_line(black_box(e), black_box(alpha)).values.at(0))
+                    .collect_vec(),
+            )
+        })
+    });
+    group.bench_function(format!("batched_{N_QUERIES}"), |b| {
+        b.iter(|| {
+            let xs = sublines.iter().map(|e| e.domain().at(0)).collect_vec();
+            let mut x_inverses = vec![BaseField::from(0); N_QUERIES];
+            BaseField::batch_inverse(&xs, &mut x_inverses);
+            let pairs = sublines
+                .iter()
+                .map(|e| [e.values.at(0), e.values.at(1)])
+                .collect_vec();
+            black_box(fold_line_pairs(
+                black_box(&pairs),
+                &x_inverses,
+                black_box(alpha),
+            ))
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, folding_benchmark, query_folding_benchmark);
 criterion_main!(benches);
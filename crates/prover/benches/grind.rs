@@ -0,0 +1,30 @@
+use criterion::{criterion_group, criterion_main, Criterion};
+use stwo_prover::core::backend::simd::SimdBackend;
+use stwo_prover::core::channel::{Poseidon252Channel, Sha256Channel};
+use stwo_prover::core::proof_of_work::GrindOps;
+
+const POW_BITS: u32 = 20;
+
+fn grind_benches(c: &mut Criterion) {
+    let mut group = c.benchmark_group("grind");
+    group.bench_function(&format!("simd sha256 grind {POW_BITS} bits"), |b| {
+        b.iter(|| {
+            <SimdBackend as GrindOps<Sha256Channel>>::grind(&Sha256Channel::default(), POW_BITS)
+        })
+    });
+    // Hashes one nonce per Poseidon permutation, only spreading the search over threads.
+    group.bench_function(&format!("simd poseidon252 grind {POW_BITS} bits"), |b| {
+        b.iter(|| {
+            <SimdBackend as GrindOps<Poseidon252Channel>>::grind(
+                &Poseidon252Channel::default(),
+                POW_BITS,
+            )
+        })
+    });
+}
+
+criterion_group!(
+    name = benches;
+    config = Criterion::default().sample_size(10);
+    targets = grind_benches);
+criterion_main!(benches);
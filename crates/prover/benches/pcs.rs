@@ -1,8 +1,4 @@
-use std::iter;
-
 use criterion::{black_box, criterion_group, criterion_main, BatchSize, Criterion};
-use rand::rngs::SmallRng;
-use rand::{Rng, SeedableRng};
 use stwo_prover::core::backend::simd::SimdBackend;
 use stwo_prover::core::backend::{BackendForChannel, CpuBackend};
 use stwo_prover::core::channel::Sha256Channel;
@@ -11,6 +7,7 @@ use stwo_prover::core::pcs::CommitmentTreeProver;
 use stwo_prover::core::poly::circle::{CanonicCoset, CircleEvaluation};
 use stwo_prover::core::poly::twiddles::TwiddleTree;
 use stwo_prover::core::poly::BitReversedOrder;
+use stwo_prover::core::utils::deterministic_trace;
 use stwo_prover::core::vcs::sha256_merkle::Sha256MerkleChannel;
 
 const LOG_COSET_SIZE: u32 = 20;
@@ -21,11 +18,16 @@ fn benched_fn<B: BackendForChannel<Sha256MerkleChannel>>(
     evals: Vec<CircleEvaluation<B, BaseField, BitReversedOrder>>,
     channel: &mut Sha256Channel,
     twiddles: &TwiddleTree<B>,
+    batch_interpolation: bool,
 ) {
-    let polys = evals
-        .into_iter()
-        .map(|eval| eval.interpolate_with_twiddles(twiddles))
-        .collect();
+    let polys = if batch_interpolation {
+        CircleEvaluation::batch_interpolate_with_twiddles(evals, twiddles)
+    } else {
+        evals
+            .into_iter()
+            .map(|eval| eval.interpolate_with_twiddles(twiddles))
+            .collect()
+    };
 
     CommitmentTreeProver::<B, Sha256MerkleChannel>::new(
         polys,
@@ -40,33 +42,34 @@ fn bench_pcs<B: BackendForChannel<Sha256MerkleChannel>>(c: &mut Criterion, id: &
     let big_domain = CanonicCoset::new(LOG_COSET_SIZE + LOG_BLOWUP_FACTOR);
     let twiddles = B::precompute_twiddles(big_domain.half_coset());
     let mut channel = Sha256Channel::default();
-    let mut rng = SmallRng::seed_from_u64(0);
 
-    let evals: Vec<CircleEvaluation<B, BaseField, BitReversedOrder>> = iter::repeat_with(|| {
-        CircleEvaluation::new(
-            small_domain.circle_domain(),
-            (0..1 << LOG_COSET_SIZE).map(|_| rng.gen()).collect(),
-        )
-    })
-    .take(N_POLYS)
-    .collect();
+    let evals: Vec<CircleEvaluation<B, BaseField, BitReversedOrder>> =
+        deterministic_trace(LOG_COSET_SIZE, N_POLYS)
+            .into_iter()
+            .map(|col| {
+                CircleEvaluation::new(small_domain.circle_domain(), col.into_iter().collect())
+            })
+            .collect();
 
-    c.bench_function(
-        &format!("{id} polynomial commitment 2^{LOG_COSET_SIZE}"),
-        |b| {
-            b.iter_batched(
-                || evals.clone(),
-                |evals| {
-                    benched_fn::<B>(
-                        black_box(evals),
-                        black_box(&mut channel),
-                        black_box(&twiddles),
-                    )
-                },
-                BatchSize::LargeInput,
-            );
-        },
-    );
+    for (batch_interpolation, variant) in [(false, ""), (true, " batch interpolation")] {
+        c.bench_function(
+            &format!("{id} polynomial commitment 2^{LOG_COSET_SIZE}{variant}"),
+            |b| {
+                b.iter_batched(
+                    || evals.clone(),
+                    |evals| {
+                        benched_fn::<B>(
+                            black_box(evals),
+                            black_box(&mut channel),
+                            black_box(&twiddles),
+                            batch_interpolation,
+                        )
+                    },
+                    BatchSize::LargeInput,
+                );
+            },
+        );
+    }
 }
 
 fn pcs_benches(c: &mut Criterion) {
@@ -27,7 +27,7 @@ impl FriOps for CpuBackend {
 
     fn decompose(eval: &SecureEvaluation<Self>) -> (SecureEvaluation<Self>, SecureField) {
         let lambda = Self::decomposition_coefficient(eval);
-        let mut g_values = unsafe { SecureColumnByCoords::<Self>::uninitialized(eval.len()) };
+        let mut g_values = SecureColumnByCoords::<Self>::zeros(eval.len());
 
         let domain_size = eval.len();
         let half_domain_size = domain_size / 2;
@@ -88,8 +88,11 @@ impl CpuBackend {
 #[cfg(test)]
 mod tests {
     use num_traits::Zero;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
 
     use crate::core::backend::cpu::{CpuCircleEvaluation, CpuCirclePoly};
+    use crate::core::backend::simd::SimdBackend;
     use crate::core::backend::CpuBackend;
     use crate::core::fields::m31::BaseField;
     use crate::core::fields::qm31::SecureField;
@@ -140,4 +143,28 @@ mod tests {
             }
         }
     }
+
+    #[test]
+    fn decompose_matches_simd_decompose() {
+        const LOG_SIZE: u32 = 7;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let domain = CanonicCoset::new(LOG_SIZE).circle_domain();
+        let values = (0..1 << LOG_SIZE)
+            .map(|_| rng.gen())
+            .collect::<Vec<SecureField>>();
+        let cpu_eval = SecureEvaluation::<CpuBackend> {
+            domain,
+            values: values.iter().copied().collect(),
+        };
+        let simd_eval = SecureEvaluation::<SimdBackend> {
+            domain,
+            values: values.into_iter().collect(),
+        };
+
+        let (cpu_g, cpu_lambda) = CpuBackend::decompose(&cpu_eval);
+        let (simd_g, simd_lambda) = SimdBackend::decompose(&simd_eval);
+
+        assert_eq!(cpu_lambda, simd_lambda);
+        assert_eq!(cpu_g.values.to_vec(), simd_g.values.to_vec());
+    }
 }
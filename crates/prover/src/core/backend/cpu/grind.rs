@@ -1,18 +1,17 @@
 use super::CpuBackend;
 use crate::core::channel::Channel;
-use crate::core::proof_of_work::GrindOps;
+use crate::core::proof_of_work::{verify_work, verify_work_with_predicate, GrindOps};
 
 impl<C: Channel> GrindOps<C> for CpuBackend {
-    fn grind(channel: &C, pow_bits: u32) -> u64 {
+    fn grind_from(channel: &C, pow_bits: u32, start_nonce: u64, max_iters: u64) -> Option<u64> {
         // TODO(spapini): This is a naive implementation. Optimize it.
-        let mut nonce = 0;
-        loop {
-            let mut channel = channel.clone();
-            channel.mix_nonce(nonce);
-            if channel.trailing_zeros() >= pow_bits {
-                return nonce;
-            }
-            nonce += 1;
-        }
+        (start_nonce..start_nonce.saturating_add(max_iters))
+            .find(|&nonce| verify_work(channel, nonce, pow_bits))
+    }
+
+    fn grind_with_predicate(channel: &C, predicate: impl Fn(&C) -> bool + Sync) -> u64 {
+        (0..)
+            .find(|&nonce| verify_work_with_predicate(channel, nonce, &predicate))
+            .unwrap()
     }
 }
@@ -0,0 +1,23 @@
+use itertools::Itertools;
+
+use crate::core::backend::CpuBackend;
+use crate::core::fields::m31::BaseField;
+use crate::core::vcs::keccak256_merkle::{Keccak256Hash, Keccak256MerkleHasher};
+use crate::core::vcs::ops::{MerkleHasher, MerkleOps};
+
+impl MerkleOps<Keccak256MerkleHasher> for CpuBackend {
+    fn commit_on_layer(
+        log_size: u32,
+        prev_layer: Option<&Vec<Keccak256Hash>>,
+        columns: &[&Vec<BaseField>],
+    ) -> Vec<Keccak256Hash> {
+        (0..(1 << log_size))
+            .map(|i| {
+                Keccak256MerkleHasher::hash_tagged_node(
+                    prev_layer.map(|prev_layer| (prev_layer[2 * i], prev_layer[2 * i + 1])),
+                    &columns.iter().map(|column| column[i]).collect_vec(),
+                )
+            })
+            .collect()
+    }
+}
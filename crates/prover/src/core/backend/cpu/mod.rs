@@ -2,6 +2,8 @@ mod accumulation;
 mod circle;
 mod fri;
 mod grind;
+#[cfg(feature = "keccak")]
+mod keccak256;
 pub mod lookups;
 pub mod quotients;
 mod sha256;
@@ -11,21 +13,33 @@ use std::fmt::Debug;
 use serde::{Deserialize, Serialize};
 
 use super::{Backend, BackendForChannel, Column, ColumnOps, FieldOps};
+#[cfg(any(test, feature = "testing"))]
+use crate::core::channel::DummyMerkleChannel;
 use crate::core::fields::Field;
 use crate::core::lookups::mle::Mle;
 use crate::core::poly::circle::{CircleEvaluation, CirclePoly};
 use crate::core::utils::bit_reverse;
+#[cfg(feature = "keccak")]
+use crate::core::vcs::keccak256_merkle::Keccak256MerkleChannel;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::core::vcs::poseidon252_merkle::Poseidon252MerkleChannel;
-use crate::core::vcs::sha256_merkle::Sha256MerkleChannel;
+use crate::core::vcs::sha256_merkle::{
+    Sha256BatchedMerkleChannel, Sha256MerkleChannel, Sha256StreamingMerkleChannel,
+};
 
 #[derive(Copy, Clone, Debug, Deserialize, Serialize)]
 pub struct CpuBackend;
 
 impl Backend for CpuBackend {}
 impl BackendForChannel<Sha256MerkleChannel> for CpuBackend {}
+impl BackendForChannel<Sha256StreamingMerkleChannel> for CpuBackend {}
+impl BackendForChannel<Sha256BatchedMerkleChannel> for CpuBackend {}
+#[cfg(feature = "keccak")]
+impl BackendForChannel<Keccak256MerkleChannel> for CpuBackend {}
 #[cfg(not(target_arch = "wasm32"))]
 impl BackendForChannel<Poseidon252MerkleChannel> for CpuBackend {}
+#[cfg(any(test, feature = "testing"))]
+impl BackendForChannel<DummyMerkleChannel> for CpuBackend {}
 
 impl<T: Debug + Clone + Default> ColumnOps<T> for CpuBackend {
     type Column = Vec<T>;
@@ -1,5 +1,7 @@
-use itertools::{izip, zip_eq};
+use itertools::{izip, zip_eq, Itertools};
 use num_traits::{One, Zero};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 use super::CpuBackend;
 use crate::core::backend::Col;
@@ -9,8 +11,10 @@ use crate::core::fields::cm31::CM31;
 use crate::core::fields::m31::BaseField;
 use crate::core::fields::qm31::{SecureField, QM31};
 use crate::core::fields::secure_column::SecureColumnByCoords;
-use crate::core::fields::{ComplexOf, FieldExpOps};
-use crate::core::pcs::quotients::{ColumnSampleBatch, PointSample, QuotientOps};
+use crate::core::fields::{ComplexConjugate, ComplexOf, FieldExpOps};
+use crate::core::pcs::quotients::{
+    ColumnSampleBatch, MultiPointSampleBatch, PointSample, QuotientError, QuotientOps,
+};
 use crate::core::poly::circle::{CircleDomain, CircleEvaluation, SecureEvaluation};
 use crate::core::poly::BitReversedOrder;
 use crate::core::utils::{bit_reverse, bit_reverse_index};
@@ -34,12 +38,34 @@ impl QuotientOps for CpuBackend {
         sample_batches: &[ColumnSampleBatch],
         _log_blowup_factor: u32,
     ) -> SecureEvaluation<Self> {
-        let mut values = unsafe { SecureColumnByCoords::uninitialized(domain.size()) };
+        if sample_batches
+            .iter()
+            .all(|sample_batch| sample_batch.columns_and_values.len() == 1)
+        {
+            return accumulate_single_column_quotients(
+                domain,
+                columns,
+                random_coeff,
+                sample_batches,
+            );
+        }
+
         let (line_coeffs, batch_random_coeffs) =
             line_batch_random_coeffs(sample_batches, random_coeff);
         let denominator_inverses = denominator_inverses(sample_batches, domain);
 
-        for row in 0..domain.size() {
+        // Rows are independent, so computing them in parallel gives the same values. Each row is
+        // written in place to the coordinate columns.
+        let mut values = SecureColumnByCoords::<Self>::zeros(domain.size());
+        let [col0, col1, col2, col3] = &mut values.columns;
+
+        #[cfg(not(feature = "parallel"))]
+        let rows = izip!(col0, col1, col2, col3).enumerate();
+
+        #[cfg(feature = "parallel")]
+        let rows = (col0, col1, col2, col3).into_par_iter().enumerate();
+
+        rows.for_each(|(row, (v0, v1, v2, v3))| {
             let domain_point = domain.at(bit_reverse_index(row, domain.log_size()));
             let row_value = accumulate_row_quotients(
                 sample_batches,
@@ -50,10 +76,66 @@ impl QuotientOps for CpuBackend {
                 domain_point,
                 random_coeff,
             );
-            values.set(row, row_value);
-        }
+            [*v0, *v1, *v2, *v3] = row_value.to_m31_array();
+        });
         SecureEvaluation { domain, values }
     }
+
+    fn accumulate_multi_point_quotients(
+        domain: CircleDomain,
+        columns: &[&CircleEvaluation<Self, BaseField, BitReversedOrder>],
+        random_coeff: SecureField,
+        sample_batches: &[MultiPointSampleBatch],
+        max_numerator_degree: usize,
+    ) -> Result<SecureEvaluation<Self>, QuotientError> {
+        for sample_batch in sample_batches {
+            let degree = (2 * sample_batch.points.len()).saturating_sub(1);
+            if degree > max_numerator_degree {
+                return Err(QuotientError::NumeratorDegreeTooHigh {
+                    degree,
+                    max_degree: max_numerator_degree,
+                });
+            }
+        }
+        let interpolant_coeffs = column_interpolant_coeffs(sample_batches)?;
+        let batch_random_coeffs = sample_batches
+            .iter()
+            .map(|sb| random_coeff.pow(sb.columns_and_values.len() as u128))
+            .collect_vec();
+        let denominator_inverses = sample_batches
+            .iter()
+            .map(|sample_batch| multi_point_denominator_inverses(&sample_batch.points, domain))
+            .collect_vec();
+
+        let mut values = SecureColumnByCoords::zeros(domain.size());
+        for row in 0..domain.size() {
+            let domain_point = domain.at(bit_reverse_index(row, domain.log_size()));
+            let mut row_accumulator = SecureField::zero();
+            for (sample_batch, interpolant_coeffs, batch_coeff, denominator_inverses) in izip!(
+                sample_batches,
+                &interpolant_coeffs,
+                &batch_random_coeffs,
+                &denominator_inverses
+            ) {
+                let mut numerator = SecureField::zero();
+                for ((column_index, _), coeffs) in
+                    zip_eq(&sample_batch.columns_and_values, interpolant_coeffs)
+                {
+                    let value = columns[*column_index][row];
+                    let interpolant = coeffs
+                        .iter()
+                        .rfold(CM31::zero(), |acc, &coeff| acc * domain_point.y + coeff);
+                    numerator *= random_coeff;
+                    numerator += SecureField::from(value - interpolant);
+                }
+
+                row_accumulator = row_accumulator * *batch_coeff
+                    + numerator * SecureField::from(denominator_inverses[row]);
+            }
+            values.set(row, row_accumulator);
+        }
+        Ok(SecureEvaluation { domain, values })
+    }
 }
 // TODO(Ohad): no longer using pair_vanishing, remove domain_point_vec and line_coeffs, or write a
 // function that deals with quotients over pair_vanishing polynomials.
@@ -102,6 +184,151 @@ pub fn accumulate_row_quotients(
     row_accumulator
 }
 
+/// Accumulates quotients for sample batches of a single column each, one batch at a time.
+///
+/// Equivalent to [QuotientOps::accumulate_quotients], but the line coefficients and the batch
+/// coefficient of each batch are hoisted out of the row loop, and each column is traversed in a
+/// single pass.
+///
+/// # Panics
+///
+/// Panics if a sample batch doesn't have exactly one column.
+pub fn accumulate_single_column_quotients(
+    domain: CircleDomain,
+    columns: &[&CircleEvaluation<CpuBackend, BaseField, BitReversedOrder>],
+    random_coeff: SecureField,
+    sample_batches: &[ColumnSampleBatch],
+) -> SecureEvaluation<CpuBackend> {
+    assert!(sample_batches
+        .iter()
+        .all(|sample_batch| sample_batch.columns_and_values.len() == 1));
+    let (line_coeffs, batch_random_coeffs) = line_batch_random_coeffs(sample_batches, random_coeff);
+    let denominator_inverses = denominator_inverses(sample_batches, domain);
+    let domain_ys = (0..domain.size())
+        .map(|row| domain.at(bit_reverse_index(row, domain.log_size())).y)
+        .collect_vec();
+
+    let mut accumulator = vec![SecureField::zero(); domain.size()];
+    for (sample_batch, line_coeffs, batch_coeff, denominator_inverses) in izip!(
+        sample_batches,
+        &line_coeffs,
+        batch_random_coeffs,
+        &denominator_inverses
+    ) {
+        let column = &columns[sample_batch.columns_and_values[0].0];
+        let (a, b) = line_coeffs[0];
+
+        for (row_accumulator, &value, &y, &denominator_inverse) in izip!(
+            &mut accumulator,
+            &column.values,
+            &domain_ys,
+            denominator_inverses
+        ) {
+            let numerator = (value - (a * y + b)) * denominator_inverse;
+            *row_accumulator = *row_accumulator * batch_coeff + QM31::from(numerator);
+        }
+    }
+
+    SecureEvaluation {
+        domain,
+        values: accumulator.into_iter().collect(),
+    }
+}
+
+/// Precomputes, for each column in each multi-point sample batch, the coefficients of the
+/// numerator interpolant `I`, from the constant term up. `I` has CM31 coefficients and satisfies
+/// `I(p.y) = F(p)` and `I(conj(p.y)) = conj(F(p))` for each sampled point `p`.
+///
+/// For a single point, the coefficients are the `(b, a)` of [column_line_coeffs].
+///
+/// # Errors
+///
+/// Returns [QuotientError::DuplicateSamplePoint] if two points of a batch, or a point of a batch
+/// and its complex conjugate, have the same y coordinate.
+pub fn column_interpolant_coeffs(
+    sample_batches: &[MultiPointSampleBatch],
+) -> Result<Vec<Vec<Vec<CM31>>>, QuotientError> {
+    sample_batches
+        .iter()
+        .map(|sample_batch| {
+            sample_batch
+                .columns_and_values
+                .iter()
+                .map(|(_, sampled_values)| {
+                    let samples = zip_eq(&sample_batch.points, sampled_values)
+                        .flat_map(|(point, &value)| {
+                            [
+                                (point.y, value),
+                                (point.y.complex_conjugate(), value.complex_conjugate()),
+                            ]
+                        })
+                        .collect_vec();
+                    // The interpolant is invariant to conjugation, so its coefficients are in
+                    // CM31.
+                    let coeffs =
+                        interpolate(&samples).ok_or(QuotientError::DuplicateSamplePoint)?;
+                    Ok(coeffs
+                        .into_iter()
+                        .map(|coeff| {
+                            assert_eq!(coeff.1, CM31::zero());
+                            coeff.0
+                        })
+                        .collect())
+                })
+                .collect()
+        })
+        .collect()
+}
+
+/// Returns the coefficients, from the constant term up, of the polynomial of degree
+/// `samples.len() - 1` that passes through all `(x, y)` samples, or `None` if two samples have the
+/// same `x`.
+fn interpolate(samples: &[(SecureField, SecureField)]) -> Option<Vec<SecureField>> {
+    let mut coeffs = vec![SecureField::zero(); samples.len()];
+    for (i, &(x_i, y_i)) in samples.iter().enumerate() {
+        // Lagrange basis polynomial of the i-th sample, prod_{j != i} (X - x_j) / (x_i - x_j).
+        let mut basis = vec![SecureField::one()];
+        let mut scale = y_i;
+        for (j, &(x_j, _)) in samples.iter().enumerate() {
+            if j == i {
+                continue;
+            }
+            if x_i == x_j {
+                return None;
+            }
+            basis.insert(0, SecureField::zero());
+            for k in 0..basis.len() - 1 {
+                let next = basis[k + 1];
+                basis[k] -= x_j * next;
+            }
+            scale /= x_i - x_j;
+        }
+        for (coeff, basis_coeff) in zip_eq(&mut coeffs, basis) {
+            *coeff += scale * basis_coeff;
+        }
+    }
+    Some(coeffs)
+}
+
+/// Returns the bit-reversed inverses of the product of the vanishing polynomials of all `points`
+/// (and their complex conjugates) over `domain`.
+fn multi_point_denominator_inverses(
+    points: &[CirclePoint<SecureField>],
+    domain: CircleDomain,
+) -> Vec<CM31> {
+    let point_batches = points
+        .iter()
+        .map(|&point| ColumnSampleBatch {
+            point,
+            columns_and_values: vec![],
+        })
+        .collect_vec();
+    denominator_inverses(&point_batches, domain)
+        .into_iter()
+        .reduce(|acc, inverses| zip_eq(acc, inverses).map(|(a, b)| a * b).collect())
+        .expect("Sample batch has no points.")
+}
+
 /// Precompute the complex conjugate line coefficients for each column in each sample batch.
 /// Specifically, for the i-th (in a sample batch) column's numerator term
 /// `alpha^i * (c * F(p) - (a * p.y + b))`, we precompute and return the constants:
@@ -144,14 +371,32 @@ pub fn batch_random_coeffs(
         .collect()
 }
 
+/// Returns the denominator of the quotients by `sample_point` at `point`, as computed for each
+/// domain point by [denominator_inverses]. E.g. for a verifier recomputing quotients at the
+/// queried points only.
+pub fn quotient_denominator(
+    point: CirclePoint<BaseField>,
+    sample_point: CirclePoint<SecureField>,
+) -> CM31 {
+    let (d, cross_term) = denominator_coefficients(sample_point);
+    CM31::from(point.x) - CM31::from(point.y) * d + cross_term
+}
+
+/// Returns the coefficients `(d, cross_term)` of the quotient denominator
+/// `p.x - p.y * d + cross_term`, which only depend on the sample point.
+fn denominator_coefficients(sample_point: CirclePoint<SecureField>) -> (CM31, CM31) {
+    let d = sample_point.x.get_imag() * sample_point.y.get_imag().inverse();
+    let cross_term = d * sample_point.y.get_real() - sample_point.x.get_real();
+    (d, cross_term)
+}
+
 pub fn denominator_inverses(
     sample_batches: &[ColumnSampleBatch],
     domain: CircleDomain,
 ) -> Vec<Vec<CM31>> {
     let mut flat_denominators = Vec::with_capacity(sample_batches.len() * domain.size());
     for sample_batch in sample_batches {
-        let d = sample_batch.point.x.get_imag() * sample_batch.point.y.get_imag().inverse();
-        let cross_term = d * sample_batch.point.y.get_real() - sample_batch.point.x.get_real();
+        let (d, cross_term) = denominator_coefficients(sample_batch.point);
 
         for row in 0..domain.size() {
             let domain_point = domain.at(row);
@@ -185,13 +430,51 @@ pub fn line_batch_random_coeffs(
 
 #[cfg(test)]
 mod tests {
+    use itertools::{zip_eq, Itertools};
+    use num_traits::Zero;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::{
+        accumulate_row_quotients, accumulate_single_column_quotients, batch_random_coeffs,
+        denominator_inverses, line_batch_random_coeffs, quotient_denominator,
+    };
     use crate::core::backend::cpu::{CpuCircleEvaluation, CpuCirclePoly};
     use crate::core::backend::CpuBackend;
     use crate::core::circle::SECURE_FIELD_CIRCLE_GEN;
-    use crate::core::pcs::quotients::{ColumnSampleBatch, QuotientOps};
+    use crate::core::fields::qm31::SecureField;
+    use crate::core::fields::{ComplexConjugate, FieldExpOps};
+    use crate::core::pcs::quotients::{
+        ColumnSampleBatch, MultiPointSampleBatch, QuotientError, QuotientOps,
+    };
     use crate::core::poly::circle::CanonicCoset;
+    use crate::core::utils::bit_reverse_index;
     use crate::{m31, qm31};
 
+    #[test]
+    fn test_quotient_denominator_matches_denominator_inverses() {
+        const LOG_SIZE: u32 = 5;
+        let domain = CanonicCoset::new(LOG_SIZE).circle_domain();
+        let sample_batches = (1..4)
+            .map(|i| ColumnSampleBatch {
+                point: SECURE_FIELD_CIRCLE_GEN.mul(i),
+                columns_and_values: vec![],
+            })
+            .collect_vec();
+
+        let denominator_inverses = denominator_inverses(&sample_batches, domain);
+
+        for (sample_batch, inverses) in zip_eq(&sample_batches, &denominator_inverses) {
+            for row in 0..domain.size() {
+                let denominator = quotient_denominator(domain.at(row), sample_batch.point);
+                assert_eq!(
+                    denominator.inverse(),
+                    inverses[bit_reverse_index(row, LOG_SIZE)]
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_quotients_are_low_degree() {
         const LOG_SIZE: u32 = 7;
@@ -216,4 +499,244 @@ mod tests {
             CpuCircleEvaluation::new(eval_domain, quot_eval.columns[0].clone()).interpolate();
         assert!(quot_poly_base_field.is_in_fri_space(LOG_SIZE));
     }
+
+    #[test]
+    fn test_quotients_per_batch_recombine() {
+        const LOG_SIZE: u32 = 6;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let domain = CanonicCoset::new(LOG_SIZE).circle_domain();
+        let columns = (0..4)
+            .map(|_| {
+                CpuCircleEvaluation::new(domain, (0..1 << LOG_SIZE).map(|_| rng.gen()).collect())
+            })
+            .collect_vec();
+        let columns = columns.iter().collect_vec();
+        let sample_batches = [1, 3, 2, 1]
+            .map(|n_columns| ColumnSampleBatch {
+                point: SECURE_FIELD_CIRCLE_GEN.mul(rng.gen::<u128>()),
+                columns_and_values: (0..n_columns)
+                    .map(|_| (rng.gen_range(0..columns.len()), rng.gen()))
+                    .collect(),
+            })
+            .to_vec();
+        let random_coeff = rng.gen();
+
+        let per_batch = CpuBackend::accumulate_quotients_per_batch(
+            domain,
+            &columns,
+            random_coeff,
+            &sample_batches,
+            1,
+        );
+
+        let batch_coeffs = batch_random_coeffs(&sample_batches, random_coeff);
+        let expected =
+            CpuBackend::accumulate_quotients(domain, &columns, random_coeff, &sample_batches, 1);
+        for row in 0..domain.size() {
+            let recombined = per_batch
+                .iter()
+                .zip(&batch_coeffs)
+                .fold(SecureField::zero(), |acc, (quotients, &batch_coeff)| {
+                    acc * batch_coeff + quotients.values.at(row)
+                });
+            assert_eq!(recombined, expected.values.at(row));
+        }
+    }
+
+    #[test]
+    fn test_accumulate_quotients_matches_sequential_rows() {
+        const LOG_SIZE: u32 = 16;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let domain = CanonicCoset::new(LOG_SIZE).circle_domain();
+        let columns = (0..3)
+            .map(|_| {
+                CpuCircleEvaluation::new(domain, (0..1 << LOG_SIZE).map(|_| rng.gen()).collect())
+            })
+            .collect_vec();
+        let columns = columns.iter().collect_vec();
+        let sample_batches = [2, 1]
+            .map(|n_columns| ColumnSampleBatch {
+                point: SECURE_FIELD_CIRCLE_GEN.mul(rng.gen::<u128>()),
+                columns_and_values: (0..n_columns)
+                    .map(|_| (rng.gen_range(0..columns.len()), rng.gen()))
+                    .collect(),
+            })
+            .to_vec();
+        let random_coeff = rng.gen();
+
+        let quotients =
+            CpuBackend::accumulate_quotients(domain, &columns, random_coeff, &sample_batches, 1);
+
+        let (line_coeffs, batch_random_coeffs) =
+            line_batch_random_coeffs(&sample_batches, random_coeff);
+        let denominator_inverses = denominator_inverses(&sample_batches, domain);
+        let mut expected = Vec::with_capacity(domain.size());
+        for row in 0..domain.size() {
+            expected.push(accumulate_row_quotients(
+                &sample_batches,
+                &columns,
+                (&line_coeffs, &batch_random_coeffs),
+                &denominator_inverses,
+                row,
+                domain.at(bit_reverse_index(row, LOG_SIZE)),
+                random_coeff,
+            ));
+        }
+        assert_eq!(quotients.values.to_vec(), expected);
+    }
+
+    #[test]
+    fn test_single_column_quotients_match_generic_path() {
+        const LOG_SIZE: u32 = 6;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let domain = CanonicCoset::new(LOG_SIZE).circle_domain();
+        let columns = (0..4)
+            .map(|_| {
+                CpuCircleEvaluation::new(domain, (0..1 << LOG_SIZE).map(|_| rng.gen()).collect())
+            })
+            .collect_vec();
+        let columns = columns.iter().collect_vec();
+        let sample_batches = (0..6)
+            .map(|_| ColumnSampleBatch {
+                point: SECURE_FIELD_CIRCLE_GEN.mul(rng.gen::<u128>()),
+                columns_and_values: vec![(rng.gen_range(0..columns.len()), rng.gen())],
+            })
+            .collect_vec();
+        let random_coeff = rng.gen();
+
+        let quotients =
+            accumulate_single_column_quotients(domain, &columns, random_coeff, &sample_batches);
+
+        let (line_coeffs, batch_random_coeffs) =
+            line_batch_random_coeffs(&sample_batches, random_coeff);
+        let denominator_inverses = denominator_inverses(&sample_batches, domain);
+        let expected = (0..domain.size())
+            .map(|row| {
+                accumulate_row_quotients(
+                    &sample_batches,
+                    &columns,
+                    (&line_coeffs, &batch_random_coeffs),
+                    &denominator_inverses,
+                    row,
+                    domain.at(bit_reverse_index(row, LOG_SIZE)),
+                    random_coeff,
+                )
+            })
+            .collect_vec();
+        assert_eq!(quotients.domain, domain);
+        assert_eq!(quotients.values.to_vec(), expected);
+    }
+
+    #[test]
+    fn test_multi_point_quotients_are_low_degree() {
+        const LOG_SIZE: u32 = 7;
+        let polynomial = CpuCirclePoly::new((0..1 << LOG_SIZE).map(|i| m31!(i)).collect());
+        let eval_domain = CanonicCoset::new(LOG_SIZE + 1).circle_domain();
+        let eval = polynomial.evaluate(eval_domain);
+        let points = vec![SECURE_FIELD_CIRCLE_GEN, SECURE_FIELD_CIRCLE_GEN.mul(3)];
+        let values = points
+            .iter()
+            .map(|&point| polynomial.eval_at_point(point))
+            .collect_vec();
+        let coeff = qm31!(1, 2, 3, 4);
+        let sample_batch = MultiPointSampleBatch {
+            points,
+            columns_and_values: vec![(0, values.clone())],
+        };
+        let mut wrong_sample_batch = sample_batch.clone();
+        wrong_sample_batch.columns_and_values[0].1[1] += qm31!(1, 0, 0, 0);
+
+        let quot_eval = CpuBackend::accumulate_multi_point_quotients(
+            eval_domain,
+            &[&eval],
+            coeff,
+            &[sample_batch],
+            3,
+        )
+        .unwrap();
+        let wrong_quot_eval = CpuBackend::accumulate_multi_point_quotients(
+            eval_domain,
+            &[&eval],
+            coeff,
+            &[wrong_sample_batch],
+            3,
+        )
+        .unwrap();
+
+        assert!(quot_eval.is_in_fri_space(LOG_SIZE));
+        assert!(!wrong_quot_eval.is_in_fri_space(LOG_SIZE));
+    }
+
+    #[test]
+    fn test_multi_point_quotients_reject_invalid_batches() {
+        const LOG_SIZE: u32 = 5;
+        let domain = CanonicCoset::new(LOG_SIZE).circle_domain();
+        let eval = CpuCircleEvaluation::new(domain, (0..1 << LOG_SIZE).map(|i| m31!(i)).collect());
+        let point = SECURE_FIELD_CIRCLE_GEN;
+        let batch = |points: Vec<_>| MultiPointSampleBatch {
+            columns_and_values: vec![(0, vec![qm31!(1, 2, 3, 4); points.len()])],
+            points,
+        };
+        let accumulate = |sample_batch, max_numerator_degree| {
+            CpuBackend::accumulate_multi_point_quotients(
+                domain,
+                &[&eval],
+                qm31!(1, 2, 3, 4),
+                &[sample_batch],
+                max_numerator_degree,
+            )
+            .err()
+        };
+
+        assert_eq!(
+            accumulate(batch(vec![point, point.mul(3)]), 2),
+            Some(QuotientError::NumeratorDegreeTooHigh {
+                degree: 3,
+                max_degree: 2
+            })
+        );
+        assert_eq!(
+            accumulate(batch(vec![point, point]), 3),
+            Some(QuotientError::DuplicateSamplePoint)
+        );
+        assert_eq!(
+            accumulate(batch(vec![point, point.complex_conjugate()]), 3),
+            Some(QuotientError::DuplicateSamplePoint)
+        );
+    }
+
+    #[test]
+    fn test_single_point_multi_point_quotients_match_accumulate_quotients() {
+        const LOG_SIZE: u32 = 6;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let domain = CanonicCoset::new(LOG_SIZE).circle_domain();
+        let columns = (0..4)
+            .map(|_| {
+                CpuCircleEvaluation::new(domain, (0..1 << LOG_SIZE).map(|_| rng.gen()).collect())
+            })
+            .collect_vec();
+        let columns = columns.iter().collect_vec();
+        let sample_batches = (0..3)
+            .map(|_| ColumnSampleBatch {
+                point: SECURE_FIELD_CIRCLE_GEN.mul(rng.gen::<u128>()),
+                columns_and_values: (0..3)
+                    .map(|_| (rng.gen_range(0..columns.len()), rng.gen()))
+                    .collect(),
+            })
+            .collect_vec();
+        let random_coeff = rng.gen();
+
+        let quotients = CpuBackend::accumulate_multi_point_quotients(
+            domain,
+            &columns,
+            random_coeff,
+            &sample_batches.iter().cloned().map_into().collect_vec(),
+            1,
+        )
+        .unwrap();
+
+        let expected =
+            CpuBackend::accumulate_quotients(domain, &columns, random_coeff, &sample_batches, 1);
+        assert_eq!(quotients.values.to_vec(), expected.values.to_vec());
+    }
 }
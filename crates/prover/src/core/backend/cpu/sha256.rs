@@ -14,7 +14,7 @@ impl MerkleOps<Sha256MerkleHasher> for CpuBackend {
     ) -> Vec<Sha256Hash> {
         (0..(1 << log_size))
             .map(|i| {
-                Sha256MerkleHasher::hash_node(
+                Sha256MerkleHasher::hash_tagged_node(
                     prev_layer.map(|prev_layer| (prev_layer[2 * i], prev_layer[2 * i + 1])),
                     &columns.iter().map(|column| column[i]).collect_vec(),
                 )
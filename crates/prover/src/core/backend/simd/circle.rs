@@ -3,6 +3,8 @@ use std::mem::transmute;
 
 use bytemuck::{cast_slice, Zeroable};
 use num_traits::One;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 
 use super::fft::{ifft, rfft, CACHED_FFT_LOG_SIZE};
 use super::m31::{PackedBaseField, LOG_N_LANES, N_LANES};
@@ -139,6 +141,8 @@ impl PolyOps for SimdBackend {
         )
     }
 
+    /// The IFFT runs on the calling thread, in a fixed order, so the coefficients are
+    /// bit-identical for any number of rayon threads.
     fn interpolate(
         eval: CircleEvaluation<Self, BaseField, BitReversedOrder>,
         twiddles: &TwiddleTree<Self>,
@@ -164,6 +168,21 @@ impl PolyOps for SimdBackend {
         CirclePoly::new(values)
     }
 
+    /// Interpolates each evaluation on its own thread. Each polynomial is computed exactly as by
+    /// [Self::interpolate], so the results don't depend on the number of threads.
+    fn batch_interpolate(
+        evals: Vec<CircleEvaluation<Self, BaseField, BitReversedOrder>>,
+        twiddles: &TwiddleTree<Self>,
+    ) -> Vec<CirclePoly<Self>> {
+        #[cfg(not(feature = "parallel"))]
+        let iter = evals.into_iter();
+
+        #[cfg(feature = "parallel")]
+        let iter = evals.into_par_iter();
+
+        iter.map(|eval| Self::interpolate(eval, twiddles)).collect()
+    }
+
     fn eval_at_point(poly: &CirclePoly<Self>, point: CirclePoint<SecureField>) -> SecureField {
         // If the polynomial is small, fallback to evaluate directly.
         // TODO(Ohad): it's possible to avoid falling back. Consider fixing.
@@ -328,6 +347,9 @@ fn slow_eval_at_point(
 
 #[cfg(test)]
 mod tests {
+    use std::iter::zip;
+
+    use itertools::Itertools;
     use rand::rngs::SmallRng;
     use rand::{Rng, SeedableRng};
 
@@ -356,6 +378,57 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_batch_interpolate_matches_interpolate() {
+        let max_log_size = CACHED_FFT_LOG_SIZE + 2;
+        let twiddles =
+            SimdBackend::precompute_twiddles(CanonicCoset::new(max_log_size).half_coset());
+        let mut rng = SmallRng::seed_from_u64(0);
+        let evals = (MIN_FFT_LOG_SIZE..=max_log_size)
+            .map(|log_size| {
+                CircleEvaluation::<SimdBackend, BaseField, BitReversedOrder>::new(
+                    CanonicCoset::new(log_size).circle_domain(),
+                    (0..1 << log_size).map(|_| rng.gen()).collect(),
+                )
+            })
+            .collect_vec();
+
+        let polys = CircleEvaluation::batch_interpolate_with_twiddles(evals.clone(), &twiddles);
+
+        assert_eq!(polys.len(), evals.len());
+        for (poly, eval) in zip(polys, evals) {
+            let expected = eval.interpolate_with_twiddles(&twiddles);
+            assert_eq!(poly.coeffs.to_cpu(), expected.coeffs.to_cpu());
+        }
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_interpolate_is_independent_of_thread_count() {
+        let log_size = CACHED_FFT_LOG_SIZE + 2;
+        let domain = CanonicCoset::new(log_size).circle_domain();
+        let mut rng = SmallRng::seed_from_u64(0);
+        let evaluation = CircleEvaluation::<SimdBackend, BaseField, BitReversedOrder>::new(
+            domain,
+            (0..1 << log_size).map(|_| rng.gen()).collect(),
+        );
+        let twiddles = SimdBackend::precompute_twiddles(domain.half_coset);
+        let interpolate_with_n_threads = |n_threads| {
+            let pool = rayon::ThreadPoolBuilder::new()
+                .num_threads(n_threads)
+                .build()
+                .unwrap();
+            pool.install(|| {
+                let poly = evaluation.clone().interpolate_with_twiddles(&twiddles);
+                poly.coeffs.to_cpu()
+            })
+        };
+
+        let coeffs = interpolate_with_n_threads(1);
+
+        assert_eq!(coeffs, interpolate_with_n_threads(4));
+    }
+
     #[test]
     fn test_eval_extension() {
         for log_size in MIN_FFT_LOG_SIZE..CACHED_FFT_LOG_SIZE + 2 {
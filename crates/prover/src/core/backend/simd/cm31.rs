@@ -177,6 +177,11 @@ mod tests {
     use rand::{Rng, SeedableRng};
 
     use crate::core::backend::simd::cm31::PackedCM31;
+    use crate::core::backend::simd::column::CM31Column;
+    use crate::core::backend::simd::SimdBackend;
+    use crate::core::backend::Column;
+    use crate::core::fields::cm31::CM31;
+    use crate::core::fields::{FieldExpOps, FieldOps};
 
     #[test]
     fn addition_works() {
@@ -227,4 +232,19 @@ mod tests {
 
         assert_eq!(res.to_array(), values.map(|v| -v));
     }
+
+    #[test]
+    fn batch_inverse_matches_scalar() {
+        const N: usize = 1 << 8;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let values: Vec<CM31> = (0..N).map(|_| rng.gen()).collect();
+        let column = values.iter().copied().collect::<CM31Column>();
+        let mut dst = CM31Column::zeros(N);
+
+        <SimdBackend as FieldOps<CM31>>::batch_inverse(&column, &mut dst);
+
+        let mut expected = vec![CM31::default(); N];
+        CM31::batch_inverse(&values, &mut expected);
+        assert_eq!(dst.to_cpu(), expected);
+    }
 }
@@ -7,6 +7,7 @@ use num_traits::Zero;
 
 use super::cm31::PackedCM31;
 use super::m31::{PackedBaseField, N_LANES};
+use super::prefix_sum::inclusive_prefix_sum;
 use super::qm31::{PackedQM31, PackedSecureField};
 use super::very_packed_m31::{VeryPackedBaseField, VeryPackedSecureField, N_VERY_PACKED_ELEMS};
 use super::SimdBackend;
@@ -45,6 +46,9 @@ pub struct BaseColumn {
 
 impl BaseColumn {
     /// Extracts a slice containing the entire vector of [`BaseField`]s.
+    ///
+    /// This doesn't copy: a [`PackedBaseField`] has the layout of its [`N_LANES`] values, so the
+    /// packed data is reinterpreted in place.
     pub fn as_slice(&self) -> &[BaseField] {
         &cast_slice(&self.data)[..self.length]
     }
@@ -73,6 +77,12 @@ impl BaseColumn {
     }
 }
 
+impl AsRef<[BaseField]> for BaseColumn {
+    fn as_ref(&self) -> &[BaseField] {
+        self.as_slice()
+    }
+}
+
 impl Column<BaseField> for BaseColumn {
     fn zeros(length: usize) -> Self {
         let data = vec![PackedBaseField::zeroed(); length.div_ceil(N_LANES)];
@@ -364,6 +374,15 @@ impl SecureColumnByCoords<SimdBackend> {
         .collect()
     }
 
+    /// Returns the inclusive prefix sum of the column, as [inclusive_prefix_sum] computes it for
+    /// each coordinate: the values are summed in `Coset` order, where the column holds
+    /// evaluations in bit-reversed `CircleDomain` order.
+    pub fn coset_order_prefix_sum(&self) -> Self {
+        SecureColumnByCoords {
+            columns: self.columns.clone().map(inclusive_prefix_sum),
+        }
+    }
+
     /// Returns a vector of `SecureColumnByCoordsMutSlice`s, each mutably owning
     /// `SECURE_EXTENSION_DEGREE` slices of `chunk_size` `PackedBaseField`s
     /// (i.e, `chuck_size` * `N_LANES` secure field elements, by coordinates).
@@ -554,6 +573,8 @@ impl VeryPackedSecureColumnByCoords {
 mod tests {
     use std::array;
 
+    use itertools::Itertools;
+    use num_traits::Zero;
     use rand::rngs::SmallRng;
     use rand::{Rng, SeedableRng};
 
@@ -561,10 +582,14 @@ mod tests {
     use crate::core::backend::simd::column::SecureColumn;
     use crate::core::backend::simd::m31::N_LANES;
     use crate::core::backend::simd::qm31::PackedQM31;
+    use crate::core::backend::simd::SimdBackend;
     use crate::core::backend::Column;
     use crate::core::fields::m31::BaseField;
     use crate::core::fields::qm31::SecureField;
     use crate::core::fields::secure_column::SecureColumnByCoords;
+    use crate::core::utils::{
+        bit_reverse, circle_domain_order_to_coset_order, coset_order_to_circle_domain_order,
+    };
 
     #[test]
     fn base_field_vec_from_iter_works() {
@@ -585,6 +610,20 @@ mod tests {
         assert_eq!(res.to_cpu(), values);
     }
 
+    #[test]
+    fn test_base_column_as_slice_matches_at() {
+        let values = (0..3 * N_LANES as u32 + 5)
+            .map(BaseField::from)
+            .collect_vec();
+        let column = values.iter().copied().collect::<BaseColumn>();
+
+        let at_values = (0..column.len()).map(|i| column.at(i)).collect_vec();
+
+        assert_eq!(column.as_slice(), at_values);
+        assert_eq!(column.to_cpu(), at_values);
+        assert_eq!(at_values, values);
+    }
+
     #[test]
     fn test_base_column_chunks_mut() {
         let values: [BaseField; N_LANES * 7] = array::from_fn(BaseField::from);
@@ -630,4 +669,31 @@ mod tests {
             );
         }
     }
+
+    #[test]
+    fn test_secure_column_by_coords_coset_order_prefix_sum() {
+        const LOG_SIZE: u32 = 7;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let values = (0..1 << LOG_SIZE).map(|_| rng.gen()).collect_vec();
+        let column = values
+            .iter()
+            .copied()
+            .collect::<SecureColumnByCoords<SimdBackend>>();
+        let mut coset_order_values = values;
+        bit_reverse(&mut coset_order_values);
+        let coset_order_values = circle_domain_order_to_coset_order(&coset_order_values);
+        let coset_order_prefix_sum = coset_order_values
+            .into_iter()
+            .scan(SecureField::zero(), |acc, v| {
+                *acc += v;
+                Some(*acc)
+            })
+            .collect_vec();
+        let mut expected = coset_order_to_circle_domain_order(&coset_order_prefix_sum);
+        bit_reverse(&mut expected);
+
+        let res = column.coset_order_prefix_sum();
+
+        assert_eq!(res.to_vec(), expected);
+    }
 }
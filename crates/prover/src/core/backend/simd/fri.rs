@@ -120,6 +120,40 @@ impl FriOps for SimdBackend {
     }
 }
 
+/// Folds many pairs of evaluations `(f(x), f(-x))` of line polynomials at once, given the inverses
+/// of their `x`. Each pair is folded as [fri::fold_line] folds a [LineEvaluation] of size 2, i.e.
+/// to `f0(x) + alpha * f1(x)`, where `2f(x) = f0(x) + x * f1(x)`.
+///
+/// Used by the FRI verifier to fold all the queries of a layer together.
+///
+/// # Panics
+///
+/// Panics if `pairs` and `x_inverses` have different lengths.
+pub fn fold_line_pairs(
+    pairs: &[[SecureField; 2]],
+    x_inverses: &[BaseField],
+    alpha: SecureField,
+) -> Vec<SecureField> {
+    assert_eq!(pairs.len(), x_inverses.len());
+    let packed_alpha = PackedSecureField::broadcast(alpha);
+    let mut res = Vec::with_capacity(pairs.len());
+    for (pairs, x_inverses) in pairs.chunks(N_LANES).zip(x_inverses.chunks(N_LANES)) {
+        let f_x = PackedSecureField::from_array(array::from_fn(|i| {
+            pairs.get(i).map_or_else(SecureField::zero, |pair| pair[0])
+        }));
+        let f_neg_x = PackedSecureField::from_array(array::from_fn(|i| {
+            pairs.get(i).map_or_else(SecureField::zero, |pair| pair[1])
+        }));
+        let x_inverse = PackedBaseField::from_array(array::from_fn(|i| {
+            x_inverses.get(i).copied().unwrap_or_else(BaseField::zero)
+        }));
+        let f0 = f_x + f_neg_x;
+        let f1 = (f_x - f_neg_x) * x_inverse;
+        res.extend_from_slice(&(f0 + packed_alpha * f1).to_array()[..pairs.len()]);
+    }
+    res
+}
+
 /// See [`decomposition_coefficient`].
 ///
 /// [`decomposition_coefficient`]: crate::core::backend::cpu::CpuBackend::decomposition_coefficient
@@ -1,35 +1,423 @@
+use std::array;
+use std::ops::Range;
+use std::simd::u32x8;
+#[cfg(feature = "parallel")]
+use std::sync::atomic::{AtomicU64, Ordering};
+
+use super::sha256::{compress_sha256_x8, SHA256_IV};
 use super::SimdBackend;
+#[cfg(any(test, feature = "testing"))]
+use crate::core::channel::DummyChannel;
 #[cfg(not(target_arch = "wasm32"))]
 use crate::core::channel::Poseidon252Channel;
-use crate::core::channel::{Channel, Sha256Channel};
+use crate::core::channel::{Sha256BatchedChannel, Sha256Channel, Sha256StreamingChannel};
 use crate::core::proof_of_work::GrindOps;
+#[cfg(any(test, feature = "testing", not(target_arch = "wasm32")))]
+use crate::core::proof_of_work::{verify_work, verify_work_with_predicate};
+use crate::core::vcs::sha256_hash::Sha256Hash;
+
+const N_SHA256_LANES: usize = 8;
 
 impl GrindOps<Sha256Channel> for SimdBackend {
-    fn grind(channel: &Sha256Channel, pow_bits: u32) -> u64 {
-        let mut nonce = 0;
-        loop {
-            let mut channel = channel.clone();
-            channel.mix_nonce(nonce);
-            if channel.trailing_zeros() >= pow_bits {
-                return nonce;
-            }
-            nonce += 1;
-        }
+    fn grind_from(
+        channel: &Sha256Channel,
+        pow_bits: u32,
+        start_nonce: u64,
+        max_iters: u64,
+    ) -> Option<u64> {
+        let digest_words: [u32; 8] = array::from_fn(|i| {
+            u32::from_be_bytes(channel.digest.0[4 * i..4 * i + 4].try_into().unwrap())
+        });
+        let nonces = start_nonce..start_nonce.saturating_add(max_iters);
+        find_min_nonce(nonces, N_SHA256_LANES as u64, |start| {
+            first_valid_sha256_nonce(&digest_words, start, |words| {
+                trailing_zeros(words) >= pow_bits
+            })
+        })
+    }
+
+    fn grind_with_predicate(
+        channel: &Sha256Channel,
+        predicate: impl Fn(&Sha256Channel) -> bool + Sync,
+    ) -> u64 {
+        let digest_words: [u32; 8] = array::from_fn(|i| {
+            u32::from_be_bytes(channel.digest.0[4 * i..4 * i + 4].try_into().unwrap())
+        });
+        find_min_nonce(0..u64::MAX, N_SHA256_LANES as u64, |start| {
+            first_valid_sha256_nonce(&digest_words, start, |words| {
+                let digest = Sha256Hash(array::from_fn(|i| words[i / 4].to_be_bytes()[i % 4]));
+                predicate(&Sha256Channel { digest })
+            })
+        })
+        .unwrap()
+    }
+}
+
+/// Nonces are mixed as in [Sha256Channel], so the search is the same.
+impl GrindOps<Sha256StreamingChannel> for SimdBackend {
+    fn grind_from(
+        channel: &Sha256StreamingChannel,
+        pow_bits: u32,
+        start_nonce: u64,
+        max_iters: u64,
+    ) -> Option<u64> {
+        <Self as GrindOps<Sha256Channel>>::grind_from(&channel.0, pow_bits, start_nonce, max_iters)
+    }
+
+    fn grind_with_predicate(
+        channel: &Sha256StreamingChannel,
+        predicate: impl Fn(&Sha256StreamingChannel) -> bool + Sync,
+    ) -> u64 {
+        <Self as GrindOps<Sha256Channel>>::grind_with_predicate(&channel.0, |channel| {
+            predicate(&Sha256StreamingChannel(channel.clone()))
+        })
     }
 }
 
-// TODO(spapini): This is a naive implementation. Optimize it.
+/// Nonces are mixed as in [Sha256Channel], so the search is the same.
+impl GrindOps<Sha256BatchedChannel> for SimdBackend {
+    fn grind_from(
+        channel: &Sha256BatchedChannel,
+        pow_bits: u32,
+        start_nonce: u64,
+        max_iters: u64,
+    ) -> Option<u64> {
+        <Self as GrindOps<Sha256Channel>>::grind_from(&channel.0, pow_bits, start_nonce, max_iters)
+    }
+
+    fn grind_with_predicate(
+        channel: &Sha256BatchedChannel,
+        predicate: impl Fn(&Sha256BatchedChannel) -> bool + Sync,
+    ) -> u64 {
+        <Self as GrindOps<Sha256Channel>>::grind_with_predicate(&channel.0, |channel| {
+            predicate(&Sha256BatchedChannel(channel.clone()))
+        })
+    }
+}
+
+/// Nonces are checked one at a time, like on the CPU, and only the search is spread over threads.
+///
+/// Checking several nonces per permutation, as the [Sha256Channel] impl does with its 8 lanes, is
+/// not supported: starknet-crypto only exposes a scalar Poseidon permutation over the 252-bit
+/// Stark field, and there is no packed implementation of that field in this crate to build one
+/// on.
 #[cfg(not(target_arch = "wasm32"))]
 impl GrindOps<Poseidon252Channel> for SimdBackend {
-    fn grind(channel: &Poseidon252Channel, pow_bits: u32) -> u64 {
-        let mut nonce = 0;
-        loop {
-            let mut channel = channel.clone();
-            channel.mix_nonce(nonce);
-            if channel.trailing_zeros() >= pow_bits {
-                return nonce;
+    fn grind_from(
+        channel: &Poseidon252Channel,
+        pow_bits: u32,
+        start_nonce: u64,
+        max_iters: u64,
+    ) -> Option<u64> {
+        let nonces = start_nonce..start_nonce.saturating_add(max_iters);
+        find_min_nonce(nonces, 1, |nonce| {
+            verify_work(channel, nonce, pow_bits).then_some(nonce)
+        })
+    }
+
+    fn grind_with_predicate(
+        channel: &Poseidon252Channel,
+        predicate: impl Fn(&Poseidon252Channel) -> bool + Sync,
+    ) -> u64 {
+        find_min_nonce(0..u64::MAX, 1, |nonce| {
+            verify_work_with_predicate(channel, nonce, &predicate).then_some(nonce)
+        })
+        .unwrap()
+    }
+}
+
+/// The channel ignores mixed nonces, so there is nothing to vectorize.
+#[cfg(any(test, feature = "testing"))]
+impl GrindOps<DummyChannel> for SimdBackend {
+    fn grind_from(
+        channel: &DummyChannel,
+        pow_bits: u32,
+        start_nonce: u64,
+        max_iters: u64,
+    ) -> Option<u64> {
+        (start_nonce..start_nonce.saturating_add(max_iters))
+            .find(|&nonce| verify_work(channel, nonce, pow_bits))
+    }
+
+    fn grind_with_predicate(
+        channel: &DummyChannel,
+        predicate: impl Fn(&DummyChannel) -> bool + Sync,
+    ) -> u64 {
+        (0..)
+            .find(|&nonce| verify_work_with_predicate(channel, nonce, &predicate))
+            .unwrap()
+    }
+}
+
+/// Returns the smallest valid nonce in `start..start + N_SHA256_LANES`, if any, for a
+/// [Sha256Channel] with the given digest words. A nonce is valid if `is_valid` holds for the
+/// big-endian words of the digest after mixing it.
+///
+/// Mixing a nonce hashes the 64 bytes `nonce (little-endian, zero padded to 32 bytes) || digest`,
+/// which takes two compressions. Each lane hashes a different nonce. Nonces past [u64::MAX] wrap
+/// around.
+fn first_valid_sha256_nonce(
+    digest_words: &[u32; 8],
+    start: u64,
+    is_valid: impl Fn([u32; 8]) -> bool,
+) -> Option<u64> {
+    let nonces: [u64; N_SHA256_LANES] = array::from_fn(|i| start.wrapping_add(i as u64));
+
+    let mut block = [u32x8::splat(0); 16];
+    block[0] = u32x8::from_array(nonces.map(|nonce| (nonce as u32).swap_bytes()));
+    block[1] = u32x8::from_array(nonces.map(|nonce| ((nonce >> 32) as u32).swap_bytes()));
+    for (word, &digest_word) in block[8..].iter_mut().zip(digest_words) {
+        *word = u32x8::splat(digest_word);
+    }
+    let state = compress_sha256_x8(SHA256_IV.map(u32x8::splat), block);
+
+    // Padding block of a 64 bytes message.
+    let mut padding = [u32x8::splat(0); 16];
+    padding[0] = u32x8::splat(0x80000000);
+    padding[15] = u32x8::splat(512);
+    let state = compress_sha256_x8(state, padding);
+
+    (0..N_SHA256_LANES)
+        .find(|&lane| is_valid(state.map(|word| word[lane])))
+        .map(|lane| nonces[lane])
+}
+
+/// Computes [Sha256Channel]'s `trailing_zeros` from the big-endian digest words.
+fn trailing_zeros(digest_words: [u32; 8]) -> u32 {
+    let mut n_bits = 0;
+    for word in digest_words.iter().rev() {
+        // The channel counts from the last byte, so the bytes of each word are reversed.
+        let word = word.swap_bytes();
+        if word == 0 {
+            n_bits += 32;
+        } else {
+            n_bits += word.leading_zeros();
+            break;
+        }
+    }
+    n_bits
+}
+
+/// Returns the smallest valid nonce in `nonces`, if any.
+///
+/// `nonces` is split into consecutive blocks of `block_size` nonces. `check_block(start)` returns
+/// the smallest valid nonce in `start..start + block_size`, if any. The last block may exceed
+/// `nonces`, so its result is checked to be in range.
+#[cfg(not(feature = "parallel"))]
+fn find_min_nonce(
+    nonces: Range<u64>,
+    block_size: u64,
+    check_block: impl Fn(u64) -> Option<u64>,
+) -> Option<u64> {
+    let mut start = nonces.start;
+    while start < nonces.end {
+        if let Some(nonce) = check_block(start) {
+            return nonces.contains(&nonce).then_some(nonce);
+        }
+        start = start.checked_add(block_size)?;
+    }
+    None
+}
+
+/// Returns the smallest valid nonce in `nonces`, if any.
+///
+/// `nonces` is split into consecutive blocks of `block_size` nonces. `check_block(start)` returns
+/// the smallest valid nonce in `start..start + block_size`, if any. The last block may exceed
+/// `nonces`, so its result is checked to be in range.
+///
+/// Blocks are split into one stripe per thread: thread `i` checks blocks `i`, `i + n_threads`,
+/// etc. The smallest valid nonce is returned, so the result doesn't depend on the number of
+/// threads.
+#[cfg(feature = "parallel")]
+fn find_min_nonce(
+    nonces: Range<u64>,
+    block_size: u64,
+    check_block: impl Fn(u64) -> Option<u64> + Sync,
+) -> Option<u64> {
+    // `nonces.end` is never a valid nonce, so it marks that none was found.
+    let best_nonce = AtomicU64::new(nonces.end);
+    rayon::broadcast(|ctx| {
+        let stride = ctx.num_threads() as u64 * block_size;
+        let mut start = nonces.start.checked_add(ctx.index() as u64 * block_size);
+        // Each stripe is scanned in increasing order, so a thread can stop as soon as its blocks
+        // pass the best nonce found so far.
+        while let Some(block_start) = start.filter(|&s| s < best_nonce.load(Ordering::Relaxed)) {
+            if let Some(nonce) = check_block(block_start) {
+                if nonces.contains(&nonce) {
+                    best_nonce.fetch_min(nonce, Ordering::Relaxed);
+                }
+                return;
             }
-            nonce += 1;
+            start = block_start.checked_add(stride);
+        }
+    });
+    let best_nonce = best_nonce.into_inner();
+    (best_nonce < nonces.end).then_some(best_nonce)
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::backend::simd::SimdBackend;
+    use crate::core::backend::CpuBackend;
+    #[cfg(not(target_arch = "wasm32"))]
+    use crate::core::channel::Poseidon252Channel;
+    use crate::core::channel::{
+        Channel, Sha256BatchedChannel, Sha256Channel, Sha256StreamingChannel,
+    };
+    use crate::core::fields::qm31::SecureField;
+    use crate::core::proof_of_work::{GrindOps, PowDirection};
+    use crate::m31;
+
+    #[test]
+    fn test_grind_matches_sequential() {
+        let mut channel = Sha256Channel::default();
+        channel.mix_felts(&[SecureField::from(m31!(1923782))]);
+
+        for pow_bits in [0, 1, 5, 10, 12] {
+            let nonce = <SimdBackend as GrindOps<Sha256Channel>>::grind(&channel, pow_bits);
+
+            let expected = <CpuBackend as GrindOps<Sha256Channel>>::grind(&channel, pow_bits);
+            assert_eq!(nonce, expected);
+        }
+    }
+
+    #[test]
+    fn test_grind_from_matches_sequential() {
+        let mut channel = Sha256Channel::default();
+        channel.mix_felts(&[SecureField::from(m31!(1923782))]);
+        let pow_bits = 8;
+        let nonce = <SimdBackend as GrindOps<Sha256Channel>>::grind(&channel, pow_bits);
+
+        for (start_nonce, max_iters) in [(nonce, 1), (nonce + 1, u64::MAX), (3, nonce - 3), (5, 11)]
+        {
+            let result = <SimdBackend as GrindOps<Sha256Channel>>::grind_from(
+                &channel,
+                pow_bits,
+                start_nonce,
+                max_iters,
+            );
+
+            let expected = <CpuBackend as GrindOps<Sha256Channel>>::grind_from(
+                &channel,
+                pow_bits,
+                start_nonce,
+                max_iters,
+            );
+            assert_eq!(result, expected);
+        }
+    }
+
+    #[test]
+    fn test_grind_from_gives_up() {
+        let channel = Sha256Channel::default();
+
+        let result = <SimdBackend as GrindOps<Sha256Channel>>::grind_from(&channel, 40, 0, 100);
+
+        assert_eq!(result, None);
+        assert_eq!(
+            <SimdBackend as GrindOps<Sha256Channel>>::grind_from(&channel, 0, u64::MAX - 3, 3),
+            Some(u64::MAX - 3)
+        );
+    }
+
+    #[test]
+    fn test_grind_with_predicate_matches_sequential() {
+        let mut channel = Sha256Channel::default();
+        channel.mix_felts(&[SecureField::from(m31!(1923782))]);
+        let below_target = |channel: &Sha256Channel| {
+            let digest: [u8; 32] = channel.digest().into();
+            u32::from_le_bytes(digest[28..].try_into().unwrap()) < 3 << 22
+        };
+
+        let nonce =
+            <SimdBackend as GrindOps<Sha256Channel>>::grind_with_predicate(&channel, below_target);
+
+        let expected =
+            <CpuBackend as GrindOps<Sha256Channel>>::grind_with_predicate(&channel, below_target);
+        assert_eq!(nonce, expected);
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_poseidon252_grind_from_gives_up() {
+        let result = <SimdBackend as GrindOps<Poseidon252Channel>>::grind_from(
+            &Poseidon252Channel::default(),
+            40,
+            0,
+            100,
+        );
+
+        assert_eq!(result, None);
+    }
+
+    /// Both backends hash one nonce per permutation, so this only checks that the parallel search
+    /// finds the smallest valid nonce.
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_poseidon252_parallel_grind_matches_cpu() {
+        let mut channel = Poseidon252Channel::default();
+        channel.mix_felts(&[SecureField::from(m31!(1923782))]);
+
+        for pow_bits in 0..=12 {
+            let nonce = <SimdBackend as GrindOps<Poseidon252Channel>>::grind(&channel, pow_bits);
+
+            let expected = <CpuBackend as GrindOps<Poseidon252Channel>>::grind(&channel, pow_bits);
+            assert_eq!(nonce, expected);
+        }
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_poseidon252_parallel_grind_with_predicate_matches_cpu() {
+        let mut channel = Poseidon252Channel::default();
+        channel.mix_felts(&[SecureField::from(m31!(1923782))]);
+        let predicate = |channel: &Poseidon252Channel| channel.leading_zeros() >= 6;
+
+        let nonce = <SimdBackend as GrindOps<Poseidon252Channel>>::grind_with_predicate(
+            &channel, predicate,
+        );
+
+        let expected =
+            <CpuBackend as GrindOps<Poseidon252Channel>>::grind_with_predicate(&channel, predicate);
+        assert_eq!(nonce, expected);
+    }
+
+    #[test]
+    fn test_streaming_channel_grind_matches_sequential() {
+        let mut channel = Sha256StreamingChannel::default();
+        channel.mix_felts(&[SecureField::from(m31!(1923782))]);
+
+        let nonce = <SimdBackend as GrindOps<Sha256StreamingChannel>>::grind(&channel, 10);
+
+        let expected = <CpuBackend as GrindOps<Sha256StreamingChannel>>::grind(&channel, 10);
+        assert_eq!(nonce, expected);
+    }
+
+    #[test]
+    fn test_batched_channel_grind_matches_sequential() {
+        let mut channel = Sha256BatchedChannel::default();
+        channel.mix_felts(&[SecureField::from(m31!(1923782))]);
+
+        let nonce = <SimdBackend as GrindOps<Sha256BatchedChannel>>::grind(&channel, 10);
+
+        let expected = <CpuBackend as GrindOps<Sha256BatchedChannel>>::grind(&channel, 10);
+        assert_eq!(nonce, expected);
+    }
+
+    #[test]
+    fn test_grind_in_direction_matches_sequential() {
+        let mut channel = Sha256Channel::default();
+        channel.mix_felts(&[SecureField::from(m31!(1923782))]);
+
+        for direction in [PowDirection::Leading, PowDirection::Trailing] {
+            let nonce = <SimdBackend as GrindOps<Sha256Channel>>::grind_in_direction(
+                &channel, 10, direction,
+            );
+
+            let expected = <CpuBackend as GrindOps<Sha256Channel>>::grind_in_direction(
+                &channel, 10, direction,
+            );
+            assert_eq!(nonce, expected);
         }
     }
 }
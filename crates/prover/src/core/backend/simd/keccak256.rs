@@ -0,0 +1,77 @@
+use itertools::Itertools;
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
+use crate::core::backend::simd::column::BaseColumn;
+use crate::core::backend::simd::SimdBackend;
+use crate::core::backend::ColumnOps;
+use crate::core::utils::bit_reverse;
+use crate::core::vcs::keccak256_merkle::{Keccak256Hash, Keccak256MerkleHasher};
+use crate::core::vcs::ops::{MerkleHasher, MerkleOps};
+
+impl ColumnOps<Keccak256Hash> for SimdBackend {
+    type Column = Vec<Keccak256Hash>;
+
+    fn bit_reverse_column(column: &mut Self::Column) {
+        bit_reverse(column)
+    }
+}
+
+// TODO(BWS): not simd at all
+impl MerkleOps<Keccak256MerkleHasher> for SimdBackend {
+    fn commit_on_layer(
+        log_size: u32,
+        prev_layer: Option<&Vec<Keccak256Hash>>,
+        columns: &[&BaseColumn],
+    ) -> Vec<Keccak256Hash> {
+        let columns = columns.iter().map(|column| column.as_slice()).collect_vec();
+
+        #[cfg(not(feature = "parallel"))]
+        let iter = 0..1 << log_size;
+
+        #[cfg(feature = "parallel")]
+        let iter = (0..1 << log_size).into_par_iter();
+
+        iter.map(|i| {
+            Keccak256MerkleHasher::hash_tagged_node(
+                prev_layer.map(|prev_layer| (prev_layer[2 * i], prev_layer[2 * i + 1])),
+                &columns.iter().map(|column| column[i]).collect_vec(),
+            )
+        })
+        .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::core::backend::simd::column::BaseColumn;
+    use crate::core::backend::simd::SimdBackend;
+    use crate::core::backend::CpuBackend;
+    use crate::core::fields::m31::BaseField;
+    use crate::core::vcs::keccak256_merkle::Keccak256MerkleHasher;
+    use crate::core::vcs::prover::MerkleProver;
+
+    #[test]
+    fn test_commit_matches_cpu() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cols = [7, 5, 5, 4, 0]
+            .map(|log_size| (0..1 << log_size).map(|_| rng.gen()).collect_vec())
+            .to_vec();
+        let simd_cols: Vec<BaseColumn> = cols
+            .iter()
+            .map(|column| column.iter().copied().collect())
+            .collect_vec();
+
+        let merkle =
+            MerkleProver::<SimdBackend, Keccak256MerkleHasher>::commit(simd_cols.iter().collect());
+
+        let expected = MerkleProver::<CpuBackend, Keccak256MerkleHasher>::commit(
+            cols.iter().collect::<Vec<&Vec<BaseField>>>(),
+        );
+        assert_eq!(merkle.layers, expected.layers);
+    }
+}
@@ -1,7 +1,13 @@
 use serde::{Deserialize, Serialize};
 
 use super::{Backend, BackendForChannel};
-use crate::core::vcs::sha256_merkle::Sha256MerkleChannel;
+#[cfg(any(test, feature = "testing"))]
+use crate::core::channel::DummyMerkleChannel;
+#[cfg(feature = "keccak")]
+use crate::core::vcs::keccak256_merkle::Keccak256MerkleChannel;
+use crate::core::vcs::sha256_merkle::{
+    Sha256BatchedMerkleChannel, Sha256MerkleChannel, Sha256StreamingMerkleChannel,
+};
 
 pub mod accumulation;
 pub mod bit_reverse;
@@ -12,6 +18,8 @@ pub mod domain;
 pub mod fft;
 pub mod fri;
 mod grind;
+#[cfg(feature = "keccak")]
+pub mod keccak256;
 pub mod lookups;
 pub mod m31;
 pub mod prefix_sum;
@@ -26,3 +34,9 @@ pub struct SimdBackend;
 
 impl Backend for SimdBackend {}
 impl BackendForChannel<Sha256MerkleChannel> for SimdBackend {}
+impl BackendForChannel<Sha256StreamingMerkleChannel> for SimdBackend {}
+impl BackendForChannel<Sha256BatchedMerkleChannel> for SimdBackend {}
+#[cfg(feature = "keccak")]
+impl BackendForChannel<Keccak256MerkleChannel> for SimdBackend {}
+#[cfg(any(test, feature = "testing"))]
+impl BackendForChannel<DummyMerkleChannel> for SimdBackend {}
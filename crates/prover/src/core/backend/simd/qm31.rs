@@ -320,7 +320,12 @@ mod tests {
     use rand::rngs::SmallRng;
     use rand::{Rng, SeedableRng};
 
+    use crate::core::backend::simd::column::SecureColumn;
     use crate::core::backend::simd::qm31::PackedQM31;
+    use crate::core::backend::simd::SimdBackend;
+    use crate::core::backend::Column;
+    use crate::core::fields::qm31::SecureField;
+    use crate::core::fields::{FieldExpOps, FieldOps};
 
     #[test]
     fn addition_works() {
@@ -371,4 +376,19 @@ mod tests {
 
         assert_eq!(res.to_array(), values.map(|v| -v));
     }
+
+    #[test]
+    fn batch_inverse_matches_scalar() {
+        const N: usize = 1 << 8;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let values: Vec<SecureField> = (0..N).map(|_| rng.gen()).collect();
+        let column = values.iter().copied().collect::<SecureColumn>();
+        let mut dst = SecureColumn::zeros(N);
+
+        <SimdBackend as FieldOps<SecureField>>::batch_inverse(&column, &mut dst);
+
+        let mut expected = vec![SecureField::default(); N];
+        SecureField::batch_inverse(&values, &mut expected);
+        assert_eq!(dst.to_cpu(), expected);
+    }
 }
@@ -11,16 +11,18 @@ use crate::core::backend::cpu::quotients::{
     batch_random_coeffs, column_line_coeffs, BatchCoeff, LineCoeffs,
 };
 use crate::core::backend::simd::domain::CircleDomainBitRevIterator;
-use crate::core::backend::{Backend, Col, Column, ColumnOps};
+use crate::core::backend::{Backend, Col, Column, ColumnOps, CpuBackend};
 use crate::core::fields::cm31::CM31;
 use crate::core::fields::m31::BaseField;
 use crate::core::fields::qm31::SecureField;
 use crate::core::fields::secure_column::{SecureColumnByCoords, SECURE_EXTENSION_DEGREE};
 use crate::core::fields::{ComplexOf, FieldExpOps};
-use crate::core::pcs::quotients::{ColumnSampleBatch, QuotientOps};
+use crate::core::pcs::quotients::{
+    ColumnSampleBatch, MultiPointSampleBatch, QuotientError, QuotientOps,
+};
 use crate::core::poly::circle::{CircleDomain, CircleEvaluation, PolyOps, SecureEvaluation};
 use crate::core::poly::BitReversedOrder;
-use crate::core::utils::{bit_reverse, bit_reverse_index};
+use crate::core::utils::bit_reverse;
 
 /// Holds the precomputed constant values used in each quotient evaluation.
 pub struct QuotientConstants<B: Backend + ColumnOps<CM31>> {
@@ -88,6 +90,31 @@ impl QuotientOps for SimdBackend {
             values: extended_eval,
         }
     }
+
+    /// Computed on the CPU: multi-point numerators aren't vectorized yet.
+    fn accumulate_multi_point_quotients(
+        domain: CircleDomain,
+        columns: &[&CircleEvaluation<Self, BaseField, BitReversedOrder>],
+        random_coeff: SecureField,
+        sample_batches: &[MultiPointSampleBatch],
+        max_numerator_degree: usize,
+    ) -> Result<SecureEvaluation<Self>, QuotientError> {
+        let cpu_columns = columns
+            .iter()
+            .map(|column| CircleEvaluation::new(column.domain, column.values.to_cpu()))
+            .collect_vec();
+        let cpu_quotients = CpuBackend::accumulate_multi_point_quotients(
+            domain,
+            &cpu_columns.iter().collect_vec(),
+            random_coeff,
+            sample_batches,
+            max_numerator_degree,
+        )?;
+        Ok(SecureEvaluation {
+            domain,
+            values: cpu_quotients.values.to_vec().into_iter().collect(),
+        })
+    }
 }
 
 fn accumulate_quotients_on_subdomain(
@@ -215,10 +242,17 @@ fn packed_pair_vanishing(
     cross_term + packed_p.0 - d * packed_p.1
 }
 
+/// Computes the inverses of the quotient denominators of each sample batch over `domain`, in
+/// bit-reversed order. Matches [crate::core::backend::cpu::quotients::denominator_inverses].
+///
+/// The packed domain points are computed once and shared by all sample batches, and all the
+/// denominators are inverted in a single packed batch inversion.
 fn denominator_inverses(
     sample_batches: &[ColumnSampleBatch],
     domain: CircleDomain,
 ) -> Vec<CM31Column> {
+    let domain_points = CircleDomainBitRevIterator::new(domain).collect_vec();
+
     // We want a P to be on a line that passes through a point Pr + uPi in QM31^2, and its conjugate
     // Pr - uPi. Thus, Pr - P is parallel to Pi. Or, (Pr - P).x * Pi.y - (Pr - P).y * Pi.x = 0.
     let flat_denominators: CM31Column = sample_batches
@@ -230,21 +264,9 @@ fn denominator_inverses(
             );
             let d = PackedCM31::broadcast(d);
 
-            (0..(1 << (domain.log_size() - LOG_N_LANES)))
-                .map(|vec_row| {
-                    // TODO(spapini): Optimize this, for the small number of columns case.
-                    let points = std::array::from_fn(|i| {
-                        domain.at(bit_reverse_index(
-                            (vec_row << LOG_N_LANES) + i,
-                            domain.log_size(),
-                        ))
-                    });
-                    let domain_points_x = PackedBaseField::from_array(points.map(|p| p.x));
-                    let domain_points_y = PackedBaseField::from_array(points.map(|p| p.y));
-                    let domain_point_vec = (domain_points_x, domain_points_y);
-                    packed_pair_vanishing(d, cross_term, domain_point_vec)
-                })
-                .collect_vec()
+            domain_points
+                .iter()
+                .map(move |point| packed_pair_vanishing(d, cross_term, (point.x, point.y)))
         })
         .collect();
 
@@ -284,13 +306,49 @@ mod tests {
 
     use crate::core::backend::simd::column::BaseColumn;
     use crate::core::backend::simd::SimdBackend;
-    use crate::core::backend::{Column, CpuBackend};
+    use crate::core::backend::{cpu, Column, CpuBackend};
     use crate::core::circle::SECURE_FIELD_CIRCLE_GEN;
     use crate::core::fields::m31::BaseField;
-    use crate::core::pcs::quotients::{ColumnSampleBatch, QuotientOps};
-    use crate::core::poly::circle::{CanonicCoset, CircleEvaluation};
+    use crate::core::pcs::quotients::{ColumnSampleBatch, MultiPointSampleBatch, QuotientOps};
+    use crate::core::poly::circle::{CanonicCoset, CircleEvaluation, CirclePoly, SecureEvaluation};
     use crate::core::poly::BitReversedOrder;
-    use crate::qm31;
+    use crate::{m31, qm31};
+
+    #[test]
+    fn test_accumulate_multi_point_quotients_matches_cpu() {
+        const LOG_SIZE: u32 = 6;
+        let domain = CanonicCoset::new(LOG_SIZE).circle_domain();
+        let values = (0..1 << LOG_SIZE).map(|i| m31!(i * i)).collect_vec();
+        let column = CircleEvaluation::<SimdBackend, _, BitReversedOrder>::new(
+            domain,
+            values.iter().copied().collect(),
+        );
+        let cpu_column = CircleEvaluation::<CpuBackend, _, BitReversedOrder>::new(domain, values);
+        let sample_batches = [MultiPointSampleBatch {
+            points: vec![SECURE_FIELD_CIRCLE_GEN, SECURE_FIELD_CIRCLE_GEN.mul(5)],
+            columns_and_values: vec![(0, vec![qm31!(1, 2, 3, 4), qm31!(5, 6, 7, 8)])],
+        }];
+        let random_coeff = qm31!(1, 2, 3, 4);
+
+        let res = SimdBackend::accumulate_multi_point_quotients(
+            domain,
+            &[&column],
+            random_coeff,
+            &sample_batches,
+            3,
+        )
+        .unwrap();
+
+        let cpu_res = CpuBackend::accumulate_multi_point_quotients(
+            domain,
+            &[&cpu_column],
+            random_coeff,
+            &sample_batches,
+            3,
+        )
+        .unwrap();
+        assert_eq!(res.values.to_vec(), cpu_res.values.to_vec());
+    }
 
     #[test]
     fn test_accumulate_quotients() {
@@ -347,4 +405,52 @@ mod tests {
 
         assert_eq!(res, cpu_result);
     }
+
+    #[test]
+    fn test_quotients_are_low_degree() {
+        const LOG_SIZE: u32 = 7;
+        const LOG_BLOWUP_FACTOR: u32 = 1;
+        let polynomial =
+            CirclePoly::<SimdBackend>::new((0..1 << LOG_SIZE).map(|i| m31!(i)).collect());
+        let eval_domain = CanonicCoset::new(LOG_SIZE + 1).circle_domain();
+        let eval = polynomial.evaluate(eval_domain);
+        let point = SECURE_FIELD_CIRCLE_GEN;
+        let value = polynomial.eval_at_point(point);
+        let coeff = qm31!(1, 2, 3, 4);
+        let quot_eval = SimdBackend::accumulate_quotients(
+            eval_domain,
+            &[&eval],
+            coeff,
+            &[ColumnSampleBatch {
+                point,
+                columns_and_values: vec![(0, value)],
+            }],
+            LOG_BLOWUP_FACTOR,
+        );
+
+        let cpu_quot_eval = SecureEvaluation {
+            domain: eval_domain,
+            values: quot_eval.values.to_cpu(),
+        };
+        assert!(cpu_quot_eval.is_in_fri_space(LOG_SIZE));
+    }
+
+    #[test]
+    fn test_denominator_inverses_match_cpu() {
+        let domain = CanonicCoset::new(7).circle_domain();
+        let sample_batches = (1..4)
+            .map(|i| ColumnSampleBatch {
+                point: SECURE_FIELD_CIRCLE_GEN.mul(i),
+                columns_and_values: vec![],
+            })
+            .collect_vec();
+
+        let denominator_inverses = super::denominator_inverses(&sample_batches, domain)
+            .into_iter()
+            .map(|column| column.to_cpu())
+            .collect_vec();
+
+        let expected = cpu::quotients::denominator_inverses(&sample_batches, domain);
+        assert_eq!(denominator_inverses, expected);
+    }
 }
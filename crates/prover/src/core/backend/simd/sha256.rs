@@ -1,10 +1,13 @@
+use std::simd::u32x8;
+
 use itertools::Itertools;
 #[cfg(feature = "parallel")]
 use rayon::prelude::*;
 
 use crate::core::backend::simd::column::BaseColumn;
 use crate::core::backend::simd::SimdBackend;
-use crate::core::backend::{Column, ColumnOps};
+use crate::core::backend::ColumnOps;
+use crate::core::utils::bit_reverse;
 use crate::core::vcs::ops::{MerkleHasher, MerkleOps};
 use crate::core::vcs::sha256_hash::Sha256Hash;
 use crate::core::vcs::sha256_merkle::Sha256MerkleHasher;
@@ -12,8 +15,8 @@ use crate::core::vcs::sha256_merkle::Sha256MerkleHasher;
 impl ColumnOps<Sha256Hash> for SimdBackend {
     type Column = Vec<Sha256Hash>;
 
-    fn bit_reverse_column(_column: &mut Self::Column) {
-        unimplemented!()
+    fn bit_reverse_column(column: &mut Self::Column) {
+        bit_reverse(column)
     }
 }
 
@@ -24,6 +27,9 @@ impl MerkleOps<Sha256MerkleHasher> for SimdBackend {
         prev_layer: Option<&Vec<Sha256Hash>>,
         columns: &[&BaseColumn],
     ) -> Vec<Sha256Hash> {
+        // Borrow the values of each column, instead of unpacking them one at a time.
+        let columns = columns.iter().map(|column| column.as_slice()).collect_vec();
+
         #[cfg(not(feature = "parallel"))]
         let iter = 0..1 << log_size;
 
@@ -31,11 +37,127 @@ impl MerkleOps<Sha256MerkleHasher> for SimdBackend {
         let iter = (0..1 << log_size).into_par_iter();
 
         iter.map(|i| {
-            Sha256MerkleHasher::hash_node(
+            Sha256MerkleHasher::hash_tagged_node(
                 prev_layer.map(|prev_layer| (prev_layer[2 * i], prev_layer[2 * i + 1])),
-                &columns.iter().map(|column| column.at(i)).collect_vec(),
+                &columns.iter().map(|column| column[i]).collect_vec(),
             )
         })
         .collect()
     }
 }
+
+/// SHA-256 initial hash values.
+pub const SHA256_IV: [u32; 8] = [
+    0x6a09e667, 0xbb67ae85, 0x3c6ef372, 0xa54ff53a, 0x510e527f, 0x9b05688c, 0x1f83d9ab, 0x5be0cd19,
+];
+
+/// SHA-256 round constants.
+const SHA256_K: [u32; 64] = [
+    0x428a2f98, 0x71374491, 0xb5c0fbcf, 0xe9b5dba5, 0x3956c25b, 0x59f111f1, 0x923f82a4, 0xab1c5ed5,
+    0xd807aa98, 0x12835b01, 0x243185be, 0x550c7dc3, 0x72be5d74, 0x80deb1fe, 0x9bdc06a7, 0xc19bf174,
+    0xe49b69c1, 0xefbe4786, 0x0fc19dc6, 0x240ca1cc, 0x2de92c6f, 0x4a7484aa, 0x5cb0a9dc, 0x76f988da,
+    0x983e5152, 0xa831c66d, 0xb00327c8, 0xbf597fc7, 0xc6e00bf3, 0xd5a79147, 0x06ca6351, 0x14292967,
+    0x27b70a85, 0x2e1b2138, 0x4d2c6dfc, 0x53380d13, 0x650a7354, 0x766a0abb, 0x81c2c92e, 0x92722c85,
+    0xa2bfe8a1, 0xa81a664b, 0xc24b8b70, 0xc76c51a3, 0xd192e819, 0xd6990624, 0xf40e3585, 0x106aa070,
+    0x19a4c116, 0x1e376c08, 0x2748774c, 0x34b0bcb5, 0x391c0cb3, 0x4ed8aa4a, 0x5b9cca4f, 0x682e6ff3,
+    0x748f82ee, 0x78a5636f, 0x84c87814, 0x8cc70208, 0x90befffa, 0xa4506ceb, 0xbef9a3f7, 0xc67178f2,
+];
+
+fn rotate_right(x: u32x8, n: u32) -> u32x8 {
+    (x >> n) | (x << (32 - n))
+}
+
+/// Applies the SHA-256 compression function to 8 independent states at once.
+/// Lane `i` of the result is the compression of lane `i` of `state` with lane `i` of `block`, where
+/// `block` holds the 16 big-endian message words.
+pub fn compress_sha256_x8(state: [u32x8; 8], block: [u32x8; 16]) -> [u32x8; 8] {
+    let mut w = [u32x8::splat(0); 64];
+    w[..16].copy_from_slice(&block);
+    for i in 16..64 {
+        let s0 = rotate_right(w[i - 15], 7) ^ rotate_right(w[i - 15], 18) ^ (w[i - 15] >> 3);
+        let s1 = rotate_right(w[i - 2], 17) ^ rotate_right(w[i - 2], 19) ^ (w[i - 2] >> 10);
+        w[i] = w[i - 16] + s0 + w[i - 7] + s1;
+    }
+
+    let [mut a, mut b, mut c, mut d, mut e, mut f, mut g, mut h] = state;
+    for i in 0..64 {
+        let s1 = rotate_right(e, 6) ^ rotate_right(e, 11) ^ rotate_right(e, 25);
+        let ch = (e & f) ^ (!e & g);
+        let t1 = h + s1 + ch + u32x8::splat(SHA256_K[i]) + w[i];
+        let s0 = rotate_right(a, 2) ^ rotate_right(a, 13) ^ rotate_right(a, 22);
+        let maj = (a & b) ^ (a & c) ^ (b & c);
+        let t2 = s0 + maj;
+        h = g;
+        g = f;
+        f = e;
+        e = d + t1;
+        d = c;
+        c = b;
+        b = a;
+        a = t1 + t2;
+    }
+
+    let [s0, s1, s2, s3, s4, s5, s6, s7] = state;
+    [
+        s0 + a,
+        s1 + b,
+        s2 + c,
+        s3 + d,
+        s4 + e,
+        s5 + f,
+        s6 + g,
+        s7 + h,
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use std::array;
+    use std::simd::u32x8;
+
+    use itertools::Itertools;
+
+    use super::{compress_sha256_x8, SHA256_IV};
+    use crate::core::backend::simd::SimdBackend;
+    use crate::core::backend::ColumnOps;
+    use crate::core::utils::bit_reverse_index;
+    use crate::core::vcs::sha256_hash::{Sha256Hash, Sha256Hasher};
+
+    #[test]
+    fn test_bit_reverse_hash_column() {
+        const LOG_SIZE: u32 = 4;
+        let column = (0..1 << LOG_SIZE)
+            .map(|i: u32| Sha256Hasher::hash(&i.to_le_bytes()))
+            .collect_vec();
+        let mut reversed = column.clone();
+
+        <SimdBackend as ColumnOps<Sha256Hash>>::bit_reverse_column(&mut reversed);
+
+        for (i, hash) in reversed.iter().enumerate() {
+            assert_eq!(*hash, column[bit_reverse_index(i, LOG_SIZE)]);
+        }
+        <SimdBackend as ColumnOps<Sha256Hash>>::bit_reverse_column(&mut reversed);
+        assert_eq!(reversed, column);
+    }
+
+    #[test]
+    fn test_compress_sha256_x8() {
+        // 8 messages of 55 bytes, so that each one fits in a single padded block.
+        let messages: [[u8; 55]; 8] = array::from_fn(|i| array::from_fn(|j| (i * 55 + j) as u8));
+        let blocks: [[u32; 16]; 8] = messages.map(|message| {
+            let mut padded = [0u8; 64];
+            padded[..55].copy_from_slice(&message);
+            padded[55] = 0x80;
+            padded[56..].copy_from_slice(&(55u64 * 8).to_be_bytes());
+            array::from_fn(|i| u32::from_be_bytes(padded[4 * i..4 * i + 4].try_into().unwrap()))
+        });
+        let block = array::from_fn(|i| u32x8::from_array(blocks.map(|b| b[i])));
+
+        let state = compress_sha256_x8(SHA256_IV.map(u32x8::splat), block);
+
+        for (lane, message) in messages.iter().enumerate() {
+            let digest = state.map(|word| word[lane].to_be_bytes()).concat();
+            assert_eq!(digest, Vec::<u8>::from(Sha256Hasher::hash(message)));
+        }
+    }
+}
@@ -0,0 +1,134 @@
+use std::cell::RefCell;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::sync::Arc;
+
+thread_local! {
+    /// The counter that hashes computed on this thread are added to, if any.
+    static CURRENT_COUNTER: RefCell<Option<HashCounter>> = const { RefCell::new(None) };
+}
+
+/// A count of the hash function invocations of a computation, for measuring the cost of a proof.
+///
+/// Hashes are counted where they are computed: every hash of a channel operation, one hash per
+/// node of a committed Merkle tree, whatever the backend, and one hash per Merkle root mixed into
+/// a channel. Proof of work attempts are not counted.
+///
+/// The count is shared between clones of the counter, so work spread over several threads can
+/// be counted by running it with [HashCounter::count] on each of them.
+#[derive(Clone, Debug, Default)]
+pub struct HashCounter(Arc<AtomicUsize>);
+
+impl HashCounter {
+    /// Runs `f`, adding the hashes it computes on this thread to the counter.
+    pub fn count<R>(&self, f: impl FnOnce() -> R) -> R {
+        with_counter(Some(self.clone()), f)
+    }
+
+    /// Returns the counter that hashes computed on this thread are added to, if any.
+    pub fn current() -> Option<Self> {
+        CURRENT_COUNTER.with(|counter| counter.borrow().clone())
+    }
+
+    /// Returns the number of hashes counted so far.
+    pub fn n_hashes(&self) -> usize {
+        self.0.load(Ordering::Relaxed)
+    }
+}
+
+/// Adds `n_hashes` to the counter of this thread, if any.
+pub(crate) fn add_hashes(n_hashes: usize) {
+    CURRENT_COUNTER.with(|counter| {
+        if let Some(counter) = &*counter.borrow() {
+            counter.0.fetch_add(n_hashes, Ordering::Relaxed);
+        }
+    });
+}
+
+/// Runs `f` without counting the hashes it computes on this thread.
+pub(crate) fn uncounted<R>(f: impl FnOnce() -> R) -> R {
+    with_counter(None, f)
+}
+
+/// Runs `f` with `counter` as the counter of this thread, restoring the previous one afterwards,
+/// even if `f` panics.
+fn with_counter<R>(counter: Option<HashCounter>, f: impl FnOnce() -> R) -> R {
+    struct RestoreCounter(Option<HashCounter>);
+
+    impl Drop for RestoreCounter {
+        fn drop(&mut self) {
+            CURRENT_COUNTER.with(|counter| *counter.borrow_mut() = self.0.take());
+        }
+    }
+
+    let _restore = RestoreCounter(CURRENT_COUNTER.with(|current| current.replace(counter)));
+    f()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{uncounted, HashCounter};
+    use crate::core::backend::simd::column::BaseColumn;
+    use crate::core::backend::simd::SimdBackend;
+    use crate::core::backend::CpuBackend;
+    use crate::core::channel::{Channel, Sha256BatchedChannel, Sha256Channel};
+    use crate::core::fields::qm31::SecureField;
+    use crate::core::vcs::prover::MerkleProver;
+    use crate::core::vcs::sha256_merkle::Sha256MerkleHasher;
+    use crate::m31;
+
+    #[test]
+    fn test_channel_hashes_are_counted() {
+        let counter = HashCounter::default();
+        let mut channel = Sha256Channel::default();
+        let mut batched_channel = Sha256BatchedChannel::default();
+
+        counter.count(|| channel.mix_felts(&[SecureField::from(m31!(1))]));
+        assert_eq!(counter.n_hashes(), 2);
+        // Each felt costs a squeeze of two hashes.
+        counter.count(|| channel.draw_felts(3));
+        assert_eq!(counter.n_hashes(), 8);
+        // Two blocks of two felts, and the digest advance.
+        counter.count(|| batched_channel.draw_felts(3));
+        assert_eq!(counter.n_hashes(), 11);
+        channel.draw_felt();
+        uncounted(|| counter.count(|| uncounted(|| channel.draw_felt())));
+        assert_eq!(counter.n_hashes(), 11);
+        assert!(HashCounter::current().is_none());
+    }
+
+    #[test]
+    fn test_merkle_hashes_are_counted_on_every_backend() {
+        let cols = [vec![m31!(0); 1 << 4], vec![m31!(1); 1 << 2]];
+        let simd_cols = cols
+            .clone()
+            .map(|col| col.into_iter().collect::<BaseColumn>());
+        let counter = HashCounter::default();
+        let simd_counter = HashCounter::default();
+
+        counter.count(|| {
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(cols.iter().collect())
+        });
+        simd_counter.count(|| {
+            MerkleProver::<SimdBackend, Sha256MerkleHasher>::commit(simd_cols.iter().collect())
+        });
+
+        assert_eq!(counter.n_hashes(), (1 << 5) - 1);
+        assert_eq!(simd_counter.n_hashes(), (1 << 5) - 1);
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_merkle_hashes_on_other_threads_are_counted() {
+        let cols = [vec![m31!(0); 1 << 4], vec![m31!(1); 1 << 2]];
+        let counter = HashCounter::default();
+
+        counter.count(|| {
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit_trees_parallel(&[
+                cols.iter().collect(),
+                cols[1..].iter().collect(),
+            ])
+        });
+
+        assert_eq!(counter.n_hashes(), (1 << 5) - 1 + (1 << 3) - 1);
+    }
+}
@@ -0,0 +1,120 @@
+use super::{extract_secure_field_from_bytes, Channel, MerkleChannel};
+use crate::core::fields::qm31::SecureField;
+use crate::core::vcs::ops::MerkleHasher;
+use crate::core::vcs::sha256_merkle::Sha256MerkleHasher;
+
+/// A channel that ignores everything mixed into it, for isolating the cost of the prover's
+/// arithmetic from the cost of hashing the transcript, e.g. in benchmarks and fuzzing.
+///
+/// Draws are a deterministic function of the number of previous draws, so they are reproducible
+/// but not random. Not sound: never use it for real proofs.
+#[derive(Clone, Debug, Default)]
+pub struct DummyChannel {
+    /// The number of words drawn so far.
+    counter: u64,
+}
+
+impl DummyChannel {
+    /// Returns the next pseudo-random word, by Fibonacci hashing the counter.
+    fn next_word(&mut self) -> u64 {
+        self.counter += 1;
+        self.counter.wrapping_mul(0x9e3779b97f4a7c15)
+    }
+}
+
+impl Channel for DummyChannel {
+    const BYTES_PER_HASH: usize = 32;
+
+    /// The digest never changes, so it is treated as all zeros, and any proof of work is valid.
+    fn trailing_zeros(&self) -> u32 {
+        Self::BYTES_PER_HASH as u32 * 8
+    }
+
+    fn leading_zeros(&self) -> u32 {
+        Self::BYTES_PER_HASH as u32 * 8
+    }
+
+    fn mix_felts(&mut self, _felts: &[SecureField]) {}
+
+    fn mix_nonce(&mut self, _nonce: u64) {}
+
+    fn mix_u64(&mut self, _value: u64) {}
+
+    fn mix_bytes(&mut self, _bytes: &[u8]) {}
+
+    fn draw_felt(&mut self) -> SecureField {
+        let bytes = self.draw_random_bytes();
+        extract_secure_field_from_bytes(&bytes.try_into().unwrap())
+    }
+
+    fn draw_felts(&mut self, n_felts: usize) -> Vec<SecureField> {
+        (0..n_felts).map(|_| self.draw_felt()).collect()
+    }
+
+    fn draw_random_bytes(&mut self) -> Vec<u8> {
+        (0..Self::BYTES_PER_HASH / 8)
+            .flat_map(|_| self.next_word().to_le_bytes())
+            .collect()
+    }
+}
+
+/// A [MerkleChannel] over a [DummyChannel]. Trees are still committed with
+/// [Sha256MerkleHasher], but their roots are not mixed into the channel.
+#[derive(Default)]
+pub struct DummyMerkleChannel;
+
+impl MerkleChannel for DummyMerkleChannel {
+    type C = DummyChannel;
+    type H = Sha256MerkleHasher;
+
+    fn mix_root(_channel: &mut Self::C, _root: <Self::H as MerkleHasher>::Hash) {}
+}
+
+#[cfg(test)]
+mod tests {
+    use crate::core::channel::{Channel, DummyChannel, DummyMerkleChannel};
+    use crate::core::fields::qm31::SecureField;
+    use crate::core::pcs::PcsConfig;
+    use crate::examples::fibonacci::Fibonacci;
+    use crate::m31;
+    use crate::trace_generation::{commit_and_prove, commit_and_verify};
+
+    #[test]
+    fn test_draws_are_reproducible() {
+        let mut channel = DummyChannel::default();
+        let mut other_channel = DummyChannel::default();
+        other_channel.mix_felts(&[SecureField::from(m31!(1))]);
+        other_channel.mix_nonce(1);
+
+        let felts = channel.draw_felts(3);
+        let bytes = channel.draw_random_bytes();
+
+        assert_eq!(felts, other_channel.draw_felts(3));
+        assert_eq!(bytes, other_channel.draw_random_bytes());
+        assert_ne!(felts[0], felts[1]);
+        assert_ne!(channel.draw_random_bytes(), bytes);
+    }
+
+    #[test]
+    fn test_prove_and_verify() {
+        const FIB_LOG_SIZE: u32 = 5;
+        let fib = Fibonacci::new(FIB_LOG_SIZE, m31!(443693538));
+        let config = PcsConfig::default();
+
+        let proof = commit_and_prove::<_, DummyMerkleChannel>(
+            &fib.air,
+            &mut DummyChannel::default(),
+            vec![fib.get_trace()],
+            config,
+        )
+        .unwrap();
+
+        commit_and_verify::<DummyMerkleChannel>(
+            proof,
+            &fib.air,
+            &mut DummyChannel::default(),
+            config,
+        )
+        .unwrap();
+    }
+}
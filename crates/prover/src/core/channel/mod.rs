@@ -1,4 +1,8 @@
-use super::fields::qm31::SecureField;
+use itertools::Itertools;
+
+use super::fields::cm31::CM31;
+use super::fields::m31::{BaseField, M31};
+use super::fields::qm31::{SecureField, QM31};
 use super::vcs::ops::MerkleHasher;
 
 #[cfg(not(target_arch = "wasm32"))]
@@ -7,7 +11,16 @@ mod poseidon252;
 pub use poseidon252::Poseidon252Channel;
 
 pub mod sha256;
-pub use sha256::Sha256Channel;
+pub use sha256::{Sha256BatchedChannel, Sha256Channel, Sha256StreamingChannel};
+
+mod counting;
+pub use counting::HashCounter;
+pub(crate) use counting::{add_hashes, uncounted};
+
+#[cfg(any(test, feature = "testing"))]
+mod dummy;
+#[cfg(any(test, feature = "testing"))]
+pub use dummy::{DummyChannel, DummyMerkleChannel};
 
 pub const EXTENSION_FELTS_PER_HASH: usize = 2;
 
@@ -34,17 +47,218 @@ pub trait Channel: Default + Clone {
     const BYTES_PER_HASH: usize;
 
     fn trailing_zeros(&self) -> u32;
+    /// Counts the zero bits at the other end of the digest than [Channel::trailing_zeros], for
+    /// proof of work rules that require leading zeros.
+    fn leading_zeros(&self) -> u32;
 
     // Mix functions.
     fn mix_felts(&mut self, felts: &[SecureField]);
     fn mix_nonce(&mut self, nonce: u64);
+    /// Mixes a `u64` value, e.g. a public input or a configuration parameter.
+    ///
+    /// Defaults to [Channel::mix_felts] of a single felt holding `value` in three 24-bit limbs,
+    /// with `2^24` as its last coordinate. The default [Channel::mix_bytes] packs 3 bytes per
+    /// coordinate, so it never produces that felt, and `mix_u64(x)` differs from
+    /// `mix_bytes(&x.to_le_bytes())`.
+    fn mix_u64(&mut self, value: u64) {
+        const LIMB_MASK: u64 = (1 << 24) - 1;
+        self.mix_felts(&[SecureField::from_m31_array([
+            M31((value & LIMB_MASK) as u32),
+            M31(((value >> 24) & LIMB_MASK) as u32),
+            M31((value >> 48) as u32),
+            M31(1 << 24),
+        ])]);
+    }
+    /// Mixes an arbitrary byte string.
+    ///
+    /// Defaults to [Channel::mix_felts] of the little-endian `u64` length of `bytes` followed by
+    /// `bytes`, zero padded and packed 3 bytes per [BaseField] coordinate, so 12 bytes per
+    /// [SecureField]. The length distinguishes inputs that only differ by trailing zeros.
+    fn mix_bytes(&mut self, bytes: &[u8]) {
+        let mut padded_bytes = (bytes.len() as u64).to_le_bytes().to_vec();
+        padded_bytes.extend(bytes);
+        padded_bytes.resize(padded_bytes.len().next_multiple_of(12), 0);
+        let felts = padded_bytes
+            .chunks_exact(12)
+            .map(|chunk| {
+                SecureField::from_m31_array(std::array::from_fn(|i| {
+                    M31(u32::from_le_bytes([
+                        chunk[3 * i],
+                        chunk[3 * i + 1],
+                        chunk[3 * i + 2],
+                        0,
+                    ]))
+                }))
+            })
+            .collect_vec();
+        self.mix_felts(&felts);
+    }
+    /// Mixes several groups of felts, binding the number of groups and the length of each group,
+    /// so that different groupings of the same felts give different transcripts.
+    ///
+    /// This absorbs the felts differently than [Channel::mix_felts], so replacing existing
+    /// [Channel::mix_felts] calls with it changes the transcript, and must be done as a protocol
+    /// version change.
+    fn mix_felt_groups(&mut self, groups: &[&[SecureField]]) {
+        self.mix_u64(groups.len() as u64);
+        for group in groups {
+            self.mix_u64(group.len() as u64);
+            self.mix_felts(group);
+        }
+    }
+    /// Mixes the values opened by a decommitment, given per column in the order the columns are
+    /// committed, each with its values in query order.
+    ///
+    /// The values are absorbed with a single [Channel::mix_bytes] of
+    /// `n_columns || (len || values)...`, where the counts are little-endian `u64`s and each value
+    /// is a little-endian `u32`. The prover and verifier must mix the same columns in the same
+    /// order.
+    fn mix_decommitted_values(&mut self, columns: &[Vec<BaseField>]) {
+        let n_values = columns.iter().map(Vec::len).sum::<usize>();
+        let mut bytes = Vec::with_capacity(8 * (1 + columns.len()) + 4 * n_values);
+        bytes.extend((columns.len() as u64).to_le_bytes());
+        for column in columns {
+            bytes.extend((column.len() as u64).to_le_bytes());
+            for value in column {
+                bytes.extend(value.0.to_le_bytes());
+            }
+        }
+        self.mix_bytes(&bytes);
+    }
 
     // Draw functions.
     fn draw_felt(&mut self) -> SecureField;
     /// Generates a uniform random vector of SecureField elements.
     fn draw_felts(&mut self, n_felts: usize) -> Vec<SecureField>;
+    /// Generates a vector of `n_felts` random [BaseField] elements.
+    ///
+    /// Each felt is extracted from 4 random bytes as a coordinate of a [SecureField] is in
+    /// [Channel::draw_felt]: the little-endian `u32` with its top bit masked off, reduced modulo
+    /// `P`. There is no rejection sampling, so `0` is twice as likely as any other value, a
+    /// bias of `2^-31`.
+    fn draw_base_felts(&mut self, n_felts: usize) -> Vec<BaseField> {
+        let mut res = Vec::with_capacity(n_felts);
+        while res.len() < n_felts {
+            let bytes = self.draw_random_bytes();
+            res.extend(
+                bytes
+                    .chunks_exact(4)
+                    .map(|chunk| extract_base_field(chunk.try_into().unwrap())),
+            );
+        }
+        res.truncate(n_felts);
+        res
+    }
     /// Returns a vector of random bytes of length `BYTES_PER_HASH`.
     fn draw_random_bytes(&mut self) -> Vec<u8>;
+    /// Captures the state of the channel, to be resumed with [Channel::restore].
+    fn checkpoint(&self) -> ChannelState<Self> {
+        ChannelState(self.clone())
+    }
+
+    /// Resets the channel to a state captured by [Channel::checkpoint]. Everything mixed or
+    /// drawn since is forgotten, so the same values are drawn again.
+    fn restore(&mut self, state: ChannelState<Self>) {
+        *self = state.0;
+    }
+
+    /// Returns `n_bits` uniform random bits, as the low bits of a `u64`.
+    ///
+    /// The bits are taken from the little-endian bytes of [Channel::draw_random_bytes], drawing
+    /// more bytes as needed. Unused bytes of the last draw are discarded.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `n_bits` is greater than 64.
+    fn draw_bits(&mut self, n_bits: usize) -> u64 {
+        assert!(n_bits <= 64, "Can't draw {n_bits} bits into a u64.");
+        let n_bytes = n_bits.div_ceil(8);
+        let mut bytes = Vec::with_capacity(n_bytes);
+        while bytes.len() < n_bytes {
+            bytes.extend(self.draw_random_bytes());
+        }
+        let mut value = [0; 8];
+        value[..n_bytes].copy_from_slice(&bytes[..n_bytes]);
+        u64::from_le_bytes(value) & u64::MAX.checked_shr((64 - n_bits) as u32).unwrap_or(0)
+    }
+
+    /// Returns `n_queries` uniform random indices in `[0, 2^log_domain_size)`, possibly repeating.
+    ///
+    /// Each index is the little-endian `u32` of 4 bytes of [Channel::draw_random_bytes], masked to
+    /// its low `log_domain_size` bits. As the range is a power of two, masking a uniform `u32` is
+    /// uniform, so no rejection sampling is needed to avoid modulo bias. Unused bytes of the last
+    /// draw are discarded.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `log_domain_size` is greater than 32.
+    fn draw_queries(&mut self, n_queries: usize, log_domain_size: u32) -> Vec<usize> {
+        assert!(
+            log_domain_size <= u32::BITS,
+            "Can't draw queries in a domain of log size {log_domain_size}."
+        );
+        let mask = u32::MAX
+            .checked_shr(u32::BITS - log_domain_size)
+            .unwrap_or(0);
+        let mut queries = Vec::with_capacity(n_queries);
+        while queries.len() < n_queries {
+            let bytes = self.draw_random_bytes();
+            queries.extend(
+                bytes
+                    .chunks_exact(4)
+                    .map(|chunk| (u32::from_le_bytes(chunk.try_into().unwrap()) & mask) as usize),
+            );
+        }
+        queries.truncate(n_queries);
+        queries
+    }
+
+    /// Derives `n_bytes` bytes of prover randomness (e.g. for blinding) from the current state and
+    /// `label`, without advancing the channel.
+    ///
+    /// The bytes are drawn from a fork of the channel, separated from the transcript by a fixed
+    /// prefix and `label`. They are reproducible from the transcript, so they are not secret from
+    /// anyone who can replay it.
+    fn derive_prover_randomness(&self, label: &[u8], n_bytes: usize) -> Vec<u8> {
+        let mut fork = self.clone();
+        fork.mix_bytes(PROVER_RANDOMNESS_PREFIX);
+        fork.mix_bytes(label);
+        fork.mix_u64(n_bytes as u64);
+        let mut bytes = Vec::with_capacity(n_bytes);
+        while bytes.len() < n_bytes {
+            bytes.extend(fork.draw_random_bytes());
+        }
+        bytes.truncate(n_bytes);
+        bytes
+    }
+}
+
+/// Domain separator mixed into the fork in [Channel::derive_prover_randomness].
+const PROVER_RANDOMNESS_PREFIX: &[u8] = b"stwo prover randomness";
+
+/// A snapshot of a channel's state. See [Channel::checkpoint].
+#[derive(Clone, Debug)]
+pub struct ChannelState<C>(C);
+
+/// Extracts a [SecureField] from the first 16 bytes of a 32-byte hash output.
+///
+/// This is how channels squeeze a felt from a hash: each of its four [M31] coordinates is read from
+/// 4 bytes, as a little-endian `u32` with the top bit masked off, reduced modulo `P`.
+pub fn extract_secure_field_from_bytes(bytes: &[u8; 32]) -> SecureField {
+    extract_secure_field(bytes[..16].try_into().unwrap())
+}
+
+/// Extracts a [SecureField] from 16 bytes. Each of its four [M31] coordinates is read from 4
+/// bytes, as a little-endian `u32` with the top bit masked off, reduced modulo `P`.
+pub(crate) fn extract_secure_field(bytes: &[u8; 16]) -> SecureField {
+    let [a, b, c, d] =
+        std::array::from_fn(|i| extract_base_field(bytes[4 * i..4 * i + 4].try_into().unwrap()));
+    QM31(CM31(a, b), CM31(c, d))
+}
+
+/// Extracts an [M31] from 4 bytes, as in [extract_secure_field].
+pub(crate) fn extract_base_field(bytes: &[u8; 4]) -> M31 {
+    M31::from(u32::from_le_bytes(*bytes) & 0x7fffffff)
 }
 
 pub trait MerkleChannel: Default {
@@ -52,3 +266,85 @@ pub trait MerkleChannel: Default {
     type H: MerkleHasher;
     fn mix_root(channel: &mut Self::C, root: <Self::H as MerkleHasher>::Hash);
 }
+
+#[cfg(test)]
+mod tests {
+    use super::{Channel, Sha256Channel};
+    use crate::core::fields::qm31::SecureField;
+
+    /// A [Sha256Channel] that only implements the required methods of [Channel].
+    #[derive(Clone, Default)]
+    struct MinimalChannel(Sha256Channel);
+
+    impl Channel for MinimalChannel {
+        const BYTES_PER_HASH: usize = Sha256Channel::BYTES_PER_HASH;
+
+        fn trailing_zeros(&self) -> u32 {
+            self.0.trailing_zeros()
+        }
+
+        fn leading_zeros(&self) -> u32 {
+            self.0.leading_zeros()
+        }
+
+        fn mix_felts(&mut self, felts: &[SecureField]) {
+            self.0.mix_felts(felts)
+        }
+
+        fn mix_nonce(&mut self, nonce: u64) {
+            self.0.mix_nonce(nonce)
+        }
+
+        fn draw_felt(&mut self) -> SecureField {
+            self.0.draw_felt()
+        }
+
+        fn draw_felts(&mut self, n_felts: usize) -> Vec<SecureField> {
+            self.0.draw_felts(n_felts)
+        }
+
+        fn draw_random_bytes(&mut self) -> Vec<u8> {
+            self.0.draw_random_bytes()
+        }
+    }
+
+    #[test]
+    fn test_default_mix_bytes_binds_length() {
+        let mut channel = MinimalChannel::default();
+        let mut padded_channel = channel.clone();
+        let mut replay_channel = channel.clone();
+
+        channel.mix_bytes(b"stwo");
+        padded_channel.mix_bytes(b"stwo\0");
+        replay_channel.mix_bytes(b"stwo");
+
+        assert_ne!(channel.0.digest(), MinimalChannel::default().0.digest());
+        assert_ne!(channel.0.digest(), padded_channel.0.digest());
+        assert_eq!(channel.0.digest(), replay_channel.0.digest());
+    }
+
+    #[test]
+    fn test_default_mix_u64_is_separated_from_mix_nonce() {
+        let mut channel = MinimalChannel::default();
+        let mut nonce_channel = channel.clone();
+        let mut other_channel = channel.clone();
+
+        channel.mix_u64(1);
+        nonce_channel.mix_nonce(1);
+        other_channel.mix_u64(2);
+
+        assert_ne!(channel.0.digest(), nonce_channel.0.digest());
+        assert_ne!(channel.0.digest(), other_channel.0.digest());
+    }
+
+    #[test]
+    fn test_default_mix_u64_is_separated_from_mix_bytes() {
+        let mut channel = MinimalChannel::default();
+        let mut bytes_channel = channel.clone();
+
+        channel.mix_u64(1);
+        bytes_channel.mix_bytes(&1u64.to_le_bytes());
+
+        assert_ne!(channel.0.digest(), bytes_channel.0.digest());
+    }
+}
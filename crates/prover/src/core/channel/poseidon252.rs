@@ -3,7 +3,7 @@ use std::iter;
 use starknet_crypto::{poseidon_hash, poseidon_hash_many};
 use starknet_ff::FieldElement as FieldElement252;
 
-use super::{Channel, ChannelTime};
+use super::{add_hashes, Channel, ChannelTime};
 use crate::core::fields::m31::BaseField;
 use crate::core::fields::qm31::SecureField;
 use crate::core::fields::secure_column::SECURE_EXTENSION_DEGREE;
@@ -27,6 +27,7 @@ impl Poseidon252Channel {
         self.channel_time.inc_challenges();
     }
     fn draw_felt252(&mut self) -> FieldElement252 {
+        add_hashes(1);
         let res = poseidon_hash(self.digest, self.channel_time.n_sent.into());
         self.channel_time.inc_sent();
         res
@@ -34,7 +35,7 @@ impl Poseidon252Channel {
 
     // TODO(spapini): Understand if we really need uniformity here.
     /// Generates a close-to uniform random vector of BaseField elements.
-    fn draw_base_felts(&mut self) -> [BaseField; 8] {
+    fn draw_base_felt_array(&mut self) -> [BaseField; 8] {
         let shift = (1u64 << 31).into();
 
         let mut cur = self.draw_felt252();
@@ -61,6 +62,18 @@ impl Channel for Poseidon252Channel {
         u128::from_le_bytes(std::array::from_fn(|i| bytes[i])).trailing_zeros()
     }
 
+    /// Counts the leading zeros of the digest as a 252-bit integer.
+    fn leading_zeros(&self) -> u32 {
+        let bytes = self.digest.to_bytes_be();
+        let high = u128::from_be_bytes(std::array::from_fn(|i| bytes[i]));
+        let low = u128::from_be_bytes(std::array::from_fn(|i| bytes[16 + i]));
+        let n_bits = match high {
+            0 => 128 + low.leading_zeros(),
+            _ => high.leading_zeros(),
+        };
+        n_bits - (256 - 252)
+    }
+
     // TODO(spapini): Optimize.
     fn mix_felts(&mut self, felts: &[SecureField]) {
         let shift = (1u64 << 31).into();
@@ -78,20 +91,36 @@ impl Channel for Poseidon252Channel {
         }
 
         // TODO(spapini): do we need length padding?
+        add_hashes(1);
         self.update_digest(poseidon_hash_many(&res));
     }
 
     fn mix_nonce(&mut self, nonce: u64) {
+        add_hashes(1);
         self.update_digest(poseidon_hash(self.digest, nonce.into()));
     }
 
+    fn mix_bytes(&mut self, bytes: &[u8]) {
+        let mut res = Vec::with_capacity(bytes.len().div_ceil(BYTES_PER_FELT252) + 2);
+        res.push(self.digest);
+        // Mix the length, so that inputs that only differ by leading zeros are distinguished.
+        res.push((bytes.len() as u64).into());
+        res.extend(
+            bytes
+                .chunks(BYTES_PER_FELT252)
+                .map(|chunk| FieldElement252::from_byte_slice_be(chunk).unwrap()),
+        );
+        add_hashes(1);
+        self.update_digest(poseidon_hash_many(&res));
+    }
+
     fn draw_felt(&mut self) -> SecureField {
-        let felts: [BaseField; FELTS_PER_HASH] = self.draw_base_felts();
+        let felts: [BaseField; FELTS_PER_HASH] = self.draw_base_felt_array();
         SecureField::from_m31_array(felts[..SECURE_EXTENSION_DEGREE].try_into().unwrap())
     }
 
     fn draw_felts(&mut self, n_felts: usize) -> Vec<SecureField> {
-        let mut felts = iter::from_fn(|| Some(self.draw_base_felts())).flatten();
+        let mut felts = iter::from_fn(|| Some(self.draw_base_felt_array())).flatten();
         let secure_felts = iter::from_fn(|| {
             Some(SecureField::from_m31_array([
                 felts.next()?,
@@ -103,6 +132,13 @@ impl Channel for Poseidon252Channel {
         secure_felts.take(n_felts).collect()
     }
 
+    fn draw_base_felts(&mut self, n_felts: usize) -> Vec<BaseField> {
+        iter::from_fn(|| Some(self.draw_base_felt_array()))
+            .flatten()
+            .take(n_felts)
+            .collect()
+    }
+
     fn draw_random_bytes(&mut self) -> Vec<u8> {
         let shift = (1u64 << 8).into();
         let mut cur = self.draw_felt252();
@@ -120,6 +156,8 @@ impl Channel for Poseidon252Channel {
 mod tests {
     use std::collections::BTreeSet;
 
+    use starknet_ff::FieldElement as FieldElement252;
+
     use crate::core::channel::poseidon252::Poseidon252Channel;
     use crate::core::channel::Channel;
     use crate::core::fields::qm31::SecureField;
@@ -151,6 +189,59 @@ mod tests {
         assert_ne!(first_random_bytes, channel.draw_random_bytes());
     }
 
+    #[test]
+    fn test_draw_bits() {
+        let mut channel = Poseidon252Channel::default();
+        let mut bytes_channel = channel.clone();
+
+        let bits = channel.draw_bits(64);
+        let small_bits = (0..4096).map(|_| channel.draw_bits(3)).collect::<Vec<_>>();
+
+        let bytes = bytes_channel.draw_random_bytes();
+        assert_eq!(bits, u64::from_le_bytes(bytes[..8].try_into().unwrap()));
+        assert_eq!(channel.draw_bits(0), 0);
+        assert!(channel.draw_bits(13) < 1 << 13);
+        // Each of the 8 values is drawn about 512 times.
+        for value in 0..8 {
+            let count = small_bits.iter().filter(|&&bits| bits == value).count();
+            assert!((412..612).contains(&count), "{value} drawn {count} times.");
+        }
+    }
+
+    #[test]
+    fn test_checkpoint_and_restore() {
+        let mut channel = Poseidon252Channel::default();
+        channel.draw_felts(3);
+
+        let checkpoint = channel.checkpoint();
+        let felts = channel.draw_felts(4);
+        channel.mix_u64(1);
+        let felt = channel.draw_felt();
+        channel.restore(checkpoint);
+
+        assert_eq!(channel.draw_felts(4), felts);
+        channel.mix_u64(1);
+        assert_eq!(channel.draw_felt(), felt);
+    }
+
+    #[test]
+    fn test_leading_zeros() {
+        let channel = |digest: u128| Poseidon252Channel {
+            digest: FieldElement252::from(digest),
+            ..Default::default()
+        };
+
+        assert_eq!(channel(0).leading_zeros(), 252);
+        assert_eq!(channel(1).leading_zeros(), 251);
+        assert_eq!(channel(1 << 100).leading_zeros(), 151);
+        // The largest felt, P - 1 = 2^251 + 17 * 2^192, has no leading zeros.
+        let max_felt_channel = Poseidon252Channel {
+            digest: FieldElement252::ZERO - FieldElement252::ONE,
+            ..Default::default()
+        };
+        assert_eq!(max_felt_channel.leading_zeros(), 0);
+    }
+
     #[test]
     pub fn test_draw_felt() {
         let mut channel = Poseidon252Channel::default();
@@ -187,4 +278,64 @@ mod tests {
 
         assert_ne!(initial_digest, channel.digest);
     }
+
+    #[test]
+    pub fn test_mix_u64() {
+        let mut channel = Poseidon252Channel::default();
+        let initial_digest = channel.digest;
+        let mut other_channel = channel.clone();
+
+        channel.mix_u64(1);
+        other_channel.mix_u64(2);
+
+        assert_ne!(initial_digest, channel.digest);
+        assert_ne!(channel.digest, other_channel.digest);
+    }
+
+    #[test]
+    pub fn test_mix_u64_is_separated_from_mix_nonce() {
+        let mut channel = Poseidon252Channel::default();
+        let mut nonce_channel = channel.clone();
+
+        channel.mix_u64(1);
+        nonce_channel.mix_nonce(1);
+
+        assert_ne!(channel.digest, nonce_channel.digest);
+    }
+
+    #[test]
+    pub fn test_mix_u64_is_separated_from_mix_bytes() {
+        let mut channel = Poseidon252Channel::default();
+        let mut bytes_channel = channel.clone();
+
+        channel.mix_u64(1);
+        bytes_channel.mix_bytes(&1u64.to_le_bytes());
+
+        assert_ne!(channel.digest, bytes_channel.digest);
+    }
+
+    #[test]
+    pub fn test_mix_bytes() {
+        let mut channel = Poseidon252Channel::default();
+        let initial_digest = channel.digest;
+        let mut other_channel = channel.clone();
+
+        channel.mix_bytes(&[1, 2, 3]);
+        other_channel.mix_bytes(&[0, 1, 2, 3]);
+
+        assert_ne!(initial_digest, channel.digest);
+        assert_ne!(channel.digest, other_channel.digest);
+    }
+
+    #[test]
+    pub fn test_mix_felt_groups_binds_grouping() {
+        let felts = [1, 2, 3].map(|i| SecureField::from(m31!(i)));
+        let mut channel = Poseidon252Channel::default();
+        let mut other_channel = channel.clone();
+
+        channel.mix_felt_groups(&[&felts[..2], &felts[2..]]);
+        other_channel.mix_felt_groups(&[&felts[..1], &felts[1..]]);
+
+        assert_ne!(channel.digest, other_channel.digest);
+    }
 }
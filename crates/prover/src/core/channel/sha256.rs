@@ -1,8 +1,12 @@
+use itertools::Itertools;
+use serde::{Deserialize, Serialize};
 use sha2::{Digest, Sha256};
 
-use crate::core::channel::Channel;
+use crate::core::channel::{
+    add_hashes, extract_base_field, extract_secure_field, extract_secure_field_from_bytes, Channel,
+};
 use crate::core::fields::cm31::CM31;
-use crate::core::fields::m31::M31;
+use crate::core::fields::m31::{BaseField, M31, P};
 use crate::core::fields::qm31::{SecureField, QM31};
 use crate::core::utils::sha256_qm31;
 use crate::core::vcs::sha256_hash::{Sha256Hash, Sha256Hasher};
@@ -11,7 +15,12 @@ pub const BLAKE_BYTES_PER_HASH: usize = 32;
 pub const FELTS_PER_HASH: usize = 8;
 pub const EXTENSION_FELTS_PER_HASH: usize = 2;
 
-#[derive(Default, Clone)]
+/// Tag of [Channel::mix_bytes] inputs. Squeezes use the tags 0 and 1.
+const MIX_BYTES_TAG: u8 = 2;
+/// Tag of [Channel::mix_u64] inputs.
+const MIX_U64_TAG: u8 = 3;
+
+#[derive(Default, Clone, Deserialize, Serialize)]
 /// A channel.
 pub struct Sha256Channel {
     /// Current state of the channel.
@@ -19,6 +28,17 @@ pub struct Sha256Channel {
 }
 
 impl Sha256Channel {
+    /// Creates a channel resuming from `digest`, e.g. one handed over by [Self::into_digest] on
+    /// another machine.
+    pub fn from_digest(digest: Sha256Hash) -> Self {
+        Self { digest }
+    }
+
+    /// Consumes the channel, returning its whole state.
+    pub fn into_digest(self) -> Sha256Hash {
+        self.digest
+    }
+
     pub fn digest(&self) -> Sha256Hash {
         self.digest
     }
@@ -26,12 +46,40 @@ impl Sha256Channel {
     pub fn update_digest(&mut self, digest: Sha256Hash) {
         self.digest = digest;
     }
+
+    /// Draws [FELTS_PER_HASH] base field elements from a single hash, advancing the digest once.
+    /// Each one is extracted from 4 bytes like the coordinates of [Channel::draw_felt], so the
+    /// first 4 are the coordinates of the felt [Channel::draw_felt] would draw.
+    pub fn draw_base_felt_array(&mut self) -> [M31; FELTS_PER_HASH] {
+        let extract = self.squeeze(0);
+        std::array::from_fn(|i| extract_base_field(extract[4 * i..4 * i + 4].try_into().unwrap()))
+    }
 }
 
+/// Only the channel state is wiped: values returned by the channel, and hasher states internal
+/// to its methods, are not.
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Sha256Channel {
+    fn zeroize(&mut self) {
+        self.digest.zeroize();
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl Drop for Sha256Channel {
+    fn drop(&mut self) {
+        zeroize::Zeroize::zeroize(self);
+    }
+}
+
+#[cfg(feature = "zeroize")]
+impl zeroize::ZeroizeOnDrop for Sha256Channel {}
+
 impl Channel for Sha256Channel {
     const BYTES_PER_HASH: usize = 32;
 
     fn mix_felts(&mut self, felts: &[SecureField]) {
+        add_hashes(2 * felts.len());
         for felt in felts.iter() {
             let mut hasher = Sha256::new();
             Digest::update(&mut hasher, sha256_qm31(felt));
@@ -47,27 +95,54 @@ impl Channel for Sha256Channel {
         let mut hash = [0u8; 32];
         hash[..8].copy_from_slice(&nonce.to_le_bytes());
 
+        add_hashes(1);
         self.digest = Sha256Hasher::concat_and_hash(&Sha256Hash(hash), &self.digest);
     }
 
-    fn draw_felt(&mut self) -> SecureField {
-        let mut extract = [0u8; 32];
-
+    /// Computes `sha256(digest || [MIX_U64_TAG] || value)`, with a little-endian `value`.
+    fn mix_u64(&mut self, value: u64) {
+        add_hashes(1);
         let mut hasher = Sha256::new();
         Digest::update(&mut hasher, self.digest);
-        Digest::update(&mut hasher, [0u8]);
-        extract.copy_from_slice(hasher.finalize().as_slice());
+        Digest::update(&mut hasher, [MIX_U64_TAG]);
+        Digest::update(&mut hasher, value.to_le_bytes());
+        self.update_digest(hasher.finalize().as_slice().into());
+    }
 
+    /// Computes `sha256(digest || [MIX_BYTES_TAG] || len || bytes)`, with a little-endian `u64`
+    /// length. The digest comes first and the tag differs from the squeeze tags, so the input
+    /// can't be confused with the hash of any other channel operation.
+    fn mix_bytes(&mut self, bytes: &[u8]) {
+        add_hashes(1);
         let mut hasher = Sha256::new();
         Digest::update(&mut hasher, self.digest);
-        self.digest.0.copy_from_slice(hasher.finalize().as_slice());
+        Digest::update(&mut hasher, [MIX_BYTES_TAG]);
+        Digest::update(&mut hasher, (bytes.len() as u64).to_le_bytes());
+        Digest::update(&mut hasher, bytes);
+        self.update_digest(hasher.finalize().as_slice().into());
+    }
 
-        let res_1 = Self::extract_common(&extract);
-        let res_2 = Self::extract_common(&extract[4..]);
-        let res_3 = Self::extract_common(&extract[8..]);
-        let res_4 = Self::extract_common(&extract[12..]);
+    /// Absorbs all the groups in a single hash:
+    /// `sha256(digest || n_groups || (len || felts)...)`, where the counts are little-endian `u64`s
+    /// and each felt is its four coordinates as little-endian `u32`s.
+    fn mix_felt_groups(&mut self, groups: &[&[SecureField]]) {
+        add_hashes(1);
+        let mut hasher = Sha256::new();
+        Digest::update(&mut hasher, self.digest);
+        Digest::update(&mut hasher, (groups.len() as u64).to_le_bytes());
+        for group in groups {
+            Digest::update(&mut hasher, (group.len() as u64).to_le_bytes());
+            for felt in group.iter() {
+                for coordinate in felt.to_m31_array() {
+                    Digest::update(&mut hasher, coordinate.0.to_le_bytes());
+                }
+            }
+        }
+        self.update_digest(hasher.finalize().as_slice().into());
+    }
 
-        QM31(CM31(res_1, res_2), CM31(res_3, res_4))
+    fn draw_felt(&mut self) -> SecureField {
+        extract_secure_field_from_bytes(&self.squeeze(0))
     }
 
     fn draw_felts(&mut self, n_felts: usize) -> Vec<SecureField> {
@@ -79,44 +154,252 @@ impl Channel for Sha256Channel {
     }
 
     fn draw_random_bytes(&mut self) -> Vec<u8> {
-        let mut extract = [0u8; 32];
+        self.squeeze(0).to_vec()
+    }
+
+    /// Counts the zero bits at the end of the digest, starting from the last byte.
+    ///
+    /// Runs in constant time: all bytes are scanned and no branch depends on the digest.
+    fn trailing_zeros(&self) -> u32 {
+        count_zero_bits(self.digest.0.iter().rev())
+    }
+
+    /// Counts the zero bits at the start of the digest, i.e. the leading zeros of the digest as a
+    /// big-endian integer.
+    ///
+    /// Runs in constant time, like [Channel::trailing_zeros].
+    fn leading_zeros(&self) -> u32 {
+        count_zero_bits(self.digest.0.iter())
+    }
+}
+
+/// Counts the zero bits of `bytes` up to the first set bit, from the most significant bit of each
+/// byte. Runs in constant time: all bytes are scanned and no branch depends on their values.
+fn count_zero_bits<'a>(bytes: impl Iterator<Item = &'a u8>) -> u32 {
+    let mut n_bits = 0;
+    // All ones while every byte scanned so far is zero, all zeros afterwards.
+    let mut zero_prefix_mask = u32::MAX;
+    for &byte in bytes {
+        n_bits += zero_prefix_mask & byte.leading_zeros();
+        let is_zero = (byte as u32).wrapping_sub(1) >> 31;
+        zero_prefix_mask &= is_zero.wrapping_neg();
+    }
+    n_bits
+}
+
+impl Sha256Channel {
+    /// Draws a random felt with a negligible bias, in constant time.
+    ///
+    /// Unlike [Channel::draw_felt], each coordinate is derived from 8 bytes: the little-endian
+    /// 64-bit value is reduced modulo `P`, which keeps the statistical distance from uniform below
+    /// 2^-33 without re-drawing. The 32 extracted bytes are `sha256(digest || [1])`, so the drawn
+    /// felt is unrelated to the one `draw_felt` would draw. The digest is then advanced to
+    /// `sha256(digest)`, as in `draw_felt`.
+    pub fn draw_felt_wide(&mut self) -> SecureField {
+        let extract = self.squeeze(1);
+
+        let res_1 = Self::extract_wide(&extract);
+        let res_2 = Self::extract_wide(&extract[8..]);
+        let res_3 = Self::extract_wide(&extract[16..]);
+        let res_4 = Self::extract_wide(&extract[24..]);
 
+        QM31(CM31(res_1, res_2), CM31(res_3, res_4))
+    }
+
+    /// Returns `sha256(digest || [tag])` and advances the digest to `sha256(digest)`.
+    ///
+    /// Both inputs fit in a single 64-byte block, so there is no midstate to share between the
+    /// two hashes: each costs exactly one compression.
+    fn squeeze(&mut self, tag: u8) -> [u8; 32] {
+        add_hashes(2);
         let mut hasher = Sha256::new();
         Digest::update(&mut hasher, self.digest);
-        Digest::update(&mut hasher, [0u8]);
-        extract.copy_from_slice(hasher.finalize().as_slice());
+        Digest::update(&mut hasher, [tag]);
+        let extract = hasher.finalize().into();
 
         let mut hasher = Sha256::new();
         Digest::update(&mut hasher, self.digest);
         self.digest.0.copy_from_slice(hasher.finalize().as_slice());
 
-        extract.to_vec()
+        extract
     }
 
-    fn trailing_zeros(&self) -> u32 {
-        let mut n_bits = 0;
-        for byte in self.digest.0.iter().rev() {
-            if *byte == 0 {
-                n_bits += 8;
-            } else {
-                n_bits += byte.leading_zeros();
-                break;
-            }
+    /// Returns the blocks `sha256(digest || [0] || counter)` for `counter` in `0..n_blocks`, with a
+    /// little-endian `u32` counter, and advances the digest to `sha256(digest)`. Squeezing no
+    /// blocks leaves the digest unchanged.
+    fn squeeze_blocks(&mut self, n_blocks: usize) -> Vec<[u8; 32]> {
+        if n_blocks == 0 {
+            return vec![];
         }
-        n_bits
+        add_hashes(n_blocks + 1);
+        let blocks = (0..n_blocks as u32)
+            .map(|counter| {
+                let mut hasher = Sha256::new();
+                Digest::update(&mut hasher, self.digest);
+                Digest::update(&mut hasher, [0u8]);
+                Digest::update(&mut hasher, counter.to_le_bytes());
+                hasher.finalize().into()
+            })
+            .collect();
+
+        let mut hasher = Sha256::new();
+        Digest::update(&mut hasher, self.digest);
+        self.digest.0.copy_from_slice(hasher.finalize().as_slice());
+
+        blocks
+    }
+
+    /// Reduces the first 8 bytes of `hash`, read as a little-endian `u64`, modulo `P`.
+    fn extract_wide(hash: &[u8]) -> M31 {
+        let mut bytes = [0u8; 8];
+        bytes.copy_from_slice(&hash[0..8]);
+
+        let res = u64::from_le_bytes(bytes);
+        // Since 2^31 = 1 (mod P), folding the high bits keeps the value in the range [0, P^2)
+        // required by `reduce`.
+        M31::reduce((res >> 31) + (res & P as u64))
     }
 }
 
-impl Sha256Channel {
-    fn extract_common(hash: &[u8]) -> M31 {
-        let mut bytes = [0u8; 4];
-        bytes.copy_from_slice(&hash[0..4]);
+/// A [Sha256Channel] that mixes felts with a single hash per [Channel::mix_felts] call:
+/// `sha256(sha256_qm31(felt_0) || ... || sha256_qm31(felt_n) || digest)`, instead of chaining a
+/// hash per felt. Everything else is the same as [Sha256Channel].
+///
+/// Mixing more than one felt at once gives a different transcript than [Sha256Channel], so this
+/// is a separate channel, used through
+/// [crate::core::vcs::sha256_merkle::Sha256StreamingMerkleChannel].
+#[derive(Default, Clone)]
+pub struct Sha256StreamingChannel(pub Sha256Channel);
+
+impl Channel for Sha256StreamingChannel {
+    const BYTES_PER_HASH: usize = Sha256Channel::BYTES_PER_HASH;
+
+    fn mix_felts(&mut self, felts: &[SecureField]) {
+        add_hashes(felts.len() + 1);
+        let mut hasher = Sha256::new();
+        for felt in felts.iter() {
+            Digest::update(&mut hasher, sha256_qm31(felt));
+        }
+        Digest::update(&mut hasher, self.0.digest);
+        self.0.update_digest(hasher.finalize().as_slice().into());
+    }
+
+    fn mix_nonce(&mut self, nonce: u64) {
+        self.0.mix_nonce(nonce)
+    }
+
+    fn mix_u64(&mut self, value: u64) {
+        self.0.mix_u64(value)
+    }
+
+    fn mix_bytes(&mut self, bytes: &[u8]) {
+        self.0.mix_bytes(bytes)
+    }
+
+    fn mix_felt_groups(&mut self, groups: &[&[SecureField]]) {
+        self.0.mix_felt_groups(groups)
+    }
+
+    fn draw_felt(&mut self) -> SecureField {
+        self.0.draw_felt()
+    }
+
+    fn draw_felts(&mut self, n_felts: usize) -> Vec<SecureField> {
+        self.0.draw_felts(n_felts)
+    }
+
+    fn draw_base_felts(&mut self, n_felts: usize) -> Vec<BaseField> {
+        self.0.draw_base_felts(n_felts)
+    }
+
+    fn draw_random_bytes(&mut self) -> Vec<u8> {
+        self.0.draw_random_bytes()
+    }
 
-        let mut res = u32::from_le_bytes(bytes);
-        res &= 0x7fffffff;
-        res %= (1 << 31) - 1;
+    fn trailing_zeros(&self) -> u32 {
+        self.0.trailing_zeros()
+    }
 
-        M31::from(res)
+    fn leading_zeros(&self) -> u32 {
+        self.0.leading_zeros()
+    }
+}
+
+/// A [Sha256Channel] that draws several felts per digest advance, from a counter-indexed stream
+/// of blocks `sha256(digest || [0] || counter)`, with a little-endian `u32` counter. Everything
+/// else is the same as [Sha256Channel].
+///
+/// [Channel::draw_felts] takes two felts from each block, from bytes `0..16` and `16..32`, and
+/// [Channel::draw_base_felts] takes [FELTS_PER_HASH], both extracted as in [Channel::draw_felt].
+/// The digest is then advanced once, to `sha256(digest)`. This needs about 4 times fewer hashes
+/// than [Sha256Channel], which draws each felt with its own digest advance.
+///
+/// The drawn felts differ from those of [Sha256Channel], so this is a new version of the
+/// transcript, used through [crate::core::vcs::sha256_merkle::Sha256BatchedMerkleChannel].
+#[derive(Default, Clone)]
+pub struct Sha256BatchedChannel(pub Sha256Channel);
+
+impl Channel for Sha256BatchedChannel {
+    const BYTES_PER_HASH: usize = Sha256Channel::BYTES_PER_HASH;
+
+    fn mix_felts(&mut self, felts: &[SecureField]) {
+        self.0.mix_felts(felts)
+    }
+
+    fn mix_nonce(&mut self, nonce: u64) {
+        self.0.mix_nonce(nonce)
+    }
+
+    fn mix_u64(&mut self, value: u64) {
+        self.0.mix_u64(value)
+    }
+
+    fn mix_bytes(&mut self, bytes: &[u8]) {
+        self.0.mix_bytes(bytes)
+    }
+
+    fn mix_felt_groups(&mut self, groups: &[&[SecureField]]) {
+        self.0.mix_felt_groups(groups)
+    }
+
+    fn draw_felt(&mut self) -> SecureField {
+        self.0.draw_felt()
+    }
+
+    fn draw_felts(&mut self, n_felts: usize) -> Vec<SecureField> {
+        let blocks = self
+            .0
+            .squeeze_blocks(n_felts.div_ceil(EXTENSION_FELTS_PER_HASH));
+        let mut res = blocks
+            .iter()
+            .flat_map(|block| block.chunks_exact(16))
+            .map(|felt_bytes| extract_secure_field(felt_bytes.try_into().unwrap()))
+            .collect_vec();
+        res.truncate(n_felts);
+        res
+    }
+
+    fn draw_base_felts(&mut self, n_felts: usize) -> Vec<BaseField> {
+        let blocks = self.0.squeeze_blocks(n_felts.div_ceil(FELTS_PER_HASH));
+        let mut res = blocks
+            .iter()
+            .flat_map(|block| block.chunks_exact(4))
+            .map(|felt_bytes| extract_base_field(felt_bytes.try_into().unwrap()))
+            .collect_vec();
+        res.truncate(n_felts);
+        res
+    }
+
+    fn draw_random_bytes(&mut self) -> Vec<u8> {
+        self.0.draw_random_bytes()
+    }
+
+    fn trailing_zeros(&self) -> u32 {
+        self.0.trailing_zeros()
+    }
+
+    fn leading_zeros(&self) -> u32 {
+        self.0.leading_zeros()
     }
 }
 
@@ -124,9 +407,18 @@ impl Sha256Channel {
 mod tests {
     use std::collections::BTreeSet;
 
+    use itertools::Itertools;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+    use sha2::{Digest, Sha256};
+
+    use super::{Sha256BatchedChannel, Sha256StreamingChannel};
     use crate::core::channel::{Channel, Sha256Channel};
+    use crate::core::fields::m31::P;
     use crate::core::fields::qm31::SecureField;
-    use crate::m31;
+    use crate::core::utils::sha256_qm31;
+    use crate::core::vcs::sha256_hash::Sha256Hash;
+    use crate::{m31, qm31};
 
     #[test]
     fn test_draw_random_bytes() {
@@ -138,6 +430,252 @@ mod tests {
         assert_ne!(first_random_bytes, channel.draw_random_bytes());
     }
 
+    #[test]
+    fn test_draw_bits() {
+        let mut channel = Sha256Channel::default();
+        let mut bytes_channel = channel.clone();
+
+        let bits = channel.draw_bits(64);
+        let small_bits = (0..4096).map(|_| channel.draw_bits(3)).collect::<Vec<_>>();
+
+        let bytes = bytes_channel.draw_random_bytes();
+        assert_eq!(bits, u64::from_le_bytes(bytes[..8].try_into().unwrap()));
+        assert_eq!(channel.draw_bits(0), 0);
+        assert!(channel.draw_bits(13) < 1 << 13);
+        // Each of the 8 values is drawn about 512 times.
+        for value in 0..8 {
+            let count = small_bits.iter().filter(|&&bits| bits == value).count();
+            assert!((412..612).contains(&count), "{value} drawn {count} times.");
+        }
+    }
+
+    #[test]
+    fn test_draw_queries() {
+        for log_domain_size in [0, 1, 5, 16, 31, 32] {
+            let mut channel = Sha256Channel::default();
+            channel.mix_u64(log_domain_size as u64);
+            let mut replay_channel = channel.clone();
+
+            let queries = channel.draw_queries(100, log_domain_size);
+
+            assert_eq!(queries, replay_channel.draw_queries(100, log_domain_size));
+            assert_eq!(queries.len(), 100);
+            assert!(queries
+                .iter()
+                .all(|&q| (q as u64) < 1u64 << log_domain_size));
+            if log_domain_size >= 5 {
+                assert!(queries
+                    .iter()
+                    .any(|&q| q as u64 >= 1u64 << (log_domain_size - 1)));
+            }
+        }
+    }
+
+    #[test]
+    fn test_mix_decommitted_values() {
+        let columns = vec![vec![m31!(1), m31!(2)], vec![m31!(3)], vec![]];
+        let mut channel = Sha256Channel::default();
+        let mut replay_channel = channel.clone();
+        let mut altered_channel = channel.clone();
+        let mut regrouped_channel = channel.clone();
+        let mut altered_columns = columns.clone();
+        altered_columns[1][0] = m31!(4);
+
+        channel.mix_decommitted_values(&columns);
+        replay_channel.mix_decommitted_values(&columns);
+        altered_channel.mix_decommitted_values(&altered_columns);
+        regrouped_channel.mix_decommitted_values(&[vec![m31!(1)], vec![m31!(2), m31!(3)]]);
+
+        assert_eq!(channel.digest(), replay_channel.digest());
+        assert_ne!(channel.digest(), altered_channel.digest());
+        assert_ne!(channel.digest(), regrouped_channel.digest());
+        assert_ne!(channel.digest(), Sha256Channel::default().digest());
+    }
+
+    #[test]
+    fn test_draw_felt_regression() {
+        let mut channel = Sha256Channel::default();
+        channel.mix_u64(7);
+
+        let felt = channel.draw_felt();
+        let felts = channel.draw_felts(3);
+
+        assert_eq!(felt, qm31!(1153986927, 286757759, 1993681103, 1665177840));
+        assert_eq!(
+            felts,
+            [
+                qm31!(1720308332, 59837902, 1191751025, 838424290),
+                qm31!(728930912, 1689185603, 1116821768, 1860087130),
+                qm31!(1616588059, 1966690753, 804544950, 911708557),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_draw_random_bytes_matches_fresh_hashes() {
+        let mut channel = Sha256Channel::default();
+        channel.mix_u64(7);
+        let mut digest = channel.digest();
+
+        for _ in 0..100 {
+            let bytes = channel.draw_random_bytes();
+
+            assert_eq!(
+                bytes,
+                Sha256::digest([digest.as_ref(), &[0]].concat()).to_vec()
+            );
+            digest = Sha256::digest(digest).as_slice().into();
+            assert_eq!(channel.digest(), digest);
+        }
+    }
+
+    #[test]
+    fn test_draw_base_felt_array() {
+        let mut channel = Sha256Channel::default();
+        channel.mix_u64(1);
+        let mut felt_channel = channel.clone();
+        let mut bytes_channel = channel.clone();
+
+        let felts = channel.draw_base_felt_array();
+
+        assert_eq!(felts[..4], felt_channel.draw_felt().to_m31_array());
+        let bytes = bytes_channel.draw_random_bytes();
+        let expected = bytes
+            .chunks_exact(4)
+            .map(|chunk| m31!(u32::from_le_bytes(chunk.try_into().unwrap()) & 0x7fffffff))
+            .collect_vec();
+        assert_eq!(felts.to_vec(), expected);
+        assert_eq!(channel.digest(), felt_channel.digest());
+        assert_eq!(
+            channel.draw_base_felt_array(),
+            felt_channel.draw_base_felt_array()
+        );
+        assert_ne!(channel.draw_base_felt_array(), felts);
+    }
+
+    #[test]
+    fn test_draw_base_felts() {
+        let mut channel = Sha256Channel::default();
+        let mut array_channel = channel.clone();
+
+        let felts = channel.draw_base_felts(20);
+
+        let expected = (0..3)
+            .flat_map(|_| array_channel.draw_base_felt_array())
+            .take(20)
+            .collect_vec();
+        assert_eq!(felts, expected);
+        assert_eq!(channel.digest(), array_channel.digest());
+    }
+
+    #[test]
+    fn test_batched_draw_base_felts() {
+        let mut channel = Sha256BatchedChannel::default();
+        let initial_digest = channel.0.digest();
+
+        let first_felts = channel.draw_base_felts(20);
+        let after_first_digest = channel.0.digest();
+        let second_felts = channel.draw_base_felts(20);
+
+        assert_eq!(first_felts.len(), 20);
+        assert_ne!(first_felts, second_felts);
+        assert!(first_felts.iter().chain(&second_felts).all(|x| x.0 < P));
+        // The digest advances once per call.
+        let mut expected_channel = Sha256Channel::default();
+        expected_channel.update_digest(initial_digest);
+        expected_channel.draw_felt();
+        assert_eq!(after_first_digest, expected_channel.digest());
+        // Drawing fewer felts yields a prefix.
+        let mut prefix_channel = Sha256BatchedChannel::default();
+        assert_eq!(prefix_channel.draw_base_felts(3), first_felts[..3]);
+    }
+
+    #[test]
+    fn test_derive_prover_randomness() {
+        let mut channel = Sha256Channel::default();
+        channel.mix_u64(7);
+        let mut untouched_channel = channel.clone();
+
+        let randomness = channel.derive_prover_randomness(b"blinding", 100);
+
+        assert_eq!(randomness.len(), 100);
+        assert_eq!(
+            channel.derive_prover_randomness(b"blinding", 100),
+            randomness
+        );
+        assert_ne!(
+            channel.derive_prover_randomness(b"masking", 100),
+            randomness
+        );
+        assert_eq!(channel.digest(), untouched_channel.digest());
+        assert_eq!(channel.draw_felt(), untouched_channel.draw_felt());
+        assert_ne!(
+            channel.derive_prover_randomness(b"blinding", 100),
+            randomness
+        );
+    }
+
+    #[cfg(feature = "zeroize")]
+    #[test]
+    fn test_digest_is_zeroized_on_drop() {
+        use std::mem::MaybeUninit;
+
+        let mut channel = MaybeUninit::new(Sha256Channel::default());
+        // Safety: the channel is initialized.
+        unsafe { channel.assume_init_mut() }.mix_u64(1);
+        assert_ne!(unsafe { channel.assume_init_ref() }.digest.0, [0; 32]);
+
+        // Safety: the channel is initialized, and is not used after being dropped. Its storage is
+        // owned by the `MaybeUninit`, so its bytes can still be read.
+        unsafe { channel.assume_init_drop() };
+        let bytes = unsafe {
+            std::slice::from_raw_parts(
+                channel.as_ptr() as *const u8,
+                std::mem::size_of::<Sha256Channel>(),
+            )
+        };
+
+        assert!(bytes.iter().all(|&byte| byte == 0));
+    }
+
+    #[test]
+    fn test_checkpoint_and_restore() {
+        let mut channel = Sha256Channel::default();
+        channel.draw_felts(3);
+
+        let checkpoint = channel.checkpoint();
+        let felts = channel.draw_felts(4);
+        channel.mix_u64(1);
+        let felt = channel.draw_felt();
+        channel.restore(checkpoint);
+
+        assert_eq!(channel.draw_felts(4), felts);
+        channel.mix_u64(1);
+        assert_eq!(channel.draw_felt(), felt);
+    }
+
+    #[test]
+    fn test_handover_matches_uninterrupted_run() {
+        let mut channel = Sha256Channel::default();
+        channel.mix_u64(7);
+        channel.draw_felts(3);
+        let mut uninterrupted = channel.clone();
+
+        let transmitted = bincode::serialize(&channel).unwrap();
+        let mut resumed: Sha256Channel = bincode::deserialize(&transmitted).unwrap();
+        let transmitted = bincode::serialize(&resumed.clone().into_digest()).unwrap();
+        let mut resumed_from_digest =
+            Sha256Channel::from_digest(bincode::deserialize(&transmitted).unwrap());
+
+        for channel in [&mut uninterrupted, &mut resumed, &mut resumed_from_digest] {
+            channel.mix_felts(&[qm31!(1, 2, 3, 4)]);
+        }
+        let expected = uninterrupted.draw_felts(4);
+        assert_eq!(resumed.draw_felts(4), expected);
+        assert_eq!(resumed_from_digest.draw_felts(4), expected);
+        assert_eq!(resumed.digest(), uninterrupted.digest());
+    }
+
     #[test]
     pub fn test_draw_felt() {
         let mut channel = Sha256Channel::default();
@@ -162,6 +700,170 @@ mod tests {
         );
     }
 
+    #[test]
+    pub fn test_draw_no_felts_keeps_digest() {
+        let mut channel = Sha256Channel::default();
+        channel.mix_u64(1);
+        let mut batched_channel = Sha256BatchedChannel(channel.clone());
+        let digest = channel.digest;
+
+        assert!(channel.draw_felts(0).is_empty());
+        assert!(channel.draw_base_felts(0).is_empty());
+        assert!(batched_channel.draw_felts(0).is_empty());
+        assert!(batched_channel.draw_base_felts(0).is_empty());
+        assert_eq!(channel.digest, digest);
+        assert_eq!(batched_channel.0.digest, digest);
+    }
+
+    #[test]
+    pub fn test_draw_felts_regression() {
+        let mut channel = Sha256Channel::default();
+
+        let random_felts = channel.draw_felts(3);
+
+        assert_eq!(
+            random_felts,
+            vec![
+                qm31!(832478335, 1247183532, 59057455, 1009592031),
+                qm31!(1471526124, 657500383, 445601966, 1055113438),
+                qm31!(1391459955, 233860323, 494784664, 2068436758),
+            ]
+        );
+        assert_eq!(
+            channel.digest,
+            Sha256Hash::from_hex(
+                "12771355e46cd47c71ed1721fd5319b383cca3a1f9fce3aa1c8cd3bd37af20d7"
+            )
+            .unwrap()
+        );
+    }
+
+    #[test]
+    pub fn test_batched_draw_felts_regression() {
+        let mut channel = Sha256BatchedChannel::default();
+
+        let random_felts = channel.draw_felts(3);
+
+        assert_eq!(
+            random_felts,
+            vec![
+                qm31!(1604920491, 1573572164, 1833692458, 1574791685),
+                qm31!(1291073925, 1355470444, 820524133, 682110664),
+                qm31!(1196046088, 357042219, 252989322, 2093746736),
+            ]
+        );
+        assert_eq!(
+            channel.0.digest,
+            Sha256Hash::from_hex(
+                "66687aadf862bd776c8fc18b8e9f8e20089714856ee233b3902a591d0d5f2925"
+            )
+            .unwrap()
+        );
+    }
+
+    /// Runs a fixed sequence of mix and draw operations on `channel`, whose underlying
+    /// [Sha256Channel] is given by `sha256_channel`. Returns the drawn felts, the drawn random
+    /// bytes and the digest after each operation.
+    fn run_golden_transcript<C: Channel>(
+        mut channel: C,
+        sha256_channel: fn(&mut C) -> &mut Sha256Channel,
+    ) -> (Vec<SecureField>, Vec<u8>, Vec<Sha256Hash>) {
+        let mut felts = vec![];
+        let mut digests = vec![];
+
+        channel.mix_felts(&[qm31!(1, 2, 3, 4), qm31!(5, 6, 7, 8)]);
+        digests.push(sha256_channel(&mut channel).digest);
+        felts.push(channel.draw_felt());
+        digests.push(sha256_channel(&mut channel).digest);
+        channel.mix_nonce(12345);
+        digests.push(sha256_channel(&mut channel).digest);
+        felts.extend(channel.draw_felts(3));
+        digests.push(sha256_channel(&mut channel).digest);
+        channel.mix_u64(1 << 40);
+        digests.push(sha256_channel(&mut channel).digest);
+        channel.mix_bytes(b"golden");
+        digests.push(sha256_channel(&mut channel).digest);
+        let random_bytes = channel.draw_random_bytes();
+        digests.push(sha256_channel(&mut channel).digest);
+        felts.push(sha256_channel(&mut channel).draw_felt_wide());
+        digests.push(sha256_channel(&mut channel).digest);
+
+        (felts, random_bytes, digests)
+    }
+
+    /// Guards against accidental transcript changes. Any change to the channel's hashing breaks
+    /// this test, and must come with updated golden values.
+    #[test]
+    pub fn test_golden_transcript() {
+        const GOLDEN_DIGESTS: [&str; 8] = [
+            "955974197f01f449eb73cef3c8a7ecb185030549f0c3394b78df540781cace77",
+            "f3d78548d1bb579bdf4187fe929087467b6dcde9cc2d7f1a34d9f6e9264cf84e",
+            "83c4dd2aae7bf8c70e52ce4655a11c6de6dda8f85b8eb499fc10f1fb9057dee8",
+            "2e644d7acff89cec24fd3e3231347ef6a7d5cb65099d70193bba9e45b0b76358",
+            "4e91012c62b9ced710e9c1bda5688ace21f525a392fe9867600239f36df85c68",
+            "50f26fba90ed69b52c86e5f0317aea158f34af0fe40f925bd68452e58afa7dae",
+            "8017ca6391d0d7837acabb12e4ab5c0f4a9afd06fba6e4a03c125644d6d20350",
+            "5c3113ec935883333f51d5571896f4839bdee5067dcf79a990822ab1516726f8",
+        ];
+        const GOLDEN_RANDOM_BYTES: &str =
+            "da99cada4df19085fd14874672ee9aaf7961196a482e293d7ccd3b676d7b4196";
+
+        let (felts, random_bytes, digests) =
+            run_golden_transcript(Sha256Channel::default(), |channel| channel);
+
+        assert_eq!(
+            felts,
+            vec![
+                qm31!(1426818626, 365051101, 913478374, 1041082525),
+                qm31!(1448204567, 1729067342, 1022893524, 1759958121),
+                qm31!(2057280060, 258879915, 1690189155, 1004429445),
+                qm31!(585802065, 1837036725, 1452289070, 1412628862),
+                qm31!(1046359328, 1875406347, 1155973110, 1174314280),
+            ]
+        );
+        assert_eq!(hex::encode(random_bytes), GOLDEN_RANDOM_BYTES);
+        assert_eq!(
+            digests,
+            GOLDEN_DIGESTS.map(|digest| Sha256Hash::from_hex(digest).unwrap())
+        );
+    }
+
+    /// Like [test_golden_transcript], for the [Sha256BatchedChannel] version of the transcript.
+    #[test]
+    pub fn test_batched_golden_transcript() {
+        const GOLDEN_DIGESTS: [&str; 8] = [
+            "955974197f01f449eb73cef3c8a7ecb185030549f0c3394b78df540781cace77",
+            "f3d78548d1bb579bdf4187fe929087467b6dcde9cc2d7f1a34d9f6e9264cf84e",
+            "83c4dd2aae7bf8c70e52ce4655a11c6de6dda8f85b8eb499fc10f1fb9057dee8",
+            "069ff641e997b63fe51b8afb891ef16169809b9381dee864eb388e9f5b66ee57",
+            "c77880d8b35fbfdcb02cbc10745ca5b7382eb5309df5da584a3413d44f08dec9",
+            "02feb3b648237cbcbef1286f7fa03d1bf256c54d9bdfa7103d8442a9e7f06834",
+            "0f790bec2c17c5f71570548285490f675e9a497e76de95ab3c9dd7d4a1e3df0e",
+            "34e73dab3e0fc126367518e62095a812966d704eedb3acefb8d56610750e4bf5",
+        ];
+        const GOLDEN_RANDOM_BYTES: &str =
+            "b82591033b691e488e75a1d32afc88d9cd7d026f18d694ecdec142f301304233";
+
+        let (felts, random_bytes, digests) =
+            run_golden_transcript(Sha256BatchedChannel::default(), |channel| &mut channel.0);
+
+        assert_eq!(
+            felts,
+            vec![
+                qm31!(1426818626, 365051101, 913478374, 1041082525),
+                qm31!(114483815, 1577440894, 120632718, 1957484665),
+                qm31!(1832244505, 1188145904, 492485387, 1163713416),
+                qm31!(603955293, 1144356980, 279193721, 2021541609),
+                qm31!(275010657, 1262207036, 1139962949, 253215720),
+            ]
+        );
+        assert_eq!(hex::encode(random_bytes), GOLDEN_RANDOM_BYTES);
+        assert_eq!(
+            digests,
+            GOLDEN_DIGESTS.map(|digest| Sha256Hash::from_hex(digest).unwrap())
+        );
+    }
+
     #[test]
     pub fn test_mix_felts() {
         let mut channel = Sha256Channel::default();
@@ -174,4 +876,210 @@ mod tests {
 
         assert_ne!(initial_digest, channel.digest);
     }
+
+    #[test]
+    pub fn test_mix_u64() {
+        let mut channel = Sha256Channel::default();
+        let initial_digest = channel.digest;
+        let mut other_channel = channel.clone();
+
+        channel.mix_u64(1);
+        other_channel.mix_u64(2);
+
+        assert_ne!(initial_digest, channel.digest);
+        assert_ne!(channel.digest, other_channel.digest);
+    }
+
+    #[test]
+    pub fn test_mix_u64_is_separated_from_mix_bytes() {
+        let mut channel = Sha256Channel::default();
+        let mut bytes_channel = channel.clone();
+
+        channel.mix_u64(1);
+        bytes_channel.mix_bytes(&1u64.to_le_bytes());
+
+        assert_ne!(channel.digest, bytes_channel.digest);
+    }
+
+    #[test]
+    pub fn test_mix_bytes() {
+        let mut channel = Sha256Channel::default();
+        let initial_digest = channel.digest;
+        let mut other_channel = channel.clone();
+
+        channel.mix_bytes(b"stwo");
+        other_channel.mix_bytes(b"stwo\0");
+
+        assert_ne!(initial_digest, channel.digest);
+        assert_ne!(channel.digest, other_channel.digest);
+    }
+
+    #[test]
+    pub fn test_mix_bytes_is_separated_from_other_mixes() {
+        let felt = qm31!(1, 2, 3, 4);
+        let nonce = 12345u64;
+        let mut nonce_bytes = nonce.to_le_bytes().to_vec();
+        nonce_bytes.resize(32, 0);
+
+        let mut nonce_channel = Sha256Channel::default();
+        nonce_channel.mix_nonce(nonce);
+        let mut felts_channel = Sha256Channel::default();
+        felts_channel.mix_felts(&[felt]);
+        let [mut nonce_bytes_channel, mut felt_bytes_channel] =
+            [(); 2].map(|_| Sha256Channel::default());
+        nonce_bytes_channel.mix_bytes(&nonce_bytes);
+        felt_bytes_channel.mix_bytes(&sha256_qm31(&felt));
+
+        assert_ne!(nonce_bytes_channel.digest, nonce_channel.digest);
+        assert_ne!(felt_bytes_channel.digest, felts_channel.digest);
+    }
+
+    #[test]
+    pub fn test_mix_bytes_is_reproducible_and_order_sensitive() {
+        let mut channel = Sha256Channel::default();
+        let mut same_channel = channel.clone();
+        let mut swapped_channel = channel.clone();
+
+        for bytes in [&b"circuit-a"[..], &[1]] {
+            channel.mix_bytes(bytes);
+            same_channel.mix_bytes(bytes);
+        }
+        for bytes in [&[1], &b"circuit-a"[..]] {
+            swapped_channel.mix_bytes(bytes);
+        }
+
+        assert_eq!(channel.digest, same_channel.digest);
+        assert_ne!(channel.digest, swapped_channel.digest);
+    }
+
+    #[test]
+    pub fn test_mix_felt_groups_binds_grouping() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let felts: [SecureField; 3] = rng.gen();
+        let groupings: [&[&[SecureField]]; 4] = [
+            &[&felts[..2], &felts[2..]],
+            &[&felts[..1], &felts[1..]],
+            &[&felts],
+            &[&felts, &[]],
+        ];
+
+        let digests = groupings.map(|groups| {
+            let mut channel = Sha256Channel::default();
+            channel.mix_felt_groups(groups);
+            channel.digest
+        });
+
+        let mut felts_channel = Sha256Channel::default();
+        felts_channel.mix_felts(&felts);
+        assert!(digests.iter().map(|digest| digest.0).all_unique());
+        assert!(!digests.contains(&felts_channel.digest));
+    }
+
+    #[test]
+    pub fn test_streaming_mix_felts() {
+        let felts = [qm31!(1, 2, 3, 4), qm31!(5, 6, 7, 8), qm31!(9, 10, 11, 12)];
+        let mix = |felts: &[SecureField]| {
+            let mut channel = Sha256Channel::default();
+            let mut streaming_channel = Sha256StreamingChannel::default();
+            channel.mix_felts(felts);
+            streaming_channel.mix_felts(felts);
+            (channel.digest, streaming_channel.0.digest)
+        };
+
+        let (digest, streaming_digest) = mix(&felts);
+
+        assert_ne!(digest, streaming_digest);
+        assert_eq!(mix(&felts), (digest, streaming_digest));
+        assert_eq!(
+            streaming_digest.to_hex(),
+            "f91543948bf61190276359c56b4f610caa816de04d70d636cc1be9de4f477daa"
+        );
+        // A single felt is mixed the same way by both channels.
+        let (digest, streaming_digest) = mix(&felts[..1]);
+        assert_eq!(digest, streaming_digest);
+    }
+
+    #[test]
+    pub fn test_draw_felt_wide() {
+        let mut channel = Sha256Channel::default();
+        let mut narrow_channel = channel.clone();
+
+        let first_random_felt = channel.draw_felt_wide();
+
+        // Assert that the drawn felt is not the one `draw_felt` draws, but the state is the same.
+        assert_ne!(first_random_felt, narrow_channel.draw_felt());
+        assert_eq!(channel.digest, narrow_channel.digest);
+        // Assert that next random felt is different.
+        assert_ne!(first_random_felt, channel.draw_felt_wide());
+    }
+
+    #[test]
+    pub fn test_draw_felt_wide_bias_bound() {
+        // A uniform 64-bit value reduced modulo P hits `2^64 mod P` residues `q + 1` times and the
+        // rest `q` times, where `q = 2^64 / P`. The statistical distance from uniform is therefore
+        // `r * (P - r) / (P * 2^64)`, where `r = 2^64 mod P`.
+        let p = P as u128;
+        let r = (1u128 << 64) % p;
+
+        // Assert that the statistical distance is below 2^-33.
+        assert!((r * (p - r)) << 33 < p << 64);
+
+        // Assert that the reduction matches the reference.
+        for value in [
+            0,
+            1,
+            P as u64 - 1,
+            P as u64,
+            1 << 62,
+            u64::MAX - 1,
+            u64::MAX,
+        ] {
+            let extracted = Sha256Channel::extract_wide(&value.to_le_bytes());
+            assert_eq!(extracted.0 as u64, value % P as u64);
+        }
+    }
+
+    #[test]
+    fn test_leading_and_trailing_zeros() {
+        let mut bytes = [0xff; 32];
+        bytes[..2].fill(0);
+        bytes[2] = 0x0f;
+        bytes[29..].copy_from_slice(&[0x01, 0, 0]);
+        let channel = Sha256Channel {
+            digest: Sha256Hash(bytes),
+        };
+
+        assert_eq!(channel.leading_zeros(), 20);
+        assert_eq!(channel.trailing_zeros(), 23);
+        assert_eq!(Sha256Channel::default().leading_zeros(), 256);
+    }
+
+    #[test]
+    pub fn test_trailing_zeros_matches_reference() {
+        fn reference_trailing_zeros(bytes: &[u8]) -> u32 {
+            let mut n_bits = 0;
+            for byte in bytes.iter().rev() {
+                if *byte == 0 {
+                    n_bits += 8;
+                } else {
+                    n_bits += byte.leading_zeros();
+                    break;
+                }
+            }
+            n_bits
+        }
+        let mut rng = SmallRng::seed_from_u64(0);
+
+        for _ in 0..1000 {
+            let mut bytes: [u8; 32] = rng.gen();
+            // Zero a random suffix to cover long runs of zeros, including the all zero digest.
+            let n_zero_bytes = rng.gen_range(0..=32);
+            bytes[32 - n_zero_bytes..].fill(0);
+            let channel = Sha256Channel {
+                digest: Sha256Hash::from(&bytes[..]),
+            };
+
+            assert_eq!(channel.trailing_zeros(), reference_trailing_zeros(&bytes));
+        }
+    }
 }
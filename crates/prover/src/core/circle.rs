@@ -132,6 +132,8 @@ impl<F: Zero + Add<Output = F> + FieldExpOps + Sub<Output = F> + Neg<Output = F>
     }
 }
 
+/// The inverse in the circle group, i.e. `p + (-p)` is [CirclePoint::zero]. This is the
+/// conjugate point `(x, -y)`.
 impl<F: Zero + Add<Output = F> + FieldExpOps + Sub<Output = F> + Neg<Output = F>> Neg
     for CirclePoint<F>
 {
@@ -468,7 +470,9 @@ mod tests {
 
     use super::{CirclePointIndex, Coset};
     use crate::core::channel::sha256::Sha256Channel;
-    use crate::core::circle::{CirclePoint, SECURE_FIELD_CIRCLE_GEN};
+    use crate::core::circle::{
+        CirclePoint, M31_CIRCLE_GEN, M31_CIRCLE_LOG_ORDER, SECURE_FIELD_CIRCLE_GEN,
+    };
     use crate::core::fields::qm31::{SecureField, P4};
     use crate::core::fields::FieldExpOps;
     use crate::core::poly::circle::CanonicCoset;
@@ -510,6 +514,19 @@ mod tests {
         )
     }
 
+    #[test]
+    fn test_circle_point_neg() {
+        let mut channel = Sha256Channel::default();
+        for index in [0, 1, 5, 1 << 20, (1 << M31_CIRCLE_LOG_ORDER) - 1] {
+            let p = M31_CIRCLE_GEN.mul(index);
+            let q = CirclePoint::get_random_point(&mut channel);
+
+            assert_eq!(p + (-p), CirclePoint::zero());
+            assert_eq!(q + (-q), CirclePoint::zero());
+            assert_eq!(-(-q), q);
+        }
+    }
+
     #[test]
     pub fn test_get_random_circle_point() {
         let mut channel = Sha256Channel::default();
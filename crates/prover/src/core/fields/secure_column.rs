@@ -17,6 +17,27 @@ impl SecureColumnByCoords<CpuBackend> {
     pub fn to_vec(&self) -> Vec<SecureField> {
         (0..self.len()).map(|i| self.at(i)).collect()
     }
+
+    /// Folds adjacent pairs of values by a random challenge `alpha`, halving the column length.
+    /// The value at index `i` becomes `self[2 * i] + alpha * self[2 * i + 1]`.
+    ///
+    /// The folded values are written to the first half of the existing storage.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the column length is odd.
+    pub fn fold_in_place(&mut self, alpha: SecureField) {
+        assert!(self.len() % 2 == 0, "Column length must be even.");
+        let half_len = self.len() / 2;
+        // Index `i` is written only after indices `2 * i` and `2 * i + 1` were read.
+        for i in 0..half_len {
+            let folded = self.at(2 * i) + alpha * self.at(2 * i + 1);
+            self.set(i, folded);
+        }
+        self.columns
+            .iter_mut()
+            .for_each(|column| column.truncate(half_len));
+    }
 }
 impl<B: FieldOps<BaseField>> SecureColumnByCoords<B> {
     pub fn at(&self, index: usize) -> SecureField {
@@ -104,3 +125,35 @@ impl From<SecureColumnByCoords<CpuBackend>> for Vec<SecureField> {
         column.into_iter().collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::SecureColumnByCoords;
+    use crate::core::backend::CpuBackend;
+    use crate::core::fields::qm31::SecureField;
+
+    #[test]
+    fn test_fold_in_place() {
+        const LOG_SIZE: u32 = 12;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let values = (0..1 << LOG_SIZE).map(|_| rng.gen()).collect_vec();
+        let alpha = rng.gen::<SecureField>();
+        let mut column = values
+            .iter()
+            .copied()
+            .collect::<SecureColumnByCoords<CpuBackend>>();
+
+        column.fold_in_place(alpha);
+
+        let expected = values
+            .iter()
+            .tuples()
+            .map(|(&a, &b)| a + alpha * b)
+            .collect_vec();
+        assert_eq!(column.to_vec(), expected);
+    }
+}
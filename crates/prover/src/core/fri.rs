@@ -10,7 +10,7 @@ use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use tracing::{span, Level};
 
-use super::backend::CpuBackend;
+use super::backend::{simd, CpuBackend};
 use super::channel::{Channel, MerkleChannel};
 use super::fields::m31::BaseField;
 use super::fields::qm31::SecureField;
@@ -32,13 +32,15 @@ use crate::core::vcs::prover::{MerkleDecommitment, MerkleProver};
 use crate::core::vcs::verifier::{MerkleVerificationError, MerkleVerifier};
 
 /// FRI proof config
-// TODO(andrew): Support different step sizes.
 #[derive(Debug, Clone, Copy)]
 pub struct FriConfig {
     pub log_blowup_factor: u32,
     pub log_last_layer_degree_bound: u32,
     pub n_queries: usize,
-    // TODO(andrew): fold_steps.
+    /// The number of times univariate polynomials are folded between committed layers, i.e. each
+    /// layer is folded by `2^fold_step`. The last inner layer is folded less if fewer folds are
+    /// left until the last layer. See [FriConfig::with_fold_step].
+    pub fold_step: u32,
 }
 
 impl FriConfig {
@@ -52,6 +54,10 @@ impl FriConfig {
     const LOG_BLOWUP_FACTOR_RANGE: RangeInclusive<u32> =
         Self::LOG_MIN_BLOWUP_FACTOR..=Self::LOG_MAX_BLOWUP_FACTOR;
 
+    const MIN_FOLD_STEP: u32 = 1;
+    const MAX_FOLD_STEP: u32 = 4;
+    const FOLD_STEP_RANGE: RangeInclusive<u32> = Self::MIN_FOLD_STEP..=Self::MAX_FOLD_STEP;
+
     /// Creates a new FRI configuration.
     ///
     /// # Panics
@@ -66,7 +72,40 @@ impl FriConfig {
             log_blowup_factor,
             log_last_layer_degree_bound,
             n_queries,
+            fold_step: FOLD_STEP,
+        }
+    }
+
+    /// Returns the config with univariate polynomials folded `2^fold_step` times between
+    /// committed layers, instead of twice.
+    ///
+    /// Larger folds give fewer layers, so fewer commitments and decommitments in a proof, at the
+    /// cost of opening `2^fold_step` values per query in each layer.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `fold_step` is zero or greater than 4.
+    pub fn with_fold_step(self, fold_step: u32) -> Self {
+        assert!(Self::FOLD_STEP_RANGE.contains(&fold_step));
+        Self { fold_step, ..self }
+    }
+
+    /// Returns the number of folds from a line polynomial of degree bound `2^log_degree_bound` to
+    /// the next layer, or `None` if it is the last layer.
+    fn layer_fold_step(&self, log_degree_bound: u32) -> Option<u32> {
+        let n_folds_left = log_degree_bound.checked_sub(self.log_last_layer_degree_bound)?;
+        (n_folds_left > 0).then(|| self.fold_step.min(n_folds_left))
+    }
+
+    /// Returns the number of inner layers when folding a line polynomial of degree bound
+    /// `2^log_degree_bound` down to the last layer.
+    fn n_inner_layers(&self, mut log_degree_bound: u32) -> usize {
+        let mut n_inner_layers = 0;
+        while let Some(fold_step) = self.layer_fold_step(log_degree_bound) {
+            log_degree_bound -= fold_step;
+            n_inner_layers += 1;
         }
+        n_inner_layers
     }
 
     fn last_layer_domain_size(&self) -> usize {
@@ -212,10 +251,20 @@ impl<B: FriOps + MerkleOps<MC::H>, MC: MerkleChannel> FriProver<B, MC> {
         );
 
         while layer_evaluation.len() > config.last_layer_domain_size() {
-            let layer = FriLayerProver::new(layer_evaluation);
+            let log_degree_bound = layer_evaluation.len().ilog2() - config.log_blowup_factor;
+            let fold_step = config.layer_fold_step(log_degree_bound).unwrap();
+            let layer = FriLayerProver::new(layer_evaluation, fold_step);
             MC::mix_root(channel, layer.merkle_tree.root());
             let folding_alpha = channel.draw_felt();
-            let folded_layer_evaluation = B::fold_line(&layer.evaluation, folding_alpha, twiddles);
+            // Folding `f` by `2^k` with `alpha` is folding it `k` times with `alpha^(2^i)`.
+            let mut folded_layer_evaluation =
+                B::fold_line(&layer.evaluation, folding_alpha, twiddles);
+            let mut alpha_power = folding_alpha;
+            for _ in 1..fold_step {
+                alpha_power = alpha_power.square();
+                folded_layer_evaluation =
+                    B::fold_line(&folded_layer_evaluation, alpha_power, twiddles);
+            }
 
             layer_evaluation = folded_layer_evaluation;
             layers.push(layer);
@@ -279,11 +328,12 @@ impl<B: FriOps + MerkleOps<MC::H>, MC: MerkleChannel> FriProver<B, MC> {
             .inner_layers
             .into_iter()
             .scan(first_layer_queries, |layer_queries, layer| {
+                let fold_step = layer.fold_step;
                 let layer_proof = layer.decommit(layer_queries);
-                *layer_queries = layer_queries.fold(FOLD_STEP);
+                *layer_queries = layer_queries.fold(fold_step);
                 Some(layer_proof)
             })
-            .collect();
+            .collect_vec();
 
         let last_layer_poly = self.last_layer_poly;
 
@@ -336,6 +386,17 @@ impl<MC: MerkleChannel> FriVerifier<MC> {
         assert!(column_bounds.is_sorted_by_key(|b| Reverse(*b)));
 
         let max_column_bound = column_bounds[0];
+        let n_inner_layers =
+            config.n_inner_layers(max_column_bound.fold_to_line().log_degree_bound);
+        if proof.inner_layers.len() < n_inner_layers {
+            return Err(FriVerificationError::TruncatedProof {
+                expected: n_inner_layers,
+                actual: proof.inner_layers.len(),
+            });
+        }
+        if proof.inner_layers.len() > n_inner_layers {
+            return Err(FriVerificationError::InvalidNumFriLayers);
+        }
         let expected_query_log_domain_size =
             max_column_bound.log_degree_bound + config.log_blowup_factor;
 
@@ -349,6 +410,10 @@ impl<MC: MerkleChannel> FriVerifier<MC> {
         ));
 
         for (layer_index, proof) in proof.inner_layers.into_iter().enumerate() {
+            let fold_step = config
+                .layer_fold_step(layer_bound.log_degree_bound)
+                .ok_or(FriVerificationError::InvalidNumFriLayers)?;
+
             MC::mix_root(channel, proof.commitment);
 
             let folding_alpha = channel.draw_felt();
@@ -357,14 +422,17 @@ impl<MC: MerkleChannel> FriVerifier<MC> {
                 degree_bound: layer_bound,
                 domain: layer_domain,
                 folding_alpha,
+                fold_step,
                 layer_index,
                 proof,
             });
 
             layer_bound = layer_bound
-                .fold(FOLD_STEP)
+                .fold(fold_step)
                 .ok_or(FriVerificationError::InvalidNumFriLayers)?;
-            layer_domain = layer_domain.double();
+            for _ in 0..fold_step {
+                layer_domain = layer_domain.double();
+            }
         }
 
         if layer_bound.log_degree_bound != config.log_last_layer_degree_bound {
@@ -554,6 +622,8 @@ pub trait FriChannel {
 pub enum FriVerificationError {
     #[error("proof contains an invalid number of FRI layers")]
     InvalidNumFriLayers,
+    #[error("expected {expected} FRI layers but proof contains {actual}")]
+    TruncatedProof { expected: usize, actual: usize },
     #[error("queries do not resolve to their commitment in layer {layer}")]
     InnerLayerCommitmentInvalid {
         layer: usize,
@@ -617,6 +687,10 @@ impl LinePolyDegreeBound {
 
 /// A FRI proof.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "H::Hash: Serialize",
+    deserialize = "H::Hash: Deserialize<'de>"
+))]
 pub struct FriProof<H: MerkleHasher> {
     pub inner_layers: Vec<FriLayerProof<H>>,
     pub last_layer_poly: LinePoly,
@@ -633,6 +707,10 @@ pub const CIRCLE_TO_LINE_FOLD_STEP: u32 = 1;
 ///
 /// The subset corresponds to the set of evaluations needed by a FRI verifier.
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "H::Hash: Serialize",
+    deserialize = "H::Hash: Deserialize<'de>"
+))]
 pub struct FriLayerProof<H: MerkleHasher> {
     /// The subset stored corresponds to the set of evaluations the verifier doesn't have but needs
     /// to fold and verify the merkle decommitment.
@@ -645,6 +723,8 @@ pub struct FriLayerVerifier<H: MerkleHasher> {
     pub degree_bound: LinePolyDegreeBound,
     pub domain: LineDomain,
     pub folding_alpha: SecureField,
+    /// The number of folds from this layer to the next. See [FriConfig::fold_step].
+    pub fold_step: u32,
     pub layer_index: usize,
     pub proof: FriLayerProof<H>,
 }
@@ -679,14 +759,14 @@ impl<H: MerkleHasher> FriLayerVerifier<H> {
             .flat_map(|e| e.values.into_iter())
             .collect();
 
-        let folded_queries = queries.fold(FOLD_STEP);
+        let folded_queries = queries.fold(self.fold_step);
 
         // Positions of all the decommitment evals.
         let decommitment_positions = folded_queries
             .iter()
             .flat_map(|folded_query| {
-                let start = folded_query << FOLD_STEP;
-                let end = start + (1 << FOLD_STEP);
+                let start = folded_query << self.fold_step;
+                let end = start + (1 << self.fold_step);
                 start..end
             })
             .collect::<Vec<usize>>();
@@ -709,7 +789,7 @@ impl<H: MerkleHasher> FriLayerVerifier<H> {
                 error: e,
             })?;
 
-        let evals_at_folded_queries = sparse_evaluation.fold(self.folding_alpha);
+        let evals_at_folded_queries = sparse_evaluation.fold(self.folding_alpha, self.fold_step);
 
         Ok((folded_queries, evals_at_folded_queries))
     }
@@ -737,9 +817,10 @@ impl<H: MerkleHasher> FriLayerVerifier<H> {
         let mut all_subline_evals = Vec::new();
 
         // Group queries by the subline they reside in.
-        for subline_queries in queries.group_by(|a, b| a >> FOLD_STEP == b >> FOLD_STEP) {
-            let subline_start = (subline_queries[0] >> FOLD_STEP) << FOLD_STEP;
-            let subline_end = subline_start + (1 << FOLD_STEP);
+        let fold_step = self.fold_step;
+        for subline_queries in queries.group_by(|a, b| a >> fold_step == b >> fold_step) {
+            let subline_start = (subline_queries[0] >> fold_step) << fold_step;
+            let subline_end = subline_start + (1 << fold_step);
 
             let mut subline_evals = Vec::new();
             let mut subline_queries = subline_queries.iter().peekable();
@@ -762,7 +843,7 @@ impl<H: MerkleHasher> FriLayerVerifier<H> {
             // TODO(andrew): Create a constructor for LineDomain.
             let subline_initial_index = bit_reverse_index(subline_start, self.domain.log_size());
             let subline_initial = self.domain.coset().index_at(subline_initial_index);
-            let subline_domain = LineDomain::new(Coset::new(subline_initial, FOLD_STEP));
+            let subline_domain = LineDomain::new(Coset::new(subline_initial, fold_step));
 
             all_subline_evals.push(LineEvaluation::new(
                 subline_domain,
@@ -777,22 +858,22 @@ impl<H: MerkleHasher> FriLayerVerifier<H> {
             });
         }
 
-        Ok(SparseLineEvaluation::new(all_subline_evals))
+        Ok(SparseLineEvaluation::new(all_subline_evals, fold_step))
     }
 }
 
 /// A FRI layer comprises of a merkle tree that commits to evaluations of a polynomial.
 ///
 /// The polynomial evaluations are viewed as evaluation of a polynomial on multiple distinct cosets
-/// of size two. Each leaf of the merkle tree commits to a single coset evaluation.
-// TODO(andrew): Support different step sizes.
+/// of size `2^fold_step`. Each query decommits the evaluations of its coset.
 struct FriLayerProver<B: FriOps + MerkleOps<H>, H: MerkleHasher> {
     evaluation: LineEvaluation<B>,
     merkle_tree: MerkleProver<B, H>,
+    fold_step: u32,
 }
 
 impl<B: FriOps + MerkleOps<H>, H: MerkleHasher> FriLayerProver<B, H> {
-    fn new(evaluation: LineEvaluation<B>) -> Self {
+    fn new(evaluation: LineEvaluation<B>, fold_step: u32) -> Self {
         // TODO(spapini): Commit on slice.
         // TODO(spapini): Merkle tree in backend.
         let merkle_tree = MerkleProver::commit(evaluation.values.columns.iter().collect_vec());
@@ -800,6 +881,7 @@ impl<B: FriOps + MerkleOps<H>, H: MerkleHasher> FriLayerProver<B, H> {
         FriLayerProver {
             evaluation,
             merkle_tree,
+            fold_step,
         }
     }
 
@@ -810,9 +892,10 @@ impl<B: FriOps + MerkleOps<H>, H: MerkleHasher> FriLayerProver<B, H> {
 
         // Group queries by the subline they reside in.
         // TODO(andrew): Explain what a "subline" is at the top of the module.
-        for query_group in queries.group_by(|a, b| a >> FOLD_STEP == b >> FOLD_STEP) {
-            let subline_start = (query_group[0] >> FOLD_STEP) << FOLD_STEP;
-            let subline_end = subline_start + (1 << FOLD_STEP);
+        let fold_step = self.fold_step;
+        for query_group in queries.group_by(|a, b| a >> fold_step == b >> fold_step) {
+            let subline_start = (query_group[0] >> fold_step) << fold_step;
+            let subline_end = subline_start + (1 << fold_step);
 
             let mut subline_queries = query_group.iter().peekable();
 
@@ -905,18 +988,46 @@ struct SparseLineEvaluation {
 impl SparseLineEvaluation {
     /// # Panics
     ///
-    /// Panics if the evaluation domain sizes don't equal the folding factor.
-    fn new(subline_evals: Vec<LineEvaluation<CpuBackend>>) -> Self {
-        let folding_factor = 1 << FOLD_STEP;
+    /// Panics if the evaluation domain sizes don't equal the folding factor `2^fold_step`.
+    fn new(subline_evals: Vec<LineEvaluation<CpuBackend>>, fold_step: u32) -> Self {
+        let folding_factor = 1 << fold_step;
         assert!(subline_evals.iter().all(|e| e.len() == folding_factor));
         Self { subline_evals }
     }
 
-    fn fold(self, alpha: SecureField) -> Vec<SecureField> {
-        self.subline_evals
-            .into_iter()
-            .map(|e| fold_line(&e, alpha).values.at(0))
-            .collect()
+    /// Folds each subline of size `2^fold_step` into a single value, as the prover folds a layer.
+    ///
+    /// Sublines of size 2 are folded as [fold_line] would, batching the inversions and the
+    /// recombination across sublines.
+    fn fold(self, alpha: SecureField, fold_step: u32) -> Vec<SecureField> {
+        if fold_step != 1 {
+            return self
+                .subline_evals
+                .into_iter()
+                .map(|e| {
+                    let mut folded = fold_line(&e, alpha);
+                    let mut alpha_power = alpha;
+                    for _ in 1..fold_step {
+                        alpha_power = alpha_power.square();
+                        folded = fold_line(&folded, alpha_power);
+                    }
+                    folded.values.at(0)
+                })
+                .collect();
+        }
+        let xs = self
+            .subline_evals
+            .iter()
+            .map(|e| e.domain().at(0))
+            .collect_vec();
+        let mut x_inverses = vec![BaseField::zero(); xs.len()];
+        BaseField::batch_inverse(&xs, &mut x_inverses);
+        let pairs = self
+            .subline_evals
+            .iter()
+            .map(|e| [e.values.at(0), e.values.at(1)])
+            .collect_vec();
+        simd::fri::fold_line_pairs(&pairs, &x_inverses, alpha)
     }
 }
 
@@ -990,7 +1101,10 @@ mod tests {
     use itertools::Itertools;
     use num_traits::{One, Zero};
 
-    use super::{get_opening_positions, FriVerificationError, SparseCircleEvaluation};
+    use super::{
+        get_opening_positions, FriVerificationError, SparseCircleEvaluation, SparseLineEvaluation,
+        FOLD_STEP,
+    };
     use crate::core::backend::cpu::{CpuCircleEvaluation, CpuCirclePoly};
     use crate::core::backend::{Col, Column, ColumnOps, CpuBackend};
     use crate::core::circle::{CirclePointIndex, Coset};
@@ -1016,6 +1130,38 @@ mod tests {
     type FriProver = super::FriProver<CpuBackend, Sha256MerkleChannel>;
     type FriVerifier = super::FriVerifier<Sha256MerkleChannel>;
 
+    #[test]
+    fn sparse_line_evaluation_fold_matches_per_query_fold() {
+        const LOG_SIZE: u32 = 8;
+        const N_QUERIES: usize = 100;
+        let domain = LineDomain::new(Coset::half_odds(LOG_SIZE));
+        let alpha = SecureField::from_u32_unchecked(3, 5, 7, 11);
+        let subline_evals = (0..N_QUERIES)
+            .map(|query| {
+                let start = (query * 37 % (1 << (LOG_SIZE - 1))) << 1;
+                let initial_index = domain.coset().index_at(bit_reverse_index(start, LOG_SIZE));
+                let subline_domain = LineDomain::new(Coset::new(initial_index, 1));
+                let values = [0, 1]
+                    .map(|i| {
+                        let v = (4 * (query + i)) as u32;
+                        SecureField::from_u32_unchecked(v, v + 1, v + 2, v + 3)
+                    })
+                    .into_iter()
+                    .collect();
+                LineEvaluation::new(subline_domain, values)
+            })
+            .collect_vec();
+        let per_query_folds = subline_evals
+            .iter()
+            .map(|e| fold_line(e, alpha).values.at(0))
+            .collect_vec();
+
+        let batched_folds =
+            SparseLineEvaluation::new(subline_evals, FOLD_STEP).fold(alpha, FOLD_STEP);
+
+        assert_eq!(batched_folds, per_query_folds);
+    }
+
     #[test]
     fn fold_line_works() {
         const DEGREE: usize = 8;
@@ -1145,6 +1291,70 @@ mod tests {
         verifier.decommit_on_queries(&queries, vec![decommitment_value])
     }
 
+    #[test]
+    fn valid_proof_with_larger_fold_step_passes_verification() -> Result<(), FriVerificationError> {
+        const LOG_DEGREE: u32 = 6;
+        const LAST_LAYER_LOG_BOUND: u32 = 0;
+        let evaluation = polynomial_evaluation(LOG_DEGREE, LOG_BLOWUP_FACTOR);
+        let log_domain_size = evaluation.domain.log_size();
+        let queries = Queries::from_positions(vec![2, 5, 6, 31, 100], log_domain_size);
+        let twiddles = CpuBackend::precompute_twiddles(evaluation.domain.half_coset);
+        let decommitment_value = query_polynomial(&evaluation, &queries);
+        let bound = vec![CirclePolyDegreeBound::new(LOG_DEGREE)];
+        let n_inner_layers = |config| {
+            let prover = FriProver::commit(
+                &mut test_channel(),
+                config,
+                &[evaluation.clone()],
+                &twiddles,
+            );
+            let proof = prover.decommit_on_queries(&queries);
+            let n_inner_layers = proof.inner_layers.len();
+            let verifier =
+                FriVerifier::commit(&mut test_channel(), config, proof, bound.clone()).unwrap();
+            verifier
+                .decommit_on_queries(&queries, vec![decommitment_value.clone()])
+                .map(|()| n_inner_layers)
+        };
+        let config = FriConfig::new(LAST_LAYER_LOG_BOUND, LOG_BLOWUP_FACTOR, queries.len());
+
+        let fold_by_2_layers = n_inner_layers(config)?;
+        let fold_by_4_layers = n_inner_layers(config.with_fold_step(2))?;
+        let fold_by_8_layers = n_inner_layers(config.with_fold_step(3))?;
+
+        assert_eq!(fold_by_2_layers, 5);
+        assert_eq!(fold_by_4_layers, 3);
+        assert_eq!(fold_by_8_layers, 2);
+        Ok(())
+    }
+
+    #[test]
+    fn proof_with_mismatched_fold_step_fails_verification() {
+        const LOG_DEGREE: u32 = 6;
+        let evaluation = polynomial_evaluation(LOG_DEGREE, LOG_BLOWUP_FACTOR);
+        let log_domain_size = evaluation.domain.log_size();
+        let queries = Queries::from_positions(vec![5], log_domain_size);
+        let config = FriConfig::new(0, LOG_BLOWUP_FACTOR, queries.len());
+        let prover = FriProver::commit(
+            &mut test_channel(),
+            config.with_fold_step(2),
+            &[evaluation.clone()],
+            &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
+        );
+        let proof = prover.decommit_on_queries(&queries);
+        let bound = vec![CirclePolyDegreeBound::new(LOG_DEGREE)];
+
+        let verifier = FriVerifier::commit(&mut test_channel(), config, proof, bound);
+
+        assert!(matches!(
+            verifier,
+            Err(FriVerificationError::TruncatedProof {
+                expected: 5,
+                actual: 3
+            })
+        ));
+    }
+
     #[test]
     fn proof_with_removed_layer_fails_verification() {
         const LOG_DEGREE: u32 = 6;
@@ -1168,10 +1378,49 @@ mod tests {
 
         assert!(matches!(
             verifier,
-            Err(FriVerificationError::InvalidNumFriLayers)
+            Err(FriVerificationError::TruncatedProof {
+                expected: 4,
+                actual: 3
+            })
         ));
     }
 
+    #[test]
+    fn proof_with_dropped_layer_fails_verification() {
+        const LOG_DEGREE: u32 = 6;
+        let evaluation = polynomial_evaluation(LOG_DEGREE, LOG_BLOWUP_FACTOR);
+        let log_domain_size = evaluation.domain.log_size();
+        let queries = Queries::from_positions(vec![1], log_domain_size);
+        let config = FriConfig::new(2, LOG_BLOWUP_FACTOR, queries.len());
+        let prover = FriProver::commit(
+            &mut test_channel(),
+            config,
+            &[evaluation.clone()],
+            &CpuBackend::precompute_twiddles(evaluation.domain.half_coset),
+        );
+        let mut proof = prover.decommit_on_queries(&queries);
+        proof.inner_layers.pop();
+        let bound = vec![CirclePolyDegreeBound::new(LOG_DEGREE)];
+        let channel = &mut test_channel();
+
+        let verifier = FriVerifier::commit(channel, config, proof, bound);
+
+        let error = verifier.err().unwrap();
+        assert!(matches!(
+            error,
+            FriVerificationError::TruncatedProof {
+                expected: 3,
+                actual: 2
+            }
+        ));
+        assert_eq!(
+            error.to_string(),
+            "expected 3 FRI layers but proof contains 2"
+        );
+        // Nothing was processed.
+        assert_eq!(channel.digest(), test_channel().digest());
+    }
+
     #[test]
     fn proof_with_added_layer_fails_verification() {
         const LOG_DEGREE: u32 = 6;
@@ -12,7 +12,8 @@ mod utils;
 mod verifier;
 
 pub use self::prover::{
-    CommitmentSchemeProof, CommitmentSchemeProver, CommitmentTreeProver, TreeBuilder,
+    CommitmentSchemeProof, CommitmentSchemeProver, CommitmentTreeError, CommitmentTreeProver,
+    TreeBuilder,
 };
 pub use self::utils::TreeVec;
 pub use self::verifier::CommitmentSchemeVerifier;
@@ -1,7 +1,9 @@
 use std::collections::BTreeMap;
+use std::iter::zip;
 
 use itertools::Itertools;
 use serde::{Deserialize, Serialize};
+use thiserror::Error;
 use tracing::{span, Level};
 
 use super::super::circle::CirclePoint;
@@ -15,7 +17,7 @@ use super::quotients::{compute_fri_quotients, PointSample};
 use super::utils::TreeVec;
 use super::{PcsConfig, TreeColumnSpan};
 use crate::core::backend::BackendForChannel;
-use crate::core::channel::{Channel, MerkleChannel};
+use crate::core::channel::{uncounted, Channel, MerkleChannel};
 use crate::core::poly::circle::{CircleEvaluation, CirclePoly};
 use crate::core::poly::twiddles::TwiddleTree;
 use crate::core::vcs::ops::MerkleHasher;
@@ -37,11 +39,16 @@ impl<'a, B: BackendForChannel<MC>, MC: MerkleChannel> CommitmentSchemeProver<'a,
         }
     }
 
-    fn commit(&mut self, polynomials: ColumnVec<CirclePoly<B>>, channel: &mut MC::C) {
+    fn commit(
+        &mut self,
+        polynomials: ColumnVec<CirclePoly<B>>,
+        log_blowup_factors: &[u32],
+        channel: &mut MC::C,
+    ) {
         let _span = span!(Level::INFO, "Commitment").entered();
-        let tree = CommitmentTreeProver::new(
+        let tree = CommitmentTreeProver::new_with_log_blowup_factors(
             polynomials,
-            self.config.fri_config.log_blowup_factor,
+            log_blowup_factors,
             channel,
             self.twiddles,
         );
@@ -53,6 +60,7 @@ impl<'a, B: BackendForChannel<MC>, MC: MerkleChannel> CommitmentSchemeProver<'a,
             tree_index: self.trees.len(),
             commitment_scheme: self,
             polys: Vec::default(),
+            log_blowup_factors: Vec::default(),
         }
     }
 
@@ -112,7 +120,8 @@ impl<'a, B: BackendForChannel<MC>, MC: MerkleChannel> CommitmentSchemeProver<'a,
 
         // Proof of work.
         let span1 = span!(Level::INFO, "Grind").entered();
-        let proof_of_work = B::grind(channel, self.config.pow_bits);
+        // Proof of work attempts are not part of the hash count of a proof.
+        let proof_of_work = uncounted(|| B::grind(channel, self.config.pow_bits));
         span1.exit();
         channel.mix_nonce(proof_of_work);
 
@@ -142,6 +151,10 @@ impl<'a, B: BackendForChannel<MC>, MC: MerkleChannel> CommitmentSchemeProver<'a,
 }
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "H::Hash: Serialize",
+    deserialize = "H::Hash: Deserialize<'de>"
+))]
 pub struct CommitmentSchemeProof<H: MerkleHasher> {
     pub sampled_values: TreeVec<ColumnVec<Vec<SecureField>>>,
     pub decommitments: TreeVec<MerkleDecommitment<H>>,
@@ -154,6 +167,7 @@ pub struct TreeBuilder<'a, 'b, B: BackendForChannel<MC>, MC: MerkleChannel> {
     tree_index: usize,
     commitment_scheme: &'a mut CommitmentSchemeProver<'b, B, MC>,
     polys: ColumnVec<CirclePoly<B>>,
+    log_blowup_factors: ColumnVec<u32>,
 }
 impl<'a, 'b, B: BackendForChannel<MC>, MC: MerkleChannel> TreeBuilder<'a, 'b, B, MC> {
     pub fn extend_evals(
@@ -162,7 +176,6 @@ impl<'a, 'b, B: BackendForChannel<MC>, MC: MerkleChannel> TreeBuilder<'a, 'b, B,
         max_degree: u32,
     ) -> TreeColumnSpan {
         let span = span!(Level::INFO, "Interpolation for commitment").entered();
-        let col_start = self.polys.len();
         let polys = columns
             .into_iter()
             .map(|eval| {
@@ -171,16 +184,41 @@ impl<'a, 'b, B: BackendForChannel<MC>, MC: MerkleChannel> TreeBuilder<'a, 'b, B,
             })
             .collect_vec();
         span.exit();
-        self.polys.extend(polys);
-        TreeColumnSpan {
-            tree_index: self.tree_index,
-            col_start,
-            col_end: self.polys.len(),
-        }
+        self.extend_polys(polys)
     }
 
     pub fn extend_polys(&mut self, polys: ColumnVec<CirclePoly<B>>) -> TreeColumnSpan {
+        let log_blowup_factor = self.commitment_scheme.config.fri_config.log_blowup_factor;
+        self.extend_polys_with_log_blowup_factor(polys, log_blowup_factor)
+    }
+
+    /// Adds polynomials to the tree, to be evaluated on a domain blown up by
+    /// `2^log_blowup_factor`.
+    ///
+    /// Only the blowup factor of the FRI config is supported, as
+    /// [CommitmentSchemeVerifier::commit_with_log_blowup_factors] requires: FRI can't check the
+    /// quotients of columns with a smaller factor, and doesn't enforce the degree bound of columns
+    /// with a larger one.
+    ///
+    /// [CommitmentSchemeVerifier::commit_with_log_blowup_factors]: super::CommitmentSchemeVerifier::commit_with_log_blowup_factors
+    ///
+    /// # Panics
+    ///
+    /// Panics if `log_blowup_factor` differs from the FRI config log blowup factor.
+    pub fn extend_polys_with_log_blowup_factor(
+        &mut self,
+        polys: ColumnVec<CirclePoly<B>>,
+        log_blowup_factor: u32,
+    ) -> TreeColumnSpan {
+        let fri_log_blowup_factor = self.commitment_scheme.config.fri_config.log_blowup_factor;
+        assert_eq!(
+            log_blowup_factor, fri_log_blowup_factor,
+            "Log blowup factor {log_blowup_factor} differs from the FRI log blowup factor \
+            {fri_log_blowup_factor}."
+        );
         let col_start = self.polys.len();
+        self.log_blowup_factors
+            .extend(std::iter::repeat(log_blowup_factor).take(polys.len()));
         self.polys.extend(polys);
         TreeColumnSpan {
             tree_index: self.tree_index,
@@ -191,7 +229,8 @@ impl<'a, 'b, B: BackendForChannel<MC>, MC: MerkleChannel> TreeBuilder<'a, 'b, B,
 
     pub fn commit(self, channel: &mut MC::C) {
         let _span = span!(Level::INFO, "Commitment").entered();
-        self.commitment_scheme.commit(self.polys, channel);
+        self.commitment_scheme
+            .commit(self.polys, &self.log_blowup_factors, channel);
     }
 }
 
@@ -204,16 +243,68 @@ pub struct CommitmentTreeProver<B: BackendForChannel<MC>, MC: MerkleChannel> {
 }
 
 impl<B: BackendForChannel<MC>, MC: MerkleChannel> CommitmentTreeProver<B, MC> {
+    /// Evaluates the polynomials on their blown up domains and commits to the evaluations.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the polynomials are invalid. See [Self::validate_polynomials].
     pub fn new(
         polynomials: ColumnVec<CirclePoly<B>>,
         log_blowup_factor: u32,
         channel: &mut MC::C,
         twiddles: &TwiddleTree<B>,
     ) -> Self {
+        let log_blowup_factors = vec![log_blowup_factor; polynomials.len()];
+        Self::new_with_log_blowup_factors(polynomials, &log_blowup_factors, channel, twiddles)
+    }
+
+    /// Returns the root [Self::new] commits the polynomials to, without a channel. Pinning the
+    /// root of known polynomials in tests catches changes to the FFT, the ordering of the
+    /// evaluations or the hashing.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the polynomials are invalid. See [Self::validate_polynomials].
+    pub fn root_of(
+        polynomials: ColumnVec<CirclePoly<B>>,
+        log_blowup_factor: u32,
+        twiddles: &TwiddleTree<B>,
+    ) -> <MC::H as MerkleHasher>::Hash {
+        Self::new(
+            polynomials,
+            log_blowup_factor,
+            &mut MC::C::default(),
+            twiddles,
+        )
+        .commitment
+        .root()
+    }
+
+    /// Like [Self::new], but evaluates each polynomial on a domain blown up by its own factor.
+    /// Columns of the same domain size are committed on the same layer, regardless of their
+    /// polynomial sizes.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the polynomials are invalid. See
+    /// [Self::validate_polynomials_with_log_blowup_factors].
+    pub fn new_with_log_blowup_factors(
+        polynomials: ColumnVec<CirclePoly<B>>,
+        log_blowup_factors: &[u32],
+        channel: &mut MC::C,
+        twiddles: &TwiddleTree<B>,
+    ) -> Self {
+        if let Err(error) = Self::validate_polynomials_with_log_blowup_factors(
+            &polynomials,
+            log_blowup_factors,
+            twiddles,
+        ) {
+            panic!("Invalid polynomials: {error}");
+        }
+
         let span = span!(Level::INFO, "Extension").entered();
-        let evaluations = polynomials
-            .iter()
-            .map(|poly| {
+        let evaluations = zip(&polynomials, log_blowup_factors)
+            .map(|(poly, &log_blowup_factor)| {
                 poly.evaluate_with_twiddles(
                     CanonicCoset::new(poly.log_size() + log_blowup_factor).circle_domain(),
                     twiddles,
@@ -234,6 +325,57 @@ impl<B: BackendForChannel<MC>, MC: MerkleChannel> CommitmentTreeProver<B, MC> {
         }
     }
 
+    /// Checks that the polynomials can be committed to by [Self::new]: there is at least one
+    /// polynomial, and the twiddles cover the evaluation domain of each polynomial, i.e. the
+    /// canonic domain of log size `log_size + log_blowup_factor`.
+    pub fn validate_polynomials(
+        polynomials: &[CirclePoly<B>],
+        log_blowup_factor: u32,
+        twiddles: &TwiddleTree<B>,
+    ) -> Result<(), CommitmentTreeError> {
+        let log_blowup_factors = vec![log_blowup_factor; polynomials.len()];
+        Self::validate_polynomials_with_log_blowup_factors(
+            polynomials,
+            &log_blowup_factors,
+            twiddles,
+        )
+    }
+
+    /// Like [Self::validate_polynomials], with a log blowup factor per polynomial.
+    pub fn validate_polynomials_with_log_blowup_factors(
+        polynomials: &[CirclePoly<B>],
+        log_blowup_factors: &[u32],
+        twiddles: &TwiddleTree<B>,
+    ) -> Result<(), CommitmentTreeError> {
+        if polynomials.is_empty() {
+            return Err(CommitmentTreeError::NoPolynomials);
+        }
+        if polynomials.len() != log_blowup_factors.len() {
+            return Err(CommitmentTreeError::LogBlowupFactorsMismatch {
+                n_polynomials: polynomials.len(),
+                n_log_blowup_factors: log_blowup_factors.len(),
+            });
+        }
+        let max_domain_log_size = twiddles.root_coset.log_size + 1;
+        for (poly_index, (poly, &log_blowup_factor)) in
+            zip(polynomials, log_blowup_factors).enumerate()
+        {
+            let domain_log_size = poly.log_size() + log_blowup_factor;
+            if domain_log_size > max_domain_log_size {
+                return Err(CommitmentTreeError::DomainTooLarge {
+                    poly_index,
+                    domain_log_size,
+                    max_domain_log_size,
+                });
+            }
+            let domain = CanonicCoset::new(domain_log_size).circle_domain();
+            if !domain.half_coset.is_doubling_of(twiddles.root_coset) {
+                return Err(CommitmentTreeError::IncompatibleTwiddles { poly_index });
+            }
+        }
+        Ok(())
+    }
+
     /// Decommits the merkle tree on the given query positions.
     /// Returns the values at the queried positions and the decommitment.
     /// The queries are given as a mapping from the log size of the layer size to the queried
@@ -250,3 +392,271 @@ impl<B: BackendForChannel<MC>, MC: MerkleChannel> CommitmentTreeProver<B, MC> {
         self.commitment.decommit(queries, eval_vec)
     }
 }
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum CommitmentTreeError {
+    #[error("No polynomials to commit to.")]
+    NoPolynomials,
+    #[error("Got {n_log_blowup_factors} log blowup factors for {n_polynomials} polynomials.")]
+    LogBlowupFactorsMismatch {
+        n_polynomials: usize,
+        n_log_blowup_factors: usize,
+    },
+    #[error(
+        "Polynomial {poly_index} is evaluated on a domain of log size {domain_log_size}, but the \
+        twiddles only support domains of log size up to {max_domain_log_size}."
+    )]
+    DomainTooLarge {
+        poly_index: usize,
+        domain_log_size: u32,
+        max_domain_log_size: u32,
+    },
+    #[error("The twiddles are not computed for the evaluation domain of polynomial {poly_index}.")]
+    IncompatibleTwiddles { poly_index: usize },
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::{CommitmentSchemeProver, CommitmentTreeError, CommitmentTreeProver};
+    use crate::core::backend::cpu::CpuCircleEvaluation;
+    use crate::core::backend::simd::SimdBackend;
+    use crate::core::backend::CpuBackend;
+    use crate::core::channel::Sha256Channel;
+    use crate::core::circle::SECURE_FIELD_CIRCLE_GEN;
+    use crate::core::fields::m31::BaseField;
+    use crate::core::fri::FriConfig;
+    use crate::core::pcs::quotients::{compute_fri_quotients, PointSample};
+    use crate::core::pcs::{CommitmentSchemeVerifier, PcsConfig, TreeVec};
+    use crate::core::poly::circle::{CanonicCoset, CirclePoly, PolyOps};
+    use crate::core::poly::BitReversedOrder;
+    use crate::core::prover::VerificationError;
+    use crate::core::utils::bit_reverse_index;
+    use crate::core::vcs::sha256_merkle::Sha256MerkleChannel;
+    use crate::{m31, qm31};
+
+    type TreeProver = CommitmentTreeProver<CpuBackend, Sha256MerkleChannel>;
+
+    #[test]
+    fn test_validate_polynomials() {
+        let polys =
+            [3, 6, 4].map(|log_size| CirclePoly::new(vec![BaseField::from(1); 1 << log_size]));
+        let twiddles = CpuBackend::precompute_twiddles(CanonicCoset::new(7).half_coset());
+        let conjugate_twiddles =
+            CpuBackend::precompute_twiddles(CanonicCoset::new(7).half_coset().conjugate());
+
+        assert_eq!(
+            TreeProver::validate_polynomials(&polys, 1, &twiddles),
+            Ok(())
+        );
+        assert_eq!(
+            TreeProver::validate_polynomials(&[], 1, &twiddles),
+            Err(CommitmentTreeError::NoPolynomials)
+        );
+        let error = TreeProver::validate_polynomials(&polys, 2, &twiddles).unwrap_err();
+        assert_eq!(
+            error,
+            CommitmentTreeError::DomainTooLarge {
+                poly_index: 1,
+                domain_log_size: 8,
+                max_domain_log_size: 7,
+            }
+        );
+        assert_eq!(
+            error.to_string(),
+            "Polynomial 1 is evaluated on a domain of log size 8, but the twiddles only support \
+            domains of log size up to 7."
+        );
+        assert_eq!(
+            TreeProver::validate_polynomials(&polys, 1, &conjugate_twiddles),
+            Err(CommitmentTreeError::IncompatibleTwiddles { poly_index: 0 })
+        );
+    }
+
+    #[test]
+    fn test_root_of_identity_polynomial() {
+        const LOG_SIZE: u32 = 6;
+        const LOG_BLOWUP_FACTOR: u32 = 1;
+        let domain = CanonicCoset::new(LOG_SIZE).circle_domain();
+        let xs = CpuCircleEvaluation::<BaseField, BitReversedOrder>::new(
+            domain,
+            (0..domain.size())
+                .map(|i| domain.at(bit_reverse_index(i, LOG_SIZE)).x)
+                .collect(),
+        );
+        let poly = xs.interpolate();
+        let point = SECURE_FIELD_CIRCLE_GEN;
+        assert_eq!(poly.eval_at_point(point), point.x);
+        let twiddles = CpuBackend::precompute_twiddles(
+            CanonicCoset::new(LOG_SIZE + LOG_BLOWUP_FACTOR).half_coset(),
+        );
+        let simd_twiddles = SimdBackend::precompute_twiddles(
+            CanonicCoset::new(LOG_SIZE + LOG_BLOWUP_FACTOR).half_coset(),
+        );
+        let simd_poly = CirclePoly::<SimdBackend>::new(poly.coeffs.iter().copied().collect());
+
+        let root = TreeProver::root_of(vec![poly], LOG_BLOWUP_FACTOR, &twiddles);
+
+        assert_eq!(
+            root.to_string(),
+            "f7b6dd7b376136eee2760c8396d63447cacfb26de480deea3f1af1aa3180983c"
+        );
+        assert_eq!(
+            CommitmentTreeProver::<SimdBackend, Sha256MerkleChannel>::root_of(
+                vec![simd_poly],
+                LOG_BLOWUP_FACTOR,
+                &simd_twiddles
+            ),
+            root
+        );
+    }
+
+    #[test]
+    fn test_commit_with_log_blowup_factors() {
+        const LOG_DOMAIN_SIZE: u32 = 6;
+        const LOG_BLOWUP_FACTORS: [u32; 2] = [1, 2];
+        const LOG_SIZES: [u32; 2] = [LOG_DOMAIN_SIZE - 1, LOG_DOMAIN_SIZE - 2];
+        let config = PcsConfig {
+            pow_bits: 5,
+            fri_config: FriConfig::new(0, 1, 3),
+        };
+        let twiddles = CpuBackend::precompute_twiddles(
+            CanonicCoset::new(LOG_DOMAIN_SIZE)
+                .circle_domain()
+                .half_coset,
+        );
+        let polys = LOG_SIZES.map(|log_size| {
+            CirclePoly::new((0..1 << log_size).map(|i| m31!(i * i + log_size)).collect())
+        });
+        let point = SECURE_FIELD_CIRCLE_GEN;
+
+        let tree = TreeProver::new_with_log_blowup_factors(
+            polys.to_vec(),
+            &LOG_BLOWUP_FACTORS,
+            &mut Sha256Channel::default(),
+            &twiddles,
+        );
+
+        let evaluations = tree.evaluations.iter().collect_vec();
+        assert!(evaluations
+            .iter()
+            .all(|eval| eval.domain.log_size() == LOG_DOMAIN_SIZE));
+        let samples = polys
+            .iter()
+            .map(|poly| {
+                vec![PointSample {
+                    point,
+                    value: poly.eval_at_point(point),
+                }]
+            })
+            .collect_vec();
+        let random_coeff = qm31!(1, 2, 3, 4);
+        let quotients = compute_fri_quotients(
+            &evaluations,
+            &samples,
+            random_coeff,
+            config.fri_config.log_blowup_factor,
+        );
+        assert_eq!(quotients.len(), 1);
+        let quotient = &quotients[0];
+        assert!(quotient.is_in_fri_space(LOG_SIZES[0]));
+        let blowup_2_quotient =
+            compute_fri_quotients(&evaluations[1..], &samples[1..], random_coeff, 1)
+                .pop()
+                .unwrap();
+        assert!(blowup_2_quotient.is_in_fri_space(LOG_SIZES[1]));
+
+        let mut verifier = CommitmentSchemeVerifier::<Sha256MerkleChannel>::new(config);
+        assert!(matches!(
+            verifier.commit_with_log_blowup_factors(
+                tree.commitment.root(),
+                &LOG_SIZES,
+                &LOG_BLOWUP_FACTORS,
+                &mut Sha256Channel::default(),
+            ),
+            Err(VerificationError::UnsupportedLogBlowupFactor {
+                column_index: 1,
+                log_blowup_factor: 2,
+                fri_log_blowup_factor: 1,
+            })
+        ));
+    }
+
+    #[test]
+    fn test_column_over_its_degree_bound_is_rejected() {
+        const LOG_DOMAIN_SIZE: u32 = 6;
+        const LOG_SIZE: u32 = LOG_DOMAIN_SIZE - 1;
+        let config = PcsConfig {
+            pow_bits: 5,
+            fri_config: FriConfig::new(0, 1, 3),
+        };
+        let twiddles = CpuBackend::precompute_twiddles(
+            CanonicCoset::new(LOG_DOMAIN_SIZE)
+                .circle_domain()
+                .half_coset,
+        );
+        let poly = CirclePoly::new((0..1 << LOG_SIZE).map(|i| m31!(i * i)).collect());
+        let point = SECURE_FIELD_CIRCLE_GEN;
+        let mut commitment_scheme =
+            CommitmentSchemeProver::<CpuBackend, Sha256MerkleChannel>::new(config, &twiddles);
+        let prover_channel = &mut Sha256Channel::default();
+        let mut tree_builder = commitment_scheme.tree_builder();
+        tree_builder.extend_polys(vec![poly]);
+        tree_builder.commit(prover_channel);
+        let sampled_points = TreeVec::new(vec![vec![vec![point]]]);
+        let proof = commitment_scheme.prove_values(sampled_points.clone(), prover_channel);
+        let root = commitment_scheme.roots()[0];
+
+        // The committed domain also fits a column of half the degree with twice the blowup.
+        let mut verifier = CommitmentSchemeVerifier::<Sha256MerkleChannel>::new(config);
+        let res = verifier.commit_with_log_blowup_factors(
+            root,
+            &[LOG_SIZE - 1],
+            &[2],
+            &mut Sha256Channel::default(),
+        );
+
+        assert!(matches!(
+            res,
+            Err(VerificationError::UnsupportedLogBlowupFactor { .. })
+        ));
+        let verifier_channel = &mut Sha256Channel::default();
+        let mut verifier = CommitmentSchemeVerifier::<Sha256MerkleChannel>::new(config);
+        verifier
+            .commit_with_log_blowup_factors(root, &[LOG_SIZE], &[1], verifier_channel)
+            .unwrap();
+        verifier
+            .verify_values(sampled_points, proof, verifier_channel)
+            .unwrap();
+    }
+
+    fn extend_with_log_blowup_factor(log_blowup_factor: u32) {
+        let config = PcsConfig {
+            pow_bits: 5,
+            fri_config: FriConfig::new(0, 1, 3),
+        };
+        let twiddles = CpuBackend::precompute_twiddles(CanonicCoset::new(5).half_coset());
+        let mut commitment_scheme =
+            CommitmentSchemeProver::<CpuBackend, Sha256MerkleChannel>::new(config, &twiddles);
+
+        commitment_scheme
+            .tree_builder()
+            .extend_polys_with_log_blowup_factor(
+                vec![CirclePoly::new(vec![m31!(1); 4])],
+                log_blowup_factor,
+            );
+    }
+
+    #[test]
+    #[should_panic(expected = "Log blowup factor 0 differs from the FRI log blowup factor 1.")]
+    fn test_log_blowup_factor_below_fri_panics() {
+        extend_with_log_blowup_factor(0);
+    }
+
+    #[test]
+    #[should_panic(expected = "Log blowup factor 2 differs from the FRI log blowup factor 1.")]
+    fn test_log_blowup_factor_above_fri_panics() {
+        extend_with_log_blowup_factor(2);
+    }
+}
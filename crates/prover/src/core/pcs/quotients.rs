@@ -4,6 +4,7 @@ use std::iter::zip;
 
 use indexmap::IndexMap;
 use itertools::{izip, multiunzip, Itertools};
+use thiserror::Error;
 use tracing::{span, Level};
 
 use crate::core::backend::cpu::quotients::{
@@ -35,9 +36,66 @@ pub trait QuotientOps: PolyOps {
         sample_batches: &[ColumnSampleBatch],
         log_blowup_factor: u32,
     ) -> SecureEvaluation<Self>;
+
+    /// Returns the quotient of each sample batch separately, as [Self::accumulate_quotients] on
+    /// that batch alone.
+    ///
+    /// [Self::accumulate_quotients] combines these quotients `q_0, ..., q_{n-1}` as
+    ///   `(...(q_0 * c_1 + q_1) * c_2 + ...) * c_{n-1} + q_{n-1}`
+    /// where `c_i` is `random_coeff` to the power of the number of columns in batch `i` (see
+    /// [batch_random_coeffs](crate::core::backend::cpu::quotients::batch_random_coeffs)).
+    fn accumulate_quotients_per_batch(
+        domain: CircleDomain,
+        columns: &[&CircleEvaluation<Self, BaseField, BitReversedOrder>],
+        random_coeff: SecureField,
+        sample_batches: &[ColumnSampleBatch],
+        log_blowup_factor: u32,
+    ) -> Vec<SecureEvaluation<Self>> {
+        sample_batches
+            .iter()
+            .map(|sample_batch| {
+                Self::accumulate_quotients(
+                    domain,
+                    columns,
+                    random_coeff,
+                    std::slice::from_ref(sample_batch),
+                    log_blowup_factor,
+                )
+            })
+            .collect()
+    }
+
+    /// Accumulates the quotients of columns sampled at several points, like
+    /// [Self::accumulate_quotients]. See [MultiPointSampleBatch].
+    ///
+    /// A batch sampled at `k` points has numerators of degree `2k - 1`, which must be at most
+    /// `max_numerator_degree`.
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if:
+    /// * The numerators of a batch have a degree above `max_numerator_degree`.
+    /// * Two points of a batch, or a point of a batch and its complex conjugate, have the same y
+    ///   coordinate.
+    fn accumulate_multi_point_quotients(
+        domain: CircleDomain,
+        columns: &[&CircleEvaluation<Self, BaseField, BitReversedOrder>],
+        random_coeff: SecureField,
+        sample_batches: &[MultiPointSampleBatch],
+        max_numerator_degree: usize,
+    ) -> Result<SecureEvaluation<Self>, QuotientError>;
+}
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum QuotientError {
+    #[error("Numerator of degree {degree} exceeds the degree bound {max_degree}.")]
+    NumeratorDegreeTooHigh { degree: usize, max_degree: usize },
+    #[error("Sample points do not have distinct y coordinates.")]
+    DuplicateSamplePoint,
 }
 
 /// A batch of column samplings at a point.
+#[derive(Clone, Debug, PartialEq, Eq)]
 pub struct ColumnSampleBatch {
     /// The point at which the columns are sampled.
     pub point: CirclePoint<SecureField>,
@@ -50,16 +108,49 @@ impl ColumnSampleBatch {
     /// # Arguments
     /// samples: For each column, a vector of samples.
     pub fn new_vec(samples: &[&Vec<PointSample>]) -> Vec<Self> {
+        let ood_data = samples
+            .iter()
+            .enumerate()
+            .flat_map(|(column_index, samples)| {
+                samples
+                    .iter()
+                    .map(move |sample| (sample.point, column_index, sample.value))
+            })
+            .collect_vec();
+        Self::from_ood_data_vec(&ood_data)
+    }
+
+    /// Creates a batch from the values of the sampled columns at a point, as serialized in a
+    /// proof.
+    /// # Arguments
+    /// point: The sampled point.
+    /// column_values: The sampled column indices and their values at the point.
+    pub fn from_ood_data(
+        point: CirclePoint<SecureField>,
+        column_values: &[(usize, SecureField)],
+    ) -> Self {
+        Self {
+            point,
+            columns_and_values: column_values.to_vec(),
+        }
+    }
+
+    /// Groups serialized column samples by sampled point.
+    /// This is the grouping used by [Self::new_vec], so the verifier reconstructs the exact batches
+    /// the prover used.
+    /// # Arguments
+    /// ood_data: `(point, column_index, value)` triplets, ordered by column index.
+    pub fn from_ood_data_vec(
+        ood_data: &[(CirclePoint<SecureField>, usize, SecureField)],
+    ) -> Vec<Self> {
         // Group samples by point, and create a ColumnSampleBatch for each point.
         // This should keep a stable ordering.
         let mut grouped_samples = IndexMap::new();
-        for (column_index, samples) in samples.iter().enumerate() {
-            for sample in samples.iter() {
-                grouped_samples
-                    .entry(sample.point)
-                    .or_insert_with(Vec::new)
-                    .push((column_index, sample.value));
-            }
+        for &(point, column_index, value) in ood_data {
+            grouped_samples
+                .entry(point)
+                .or_insert_with(Vec::new)
+                .push((column_index, value));
         }
         grouped_samples
             .into_iter()
@@ -71,6 +162,33 @@ impl ColumnSampleBatch {
     }
 }
 
+/// A batch of column samplings at several points, i.e. a generalization of [ColumnSampleBatch].
+///
+/// For a column sampled at `k` points, the quotient numerator is `F(x) - I(x.y)`, where `I` is the
+/// polynomial of degree `2k - 1` that interpolates the samples and their complex conjugates.
+/// The denominator vanishes on all the `2k` points.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct MultiPointSampleBatch {
+    /// The points at which the columns are sampled.
+    pub points: Vec<CirclePoint<SecureField>>,
+    /// The sampled column indices and their values at each of the points, in the order of
+    /// `points`.
+    pub columns_and_values: Vec<(usize, Vec<SecureField>)>,
+}
+
+impl From<ColumnSampleBatch> for MultiPointSampleBatch {
+    fn from(sample_batch: ColumnSampleBatch) -> Self {
+        Self {
+            points: vec![sample_batch.point],
+            columns_and_values: sample_batch
+                .columns_and_values
+                .into_iter()
+                .map(|(column_index, value)| (column_index, vec![value]))
+                .collect(),
+        }
+    }
+}
+
 pub struct PointSample {
     pub point: CirclePoint<SecureField>,
     pub value: SecureField,
@@ -193,9 +311,11 @@ pub fn fri_answers_for_log_size(
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
+
     use crate::core::backend::cpu::{CpuCircleEvaluation, CpuCirclePoly};
     use crate::core::circle::SECURE_FIELD_CIRCLE_GEN;
-    use crate::core::pcs::quotients::{compute_fri_quotients, PointSample};
+    use crate::core::pcs::quotients::{compute_fri_quotients, ColumnSampleBatch, PointSample};
     use crate::core::poly::circle::CanonicCoset;
     use crate::{m31, qm31};
 
@@ -222,4 +342,35 @@ mod tests {
                 .interpolate();
         assert!(quot_poly_base_field.is_in_fri_space(LOG_SIZE));
     }
+
+    #[test]
+    fn test_sample_batches_from_ood_data() {
+        let points = [SECURE_FIELD_CIRCLE_GEN, SECURE_FIELD_CIRCLE_GEN.double()];
+        let samples = (0..5)
+            .map(|i| {
+                points[..1 + i % 2]
+                    .iter()
+                    .map(|&point| PointSample {
+                        point,
+                        value: qm31!(i as u32, 1, 2, 3),
+                    })
+                    .collect_vec()
+            })
+            .collect_vec();
+        let batches = ColumnSampleBatch::new_vec(&samples.iter().collect_vec());
+        // Serialize the samples as a proof would: the sampled values of each column, in order.
+        let ood_data = samples
+            .iter()
+            .enumerate()
+            .flat_map(|(i, samples)| samples.iter().map(move |s| (s.point, i, s.value)))
+            .collect_vec();
+
+        let reconstructed = ColumnSampleBatch::from_ood_data_vec(&ood_data);
+
+        assert_eq!(reconstructed, batches);
+        assert_eq!(
+            ColumnSampleBatch::from_ood_data(batches[1].point, &batches[1].columns_and_values),
+            batches[1]
+        );
+    }
 }
@@ -1,6 +1,6 @@
 use std::iter::zip;
 
-use itertools::Itertools;
+use itertools::{zip_eq, Itertools};
 
 use super::super::circle::CirclePoint;
 use super::super::fields::qm31::SecureField;
@@ -43,13 +43,46 @@ impl<MC: MerkleChannel> CommitmentSchemeVerifier<MC> {
         log_sizes: &[u32],
         channel: &mut MC::C,
     ) {
-        MC::mix_root(channel, commitment);
-        let extended_log_sizes = log_sizes
+        let log_blowup_factors = vec![self.config.fri_config.log_blowup_factor; log_sizes.len()];
+        self.commit_with_log_blowup_factors(commitment, log_sizes, &log_blowup_factors, channel)
+            .unwrap();
+    }
+
+    /// Reads a commitment from the prover, where each column is evaluated on a domain blown up by
+    /// its own factor. See [TreeBuilder::extend_polys_with_log_blowup_factor].
+    ///
+    /// FRI only checks the degree bound of the domain, `log_size + log_blowup_factor -
+    /// fri_log_blowup_factor`, so a column with a larger factor than the FRI one could exceed its
+    /// claimed degree bound unnoticed. Such columns are rejected with
+    /// [VerificationError::UnsupportedLogBlowupFactor], before anything is mixed into the channel.
+    ///
+    /// [TreeBuilder::extend_polys_with_log_blowup_factor]: super::TreeBuilder::extend_polys_with_log_blowup_factor
+    pub fn commit_with_log_blowup_factors(
+        &mut self,
+        commitment: <MC::H as MerkleHasher>::Hash,
+        log_sizes: &[u32],
+        log_blowup_factors: &[u32],
+        channel: &mut MC::C,
+    ) -> Result<(), VerificationError> {
+        let fri_log_blowup_factor = self.config.fri_config.log_blowup_factor;
+        if let Some((column_index, &log_blowup_factor)) = log_blowup_factors
             .iter()
-            .map(|&log_size| log_size + self.config.fri_config.log_blowup_factor)
+            .find_position(|&&log_blowup_factor| log_blowup_factor != fri_log_blowup_factor)
+        {
+            return Err(VerificationError::UnsupportedLogBlowupFactor {
+                column_index,
+                log_blowup_factor,
+                fri_log_blowup_factor,
+            });
+        }
+
+        MC::mix_root(channel, commitment);
+        let extended_log_sizes = zip_eq(log_sizes, log_blowup_factors)
+            .map(|(&log_size, &log_blowup_factor)| log_size + log_blowup_factor)
             .collect();
         let verifier = MerkleVerifier::new(commitment, extended_log_sizes);
         self.trees.push(verifier);
+        Ok(())
     }
 
     pub fn verify_values(
@@ -6,6 +6,7 @@ use crate::core::circle::{
     CirclePoint, CirclePointIndex, Coset, CosetIterator, M31_CIRCLE_LOG_ORDER,
 };
 use crate::core::fields::m31::BaseField;
+use crate::core::poly::circle::CanonicCoset;
 
 pub const MAX_CIRCLE_DOMAIN_LOG_SIZE: u32 = M31_CIRCLE_LOG_ORDER - 1;
 
@@ -80,6 +81,16 @@ impl CircleDomain {
         self.half_coset.initial_index * 4 == self.half_coset.step_size
     }
 
+    /// Returns the [CanonicCoset] this domain is the [CanonicCoset::circle_domain] of, or `None` if
+    /// there is none.
+    pub fn try_into_canonic_coset(&self) -> Option<CanonicCoset> {
+        if self.log_size() > MAX_CIRCLE_DOMAIN_LOG_SIZE {
+            return None;
+        }
+        let canonic_coset = CanonicCoset::new(self.log_size());
+        (canonic_coset.circle_domain() == *self).then_some(canonic_coset)
+    }
+
     /// Splits a circle domain into a smaller [CircleDomain]s, shifted by offsets.
     pub fn split(&self, log_parts: u32) -> (CircleDomain, Vec<CirclePointIndex>) {
         assert!(log_parts <= self.half_coset.log_size);
@@ -146,6 +157,27 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_try_into_canonic_coset() {
+        for log_size in 1..=10 {
+            let canonic_coset = CanonicCoset::new(log_size);
+
+            let domain = canonic_coset.circle_domain();
+
+            assert_eq!(domain.try_into_canonic_coset(), Some(canonic_coset));
+        }
+        let domain = CanonicCoset::new(5).circle_domain();
+        assert_eq!(
+            CircleDomain::new(domain.half_coset.conjugate()).try_into_canonic_coset(),
+            None
+        );
+        assert_eq!(
+            CircleDomain::new(Coset::new(CirclePointIndex::generator(), 4))
+                .try_into_canonic_coset(),
+            None
+        );
+    }
+
     #[test]
     fn is_canonic_invalid_domain() {
         let half_coset = Coset::new(CirclePointIndex::generator(), 4);
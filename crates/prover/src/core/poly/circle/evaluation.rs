@@ -1,17 +1,21 @@
 use std::marker::PhantomData;
-use std::ops::{Deref, Index};
+use std::ops::{Deref, Index, Range};
 
 use educe::Educe;
+use thiserror::Error;
 
-use super::{CanonicCoset, CircleDomain, CirclePoly, PolyOps};
+use super::{CanonicCoset, CircleDomain, CirclePoly, PolyOps, MAX_CIRCLE_DOMAIN_LOG_SIZE};
 use crate::core::backend::cpu::CpuCircleEvaluation;
 use crate::core::backend::{Col, Column};
-use crate::core::circle::{CirclePointIndex, Coset};
-use crate::core::fields::m31::BaseField;
+use crate::core::circle::{CirclePointIndex, Coset, M31_CIRCLE_LOG_ORDER};
+use crate::core::fields::m31::{BaseField, P};
 use crate::core::fields::{ExtensionOf, FieldOps};
 use crate::core::poly::twiddles::TwiddleTree;
 use crate::core::poly::{BitReversedOrder, NaturalOrder};
-use crate::core::utils::bit_reverse_index;
+use crate::core::utils::{
+    bit_reverse_index, bws_num_to_bytes, circle_domain_index_to_coset_index,
+    coset_order_to_circle_domain_order_index,
+};
 
 /// An evaluation defined on a [CircleDomain].
 /// The values are ordered according to the [CircleDomain] ordering.
@@ -24,16 +28,40 @@ pub struct CircleEvaluation<B: FieldOps<F>, F: ExtensionOf<BaseField>, EvalOrder
 }
 
 impl<B: FieldOps<F>, F: ExtensionOf<BaseField>, EvalOrder> CircleEvaluation<B, F, EvalOrder> {
+    /// Creates an evaluation on `domain`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the number of values doesn't match the size of the domain. See [Self::try_new].
     pub fn new(domain: CircleDomain, values: Col<B, F>) -> Self {
-        assert_eq!(domain.size(), values.len());
-        Self {
+        Self::try_new(domain, values).unwrap_or_else(|err| panic!("{err}"))
+    }
+
+    /// Creates an evaluation on `domain`, e.g. from untrusted values.
+    ///
+    /// Returns [EvalError::SizeMismatch] if the number of values doesn't match the size of the
+    /// domain.
+    pub fn try_new(domain: CircleDomain, values: Col<B, F>) -> Result<Self, EvalError> {
+        if values.len() != domain.size() {
+            return Err(EvalError::SizeMismatch {
+                expected: domain.size(),
+                got: values.len(),
+            });
+        }
+        Ok(Self {
             domain,
             values,
             _eval_order: PhantomData,
-        }
+        })
     }
 }
 
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum EvalError {
+    #[error("Expected {expected} values for the domain, got {got}.")]
+    SizeMismatch { expected: usize, got: usize },
+}
+
 // Note: The concrete implementation of the poly operations is in the specific backend used.
 // For example, the CPU backend implementation is in `src/core/backend/cpu/poly.rs`.
 impl<F: ExtensionOf<BaseField>, B: FieldOps<F>> CircleEvaluation<B, F, NaturalOrder> {
@@ -47,6 +75,46 @@ impl<F: ExtensionOf<BaseField>, B: FieldOps<F>> CircleEvaluation<B, F, NaturalOr
         B::bit_reverse_column(&mut self.values);
         CircleEvaluation::new(self.domain, self.values)
     }
+
+    /// Returns the evaluation of `f(p + steps * G_n)`, where `f` is this evaluation and `G_n`
+    /// is the step of the domain's [CanonicCoset], e.g. to evaluate a constraint on the next row.
+    ///
+    /// In [CanonicCoset] order, the value at index `i` is the value of this evaluation at index
+    /// `i + steps`, wrapping around.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the domain is not canonic.
+    pub fn rotate(&self, steps: isize) -> Self {
+        assert!(
+            self.domain.is_canonic(),
+            "Only canonic domains can be rotated."
+        );
+        let log_size = self.domain.log_size();
+        let size = self.domain.size();
+        let steps = steps.rem_euclid(size as isize) as usize;
+        let values = (0..size)
+            .map(|i| {
+                let coset_index = circle_domain_index_to_coset_index(i, log_size);
+                let rotated_index = (coset_index + steps) % size;
+                self.values.at(coset_order_to_circle_domain_order_index(
+                    rotated_index,
+                    log_size,
+                ))
+            })
+            .collect();
+        Self::new(self.domain, values)
+    }
+}
+
+impl<B: FieldOps<BaseField>, EvalOrder> CircleEvaluation<B, BaseField, EvalOrder>
+where
+    Col<B, BaseField>: AsRef<[BaseField]>,
+{
+    /// Returns the values at `range`, in stored order, borrowed from the backing column.
+    pub fn sub_column(&self, range: Range<usize>) -> &[BaseField] {
+        &self.values.as_ref()[range]
+    }
 }
 
 impl<F: ExtensionOf<BaseField>> CpuCircleEvaluation<F, NaturalOrder> {
@@ -91,6 +159,15 @@ impl<B: PolyOps> CircleEvaluation<B, BaseField, BitReversedOrder> {
     pub fn interpolate_with_twiddles(self, twiddles: &TwiddleTree<B>) -> CirclePoly<B> {
         B::interpolate(self, twiddles)
     }
+
+    /// Interpolates each of `evals` as [Self::interpolate_with_twiddles] would, sharing the
+    /// precomputed twiddles between them. Returns the polynomials in the order of `evals`.
+    pub fn batch_interpolate_with_twiddles(
+        evals: Vec<Self>,
+        twiddles: &TwiddleTree<B>,
+    ) -> Vec<CirclePoly<B>> {
+        B::batch_interpolate(evals, twiddles)
+    }
 }
 
 impl<B: FieldOps<F>, F: ExtensionOf<BaseField>> CircleEvaluation<B, F, BitReversedOrder> {
@@ -107,6 +184,83 @@ impl<B: FieldOps<F>, F: ExtensionOf<BaseField>> CircleEvaluation<B, F, BitRevers
     }
 }
 
+impl CpuCircleEvaluation<BaseField, BitReversedOrder> {
+    /// Serializes the evaluation, e.g. to cache a committed column between runs. The format is:
+    ///   initial_index (u32) | log_size (u32) | for each value: len (u8) | value bytes
+    /// where `initial_index` and `log_size` describe the half coset of the domain, and each value
+    /// is encoded with [bws_num_to_bytes]. Integers are little-endian and the values are kept in
+    /// bit-reversed order.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let half_coset = self.domain.half_coset;
+        let mut bytes = Vec::with_capacity(8 + 5 * self.values.len());
+        bytes.extend((half_coset.initial_index.0 as u32).to_le_bytes());
+        bytes.extend(half_coset.log_size.to_le_bytes());
+        for value in &self.values {
+            let value_bytes = bws_num_to_bytes(*value);
+            bytes.push(value_bytes.len() as u8);
+            bytes.extend(value_bytes);
+        }
+        bytes
+    }
+
+    /// Deserializes an evaluation serialized with [Self::to_bytes].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, EvaluationDeserializationError> {
+        let mut bytes = bytes;
+        let mut read = |len: usize| {
+            if bytes.len() < len {
+                return Err(EvaluationDeserializationError::UnexpectedEnd);
+            }
+            let (chunk, rest) = bytes.split_at(len);
+            bytes = rest;
+            Ok(chunk)
+        };
+
+        let initial_index = u32::from_le_bytes(read(4)?.try_into().unwrap()) as usize;
+        let log_size = u32::from_le_bytes(read(4)?.try_into().unwrap());
+        if initial_index >= 1 << M31_CIRCLE_LOG_ORDER || log_size >= MAX_CIRCLE_DOMAIN_LOG_SIZE {
+            return Err(EvaluationDeserializationError::InvalidDomain);
+        }
+        let domain = CircleDomain::new(Coset::new(CirclePointIndex(initial_index), log_size));
+
+        let values = (0..domain.size())
+            .map(|_| {
+                let len = read(1)?[0] as usize;
+                let value_bytes = read(len)?;
+                // Values are below P < 2^31, so a canonical encoding takes at most 4 bytes.
+                if len > 4 {
+                    return Err(EvaluationDeserializationError::InvalidFieldElement);
+                }
+                let value = value_bytes
+                    .iter()
+                    .rev()
+                    .fold(0, |acc, byte| (acc << 8) | *byte as u32);
+                let value = BaseField::from_u32_unchecked(value % P);
+                if bws_num_to_bytes(value) != value_bytes {
+                    return Err(EvaluationDeserializationError::InvalidFieldElement);
+                }
+                Ok(value)
+            })
+            .collect::<Result<_, _>>()?;
+
+        match bytes.len() {
+            0 => Ok(Self::new(domain, values)),
+            len => Err(EvaluationDeserializationError::TrailingBytes(len)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum EvaluationDeserializationError {
+    #[error("Unexpected end of input.")]
+    UnexpectedEnd,
+    #[error("{0} unexpected trailing bytes.")]
+    TrailingBytes(usize),
+    #[error("Invalid circle domain.")]
+    InvalidDomain,
+    #[error("Invalid field element encoding.")]
+    InvalidFieldElement,
+}
+
 impl<B: FieldOps<F>, F: ExtensionOf<BaseField>, EvalOrder> Deref
     for CircleEvaluation<B, F, EvalOrder>
 {
@@ -155,13 +309,41 @@ impl<'a, F: ExtensionOf<BaseField>> Index<usize> for CosetSubEvaluation<'a, F> {
 
 #[cfg(test)]
 mod tests {
+    use itertools::Itertools;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::{EvalError, EvaluationDeserializationError};
     use crate::core::backend::cpu::CpuCircleEvaluation;
+    use crate::core::backend::simd::SimdBackend;
+    use crate::core::backend::Column;
     use crate::core::circle::Coset;
-    use crate::core::fields::m31::BaseField;
-    use crate::core::poly::circle::CanonicCoset;
-    use crate::core::poly::NaturalOrder;
+    use crate::core::fields::m31::{BaseField, P};
+    use crate::core::poly::circle::{CanonicCoset, CircleEvaluation};
+    use crate::core::poly::{BitReversedOrder, NaturalOrder};
+    use crate::core::utils::{
+        circle_domain_order_to_coset_order, coset_order_to_circle_domain_order,
+    };
     use crate::m31;
 
+    #[test]
+    fn test_sub_column() {
+        let domain = CanonicCoset::new(6).circle_domain();
+        let values = (0..1 << 6).map(BaseField::from).collect_vec();
+        let cpu_evaluation =
+            CpuCircleEvaluation::<_, BitReversedOrder>::new(domain, values.clone());
+        let simd_evaluation = CircleEvaluation::<SimdBackend, BaseField, BitReversedOrder>::new(
+            domain,
+            values.into_iter().collect(),
+        );
+
+        for range in [0..0, 3..29, 16..64] {
+            let expected = range.clone().map(|i| cpu_evaluation.at(i)).collect_vec();
+            assert_eq!(cpu_evaluation.sub_column(range.clone()), expected);
+            assert_eq!(simd_evaluation.sub_column(range), expected);
+        }
+    }
+
     #[test]
     fn test_interpolate_non_canonic() {
         let domain = CanonicCoset::new(3).circle_domain();
@@ -177,6 +359,65 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_try_new() {
+        let domain = CanonicCoset::new(3).circle_domain();
+        let values = (0..8).map(BaseField::from).collect_vec();
+
+        let evaluation =
+            CpuCircleEvaluation::<_, NaturalOrder>::try_new(domain, values.clone()).unwrap();
+        assert_eq!(evaluation.values, values);
+        assert_eq!(
+            CpuCircleEvaluation::<_, NaturalOrder>::try_new(domain, values[..7].to_vec())
+                .unwrap_err(),
+            EvalError::SizeMismatch {
+                expected: 8,
+                got: 7
+            }
+        );
+    }
+
+    #[test]
+    #[should_panic(expected = "Expected 8 values for the domain, got 9.")]
+    fn test_new_size_mismatch_panics() {
+        let domain = CanonicCoset::new(3).circle_domain();
+        CpuCircleEvaluation::<_, NaturalOrder>::new(domain, (0..9).map(BaseField::from).collect());
+    }
+
+    #[test]
+    fn test_bytes_round_trip() {
+        let domain = CanonicCoset::new(6).circle_domain();
+        let mut rng = SmallRng::seed_from_u64(0);
+        let mut values = (0..domain.size()).map(|_| rng.gen()).collect_vec();
+        // Edge cases of the variable-length value encoding.
+        values[..4].copy_from_slice(&[m31!(0), m31!(0x80), m31!(0xff), m31!(P - 1)]);
+        let evaluation = CpuCircleEvaluation::<_, BitReversedOrder>::new(domain, values);
+
+        let bytes = evaluation.to_bytes();
+        let deserialized = CpuCircleEvaluation::<_, BitReversedOrder>::from_bytes(&bytes).unwrap();
+
+        assert_eq!(deserialized.domain, evaluation.domain);
+        assert_eq!(deserialized.values, evaluation.values);
+        assert_eq!(
+            deserialized.interpolate().coeffs,
+            evaluation.interpolate().coeffs
+        );
+        assert_eq!(
+            CpuCircleEvaluation::<BaseField, BitReversedOrder>::from_bytes(
+                &bytes[..bytes.len() - 1]
+            )
+            .unwrap_err(),
+            EvaluationDeserializationError::UnexpectedEnd
+        );
+        assert_eq!(
+            CpuCircleEvaluation::<BaseField, BitReversedOrder>::from_bytes(
+                &[bytes, vec![0]].concat()
+            )
+            .unwrap_err(),
+            EvaluationDeserializationError::TrailingBytes(1)
+        );
+    }
+
     #[test]
     fn test_interpolate_canonic() {
         let coset = CanonicCoset::new(3);
@@ -204,6 +445,33 @@ mod tests {
         }
     }
 
+    #[test]
+    fn test_rotate() {
+        let coset = CanonicCoset::new(5);
+        let values = (0..coset.size()).map(|i| m31!(i as u32)).collect_vec();
+        let coset_order_values = circle_domain_order_to_coset_order(&values);
+        let circle_evaluation = CpuCircleEvaluation::<_, NaturalOrder>::new(
+            coset.circle_domain(),
+            coset_order_to_circle_domain_order(&coset_order_values),
+        );
+
+        for steps in [0, 1, 3, -1, -7, 32, 45] {
+            let rotated = circle_evaluation.rotate(steps);
+
+            let shift = steps.rem_euclid(coset.size() as isize) as usize;
+            let rotated_coset_order_values = circle_domain_order_to_coset_order(&rotated.values);
+            for (i, value) in rotated_coset_order_values.into_iter().enumerate() {
+                assert_eq!(value, coset_order_values[(i + shift) % coset.size()]);
+            }
+            for index in coset.circle_domain().iter_indices() {
+                assert_eq!(
+                    rotated.get_at(index),
+                    circle_evaluation.get_at(index + coset.step_size() * shift)
+                );
+            }
+        }
+    }
+
     #[test]
     fn test_sub_evaluation() {
         let domain = CanonicCoset::new(7).circle_domain();
@@ -7,10 +7,12 @@ mod secure_poly;
 
 pub use canonic::CanonicCoset;
 pub use domain::{CircleDomain, MAX_CIRCLE_DOMAIN_LOG_SIZE};
-pub use evaluation::{CircleEvaluation, CosetSubEvaluation};
+pub use evaluation::{
+    CircleEvaluation, CosetSubEvaluation, EvalError, EvaluationDeserializationError,
+};
 pub use ops::PolyOps;
 pub use poly::CirclePoly;
-pub use secure_poly::{SecureCirclePoly, SecureEvaluation};
+pub use secure_poly::{CoordinateDomainMismatch, SecureCirclePoly, SecureEvaluation};
 
 #[cfg(test)]
 mod tests {
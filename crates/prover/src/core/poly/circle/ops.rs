@@ -27,6 +27,20 @@ pub trait PolyOps: FieldOps<BaseField> + Sized {
         itwiddles: &TwiddleTree<Self>,
     ) -> CirclePoly<Self>;
 
+    /// Interpolates several evaluations with the same twiddles, see [PolyOps::interpolate].
+    /// Used by the [`CircleEvaluation::batch_interpolate_with_twiddles()`] function.
+    ///
+    /// Defaults to interpolating each evaluation in turn.
+    fn batch_interpolate(
+        evals: Vec<CircleEvaluation<Self, BaseField, BitReversedOrder>>,
+        itwiddles: &TwiddleTree<Self>,
+    ) -> Vec<CirclePoly<Self>> {
+        evals
+            .into_iter()
+            .map(|eval| Self::interpolate(eval, itwiddles))
+            .collect()
+    }
+
     /// Evaluates the polynomial at a single point.
     /// Used by the [`CirclePoly::eval_at_point()`] function.
     fn eval_at_point(poly: &CirclePoly<Self>, point: CirclePoint<SecureField>) -> SecureField;
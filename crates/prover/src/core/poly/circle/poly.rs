@@ -66,8 +66,10 @@ impl<B: PolyOps> CirclePoly<B> {
     }
 }
 
-#[cfg(test)]
+/// Degree checks, e.g. for validating quotients and FRI layers.
 impl crate::core::backend::cpu::CpuCirclePoly {
+    /// Fft space is the space of polynomials spanned by the first n elements of the circle FFT
+    /// basis, where n = 2^`log_fft_size`. The highest degree monomial is x^{(n/2)-1}y.
     pub fn is_in_fft_space(&self, log_fft_size: u32) -> bool {
         use num_traits::Zero;
 
@@ -1,5 +1,7 @@
 use std::ops::{Deref, DerefMut};
 
+use thiserror::Error;
+
 use super::{CircleDomain, CircleEvaluation, CirclePoly, PolyOps};
 use crate::core::backend::cpu::CpuCircleEvaluation;
 use crate::core::backend::CpuBackend;
@@ -61,7 +63,52 @@ impl<B: FieldOps<BaseField>> DerefMut for SecureEvaluation<B> {
     }
 }
 
+impl<B: FieldOps<BaseField>> SecureEvaluation<B> {
+    /// Splits the evaluation into the evaluations of its [SECURE_EXTENSION_DEGREE] coordinates.
+    /// The values keep their order.
+    pub fn into_coordinate_evals<EvalOrder>(
+        self,
+    ) -> [CircleEvaluation<B, BaseField, EvalOrder>; SECURE_EXTENSION_DEGREE] {
+        let Self { domain, values } = self;
+        values.columns.map(|c| CircleEvaluation::new(domain, c))
+    }
+
+    /// Assembles an evaluation from the evaluations of its [SECURE_EXTENSION_DEGREE] coordinates.
+    /// This is the inverse of [Self::into_coordinate_evals].
+    ///
+    /// Returns an error if the coordinate evaluations are not on the same domain.
+    pub fn from_coordinate_evals<EvalOrder>(
+        coordinate_evals: [CircleEvaluation<B, BaseField, EvalOrder>; SECURE_EXTENSION_DEGREE],
+    ) -> Result<Self, CoordinateDomainMismatch> {
+        let domain = coordinate_evals[0].domain;
+        if coordinate_evals.iter().any(|eval| eval.domain != domain) {
+            return Err(CoordinateDomainMismatch);
+        }
+        Ok(Self {
+            domain,
+            values: SecureColumnByCoords {
+                columns: coordinate_evals.map(|eval| eval.values),
+            },
+        })
+    }
+}
+
+/// Error when the coordinate evaluations of a [SecureEvaluation] are on different domains.
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+#[error("Coordinate evaluations are on different domains.")]
+pub struct CoordinateDomainMismatch;
+
 impl SecureEvaluation<CpuBackend> {
+    /// Returns whether the polynomials of all [SECURE_EXTENSION_DEGREE] coordinates are in the fri
+    /// space of size 2^`log_fft_size`. See [CirclePoly::is_in_fri_space].
+    /// The values are assumed to be in bit-reversed order.
+    pub fn is_in_fri_space(&self, log_fft_size: u32) -> bool {
+        self.clone()
+            .into_coordinate_evals::<BitReversedOrder>()
+            .into_iter()
+            .all(|eval| eval.interpolate().is_in_fri_space(log_fft_size))
+    }
+
     // TODO(spapini): Remove when we no longer use CircleEvaluation<SecureField>.
     pub fn to_cpu(self) -> CpuCircleEvaluation<SecureField, BitReversedOrder> {
         CpuCircleEvaluation::new(self.domain, self.values.to_vec())
@@ -78,3 +125,76 @@ impl From<CircleEvaluation<CpuBackend, SecureField, BitReversedOrder>>
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::{CoordinateDomainMismatch, SecureEvaluation};
+    use crate::core::backend::cpu::{CpuCircleEvaluation, CpuCirclePoly};
+    use crate::core::backend::CpuBackend;
+    use crate::core::circle::CirclePointIndex;
+    use crate::core::fields::qm31::SecureField;
+    use crate::core::poly::circle::CanonicCoset;
+    use crate::core::poly::BitReversedOrder;
+
+    #[test]
+    fn test_coordinate_evals_round_trip() {
+        const LOG_SIZE: u32 = 5;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let domain = CanonicCoset::new(LOG_SIZE).circle_domain();
+        let values = (0..1 << LOG_SIZE).map(|_| rng.gen()).collect_vec();
+        let eval = SecureEvaluation::<CpuBackend> {
+            domain,
+            values: values.iter().copied().collect(),
+        };
+
+        let coordinate_evals = eval.into_coordinate_evals::<BitReversedOrder>();
+        let eval = SecureEvaluation::from_coordinate_evals(coordinate_evals.clone()).unwrap();
+
+        let expected = (0..1 << LOG_SIZE)
+            .map(|i| SecureField::from_m31_array(coordinate_evals.each_ref().map(|e| e[i])))
+            .collect_vec();
+        assert_eq!(eval.domain, domain);
+        assert_eq!(eval.values.to_vec(), expected);
+        assert_eq!(expected, values);
+    }
+
+    #[test]
+    fn test_from_coordinate_evals_domain_mismatch() {
+        let domain = CanonicCoset::new(3).circle_domain();
+        let other_domain = domain.shift(CirclePointIndex(1));
+        let mut coordinate_evals = std::array::from_fn(|_| {
+            CpuCircleEvaluation::<_, BitReversedOrder>::new(domain, vec![0.into(); 8])
+        });
+        coordinate_evals[2] = CpuCircleEvaluation::new(other_domain, vec![0.into(); 8]);
+
+        assert_eq!(
+            SecureEvaluation::from_coordinate_evals(coordinate_evals).err(),
+            Some(CoordinateDomainMismatch)
+        );
+    }
+
+    #[test]
+    fn test_is_in_fri_space() {
+        const LOG_SIZE: u32 = 4;
+        let mut rng = SmallRng::seed_from_u64(0);
+        let domain = CanonicCoset::new(LOG_SIZE + 1).circle_domain();
+        let low_degree_evals = std::array::from_fn(|_| {
+            CpuCirclePoly::new((0..1 << LOG_SIZE).map(|_| rng.gen()).collect()).evaluate(domain)
+        });
+
+        let low_degree_eval = SecureEvaluation::from_coordinate_evals(low_degree_evals).unwrap();
+        let high_degree_eval = SecureEvaluation::<CpuBackend> {
+            domain,
+            values: (0..1 << (LOG_SIZE + 1))
+                .map(|_| rng.gen::<SecureField>())
+                .collect(),
+        };
+
+        assert!(low_degree_eval.is_in_fri_space(LOG_SIZE));
+        assert!(!high_degree_eval.is_in_fri_space(LOG_SIZE));
+    }
+}
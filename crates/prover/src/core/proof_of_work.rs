@@ -1,7 +1,253 @@
+#[cfg(feature = "std-time")]
+use std::time::{Duration, Instant};
+
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
+
 use crate::core::channel::Channel;
 
 pub trait GrindOps<C: Channel> {
-    /// Searches for a nonce s.t. mixing it to the channel makes the digest have `pow_bits` leading
+    /// Searches for a nonce s.t. mixing it to the channel makes the digest have `pow_bits` trailing
     /// zero bits.
-    fn grind(channel: &C, pow_bits: u32) -> u64;
+    ///
+    /// Tries the nonces `0..u64::MAX` with [GrindOps::grind_from], so `u64::MAX` itself is never
+    /// tried. Panics if none of them is valid, which is negligibly likely for any `pow_bits` a
+    /// prover can afford.
+    fn grind(channel: &C, pow_bits: u32) -> u64 {
+        Self::grind_from(channel, pow_bits, 0, u64::MAX).expect("No valid nonce found.")
+    }
+
+    /// Like [GrindOps::grind], but only tries the `max_iters` nonces starting at `start_nonce`.
+    /// Returns the smallest valid nonce in that range, or `None` if there is none.
+    ///
+    /// The range ends at `u64::MAX` at the latest, exclusive, so it never contains `u64::MAX`.
+    fn grind_from(channel: &C, pow_bits: u32, start_nonce: u64, max_iters: u64) -> Option<u64>;
+
+    /// Searches for the smallest nonce s.t. `predicate` holds for the channel after mixing the
+    /// nonce to it, e.g. a digest below a target for a finer difficulty than `pow_bits`.
+    /// [GrindOps::grind] is the special case of a `trailing_zeros() >= pow_bits` predicate.
+    ///
+    /// The predicate must hold for some nonce, otherwise this never returns.
+    fn grind_with_predicate(channel: &C, predicate: impl Fn(&C) -> bool + Sync) -> u64;
+
+    /// Like [GrindOps::grind], but counts the zero bits from the end of the digest given by
+    /// `direction`.
+    fn grind_in_direction(channel: &C, pow_bits: u32, direction: PowDirection) -> u64 {
+        match direction {
+            PowDirection::Trailing => Self::grind(channel, pow_bits),
+            PowDirection::Leading => {
+                Self::grind_with_predicate(channel, |channel| channel.leading_zeros() >= pow_bits)
+            }
+        }
+    }
+
+    /// Returns the expected number of nonces [GrindOps::grind] tries for `pow_bits`, i.e.
+    /// 2^`pow_bits`, saturating at `u64::MAX`. Together with a measured grind time (see
+    /// `grind_timed` under the `std-time` feature), this helps picking `pow_bits` for a target
+    /// latency.
+    fn estimate_iterations(pow_bits: u32) -> u64 {
+        1u64.checked_shl(pow_bits).unwrap_or(u64::MAX)
+    }
+
+    /// Like [GrindOps::grind], also returning the wall-clock time the grind took.
+    #[cfg(feature = "std-time")]
+    fn grind_timed(channel: &C, pow_bits: u32) -> (u64, Duration) {
+        let start = Instant::now();
+        let nonce = Self::grind(channel, pow_bits);
+        (nonce, start.elapsed())
+    }
+}
+
+/// The end of the digest from which proof of work zero bits are counted.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum PowDirection {
+    /// See [Channel::leading_zeros].
+    Leading,
+    /// See [Channel::trailing_zeros].
+    #[default]
+    Trailing,
+}
+
+impl PowDirection {
+    /// Counts the zero bits of the channel's digest in this direction.
+    pub fn zeros(self, channel: &impl Channel) -> u32 {
+        match self {
+            Self::Leading => channel.leading_zeros(),
+            Self::Trailing => channel.trailing_zeros(),
+        }
+    }
+}
+
+/// Checks that mixing `nonce` to the channel makes the digest have `pow_bits` trailing zero bits
+/// (see [Channel::trailing_zeros]). The channel itself is left untouched.
+pub fn verify_work<C: Channel>(channel: &C, nonce: u64, pow_bits: u32) -> bool {
+    verify_work_with_predicate(channel, nonce, |channel| {
+        channel.trailing_zeros() >= pow_bits
+    })
+}
+
+/// Like [verify_work], but counts the zero bits from the end of the digest given by `direction`.
+/// This is the verifier of [GrindOps::grind_in_direction].
+pub fn verify_work_in_direction<C: Channel>(
+    channel: &C,
+    nonce: u64,
+    pow_bits: u32,
+    direction: PowDirection,
+) -> bool {
+    verify_work_with_predicate(channel, nonce, |channel| {
+        direction.zeros(channel) >= pow_bits
+    })
+}
+
+/// Checks that `predicate` holds for the channel after mixing `nonce` to it.
+/// This is the verifier of [GrindOps::grind_with_predicate]. The channel itself is left untouched.
+pub fn verify_work_with_predicate<C: Channel>(
+    channel: &C,
+    nonce: u64,
+    predicate: impl Fn(&C) -> bool,
+) -> bool {
+    let mut channel = channel.clone();
+    channel.mix_nonce(nonce);
+    predicate(&channel)
+}
+
+/// Checks the proof of work of each `(channel, nonce, pow_bits)` item, e.g. of many independent
+/// proofs. Returns the result of [verify_work] for each item, in order.
+pub fn verify_work_batch<C: Channel + Sync>(items: &[(C, u64, u32)]) -> Vec<bool> {
+    #[cfg(not(feature = "parallel"))]
+    let iter = items.iter();
+
+    #[cfg(feature = "parallel")]
+    let iter = items.par_iter();
+
+    iter.map(|(channel, nonce, pow_bits)| verify_work(channel, *nonce, *pow_bits))
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+
+    use super::{
+        verify_work, verify_work_batch, verify_work_in_direction, verify_work_with_predicate,
+        GrindOps, PowDirection,
+    };
+    use crate::core::backend::CpuBackend;
+    use crate::core::channel::{Channel, Sha256Channel};
+    use crate::core::fields::qm31::SecureField;
+    use crate::m31;
+
+    #[test]
+    fn test_verify_work_batch() {
+        let items = (0..16)
+            .map(|i| {
+                let mut channel = Sha256Channel::default();
+                channel.mix_felts(&[SecureField::from(m31!(i))]);
+                let pow_bits = i % 8;
+                let nonce = CpuBackend::grind(&channel, pow_bits);
+                // Corrupt every third nonce.
+                let nonce = if i % 3 == 0 { nonce + 1 } else { nonce };
+                (channel, nonce, pow_bits)
+            })
+            .collect_vec();
+
+        let results = verify_work_batch(&items);
+
+        let expected = items
+            .iter()
+            .map(|(channel, nonce, pow_bits)| verify_work(channel, *nonce, *pow_bits))
+            .collect_vec();
+        assert_eq!(results, expected);
+        assert!(results.iter().any(|&valid| valid));
+        assert!(results.iter().any(|&valid| !valid));
+    }
+
+    #[test]
+    fn test_estimate_iterations() {
+        for pow_bits in 0..64 {
+            assert_eq!(
+                <CpuBackend as GrindOps<Sha256Channel>>::estimate_iterations(pow_bits),
+                1 << pow_bits
+            );
+        }
+        assert_eq!(
+            <CpuBackend as GrindOps<Sha256Channel>>::estimate_iterations(64),
+            u64::MAX
+        );
+    }
+
+    #[cfg(feature = "std-time")]
+    #[test]
+    fn test_grind_timed() {
+        let mut channel = Sha256Channel::default();
+        channel.mix_felts(&[SecureField::from(m31!(1))]);
+        let pow_bits = 6;
+
+        let (nonce, _) = CpuBackend::grind_timed(&channel, pow_bits);
+
+        assert_eq!(nonce, CpuBackend::grind(&channel, pow_bits));
+    }
+
+    #[test]
+    fn test_grind_from() {
+        let mut channel = Sha256Channel::default();
+        channel.mix_felts(&[SecureField::from(m31!(1))]);
+        let pow_bits = 6;
+        let nonce = CpuBackend::grind(&channel, pow_bits);
+
+        assert_eq!(
+            CpuBackend::grind_from(&channel, pow_bits, 0, nonce + 1),
+            Some(nonce)
+        );
+        assert_eq!(CpuBackend::grind_from(&channel, pow_bits, 0, nonce), None);
+        let next_nonce = CpuBackend::grind_from(&channel, pow_bits, nonce + 1, u64::MAX).unwrap();
+        assert!(next_nonce > nonce);
+        assert!(verify_work(&channel, next_nonce, pow_bits));
+    }
+
+    #[test]
+    fn test_grind_from_gives_up() {
+        let channel = Sha256Channel::default();
+
+        assert_eq!(CpuBackend::grind_from(&channel, 40, 0, 16), None);
+        assert_eq!(CpuBackend::grind_from(&channel, 0, u64::MAX, 16), None);
+    }
+
+    #[test]
+    fn test_grind_with_threshold_predicate() {
+        let mut channel = Sha256Channel::default();
+        channel.mix_felts(&[SecureField::from(m31!(1))]);
+        // The last 4 digest bytes, read as a little-endian integer, are below a target.
+        let below_target = |channel: &Sha256Channel| {
+            let digest: [u8; 32] = channel.digest().into();
+            u32::from_le_bytes(digest[28..].try_into().unwrap()) < 3 << 22
+        };
+
+        let nonce = CpuBackend::grind_with_predicate(&channel, below_target);
+
+        assert!(verify_work_with_predicate(&channel, nonce, below_target));
+        assert!((0..nonce).all(|n| !verify_work_with_predicate(&channel, n, below_target)));
+    }
+
+    #[test]
+    fn test_grind_in_direction() {
+        let mut channel = Sha256Channel::default();
+        channel.mix_felts(&[SecureField::from(m31!(1))]);
+        let pow_bits = 8;
+
+        let leading_nonce =
+            CpuBackend::grind_in_direction(&channel, pow_bits, PowDirection::Leading);
+        let trailing_nonce =
+            CpuBackend::grind_in_direction(&channel, pow_bits, PowDirection::Trailing);
+
+        assert_eq!(trailing_nonce, CpuBackend::grind(&channel, pow_bits));
+        assert_ne!(leading_nonce, trailing_nonce);
+        for direction in [PowDirection::Leading, PowDirection::Trailing] {
+            let nonce = CpuBackend::grind_in_direction(&channel, pow_bits, direction);
+            assert!(verify_work_in_direction(
+                &channel, nonce, pow_bits, direction
+            ));
+            assert!((0..nonce).all(|n| !verify_work_in_direction(&channel, n, pow_bits, direction)));
+        }
+    }
 }
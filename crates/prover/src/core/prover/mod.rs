@@ -42,6 +42,10 @@ pub const N_QUERIES: usize = 16;
 pub const N_QUERIES: usize = 40;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
+#[serde(bound(
+    serialize = "H::Hash: Serialize",
+    deserialize = "H::Hash: Deserialize<'de>"
+))]
 pub struct StarkProof<H: MerkleHasher> {
     pub commitments: TreeVec<H::Hash>,
     pub lookup_values: LookupValues,
@@ -56,6 +60,24 @@ pub struct AdditionalProofData {
     pub oods_quotients: Vec<CircleEvaluation<CpuBackend, SecureField, BitReversedOrder>>,
 }
 
+/// Costs of generating a proof, to compare parameter choices. See
+/// [commit_and_prove_with_stats].
+///
+/// [commit_and_prove_with_stats]: crate::trace_generation::commit_and_prove_with_stats
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ProofStats {
+    /// Number of hashes computed while proving, on any thread, as counted by
+    /// [HashCounter](crate::core::channel::HashCounter): every Merkle node hash, including the FRI
+    /// layer trees, and every hash of a channel operation. Proof of work attempts are not counted.
+    pub n_hashes: usize,
+    /// Estimated number of butterflies in the FFTs that evaluate the committed polynomials on
+    /// their evaluation domains, each costing one field multiplication.
+    ///
+    /// This is not measured: it is computed from the committed domain sizes, assuming `n` layers
+    /// of `2^(n-1)` butterflies per domain of size `2^n`. Other field operations are not included.
+    pub n_fft_butterflies: usize,
+}
+
 pub fn prove<B: BackendForChannel<MC>, MC: MerkleChannel>(
     components: &[&dyn ComponentProver<B>],
     channel: &mut MC::C,
@@ -238,4 +260,13 @@ pub enum VerificationError {
     Fri(#[from] FriVerificationError),
     #[error("Proof of work verification failed.")]
     ProofOfWork,
+    #[error(
+        "Column {column_index} is committed with log blowup factor {log_blowup_factor}, but only \
+        the FRI log blowup factor {fri_log_blowup_factor} is supported."
+    )]
+    UnsupportedLogBlowupFactor {
+        column_index: usize,
+        log_blowup_factor: u32,
+        fri_log_blowup_factor: u32,
+    },
 }
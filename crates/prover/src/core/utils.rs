@@ -100,7 +100,7 @@ pub fn offset_bit_reversed_circle_domain_index(
 
 // TODO(AlonH): Pair both functions below with bit reverse. Consider removing both and calculating
 // the indices instead.
-pub(crate) fn circle_domain_order_to_coset_order(values: &[BaseField]) -> Vec<BaseField> {
+pub(crate) fn circle_domain_order_to_coset_order<F: Field>(values: &[F]) -> Vec<F> {
     let n = values.len();
     let mut coset_order = vec![];
     for i in 0..(n / 2) {
@@ -131,6 +131,16 @@ pub fn coset_order_to_circle_domain_order_index(index: usize, log_size: u32) ->
     }
 }
 
+/// The inverse of [coset_order_to_circle_domain_order_index].
+pub fn circle_domain_index_to_coset_index(index: usize, log_size: u32) -> usize {
+    let half_size = 1 << (log_size - 1);
+    if index < half_size {
+        index * 2
+    } else {
+        ((1 << log_size) - 1 - index) * 2 + 1
+    }
+}
+
 /// Performs a naive bit-reversal permutation inplace.
 ///
 /// # Panics
@@ -229,13 +239,27 @@ pub fn bws_num_to_bytes(v: M31) -> Vec<u8> {
     bytes
 }
 
+/// Generates `n_cols` columns of size 2^`log_size`, where `col[j][i] = (i + j + 1)^3`.
+/// Unlike random columns, the values are reproducible across runs and machines, which makes
+/// benchmark results comparable.
+pub fn deterministic_trace(log_size: u32, n_cols: usize) -> Vec<Vec<BaseField>> {
+    (0..n_cols)
+        .map(|j| {
+            (0..1 << log_size)
+                .map(|i| BaseField::from(i + j + 1).pow(3))
+                .collect()
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use itertools::Itertools;
     use num_traits::One;
 
     use super::{
-        offset_bit_reversed_circle_domain_index, previous_bit_reversed_circle_domain_index,
+        deterministic_trace, offset_bit_reversed_circle_domain_index,
+        previous_bit_reversed_circle_domain_index,
     };
     use crate::core::backend::cpu::CpuCircleEvaluation;
     use crate::core::fields::qm31::SecureField;
@@ -364,4 +388,19 @@ mod tests {
 
         assert_eq!(neighbor_pairs, expected_neighbor_pairs);
     }
+
+    #[test]
+    fn test_deterministic_trace() {
+        const LOG_SIZE: u32 = 20;
+
+        let trace = deterministic_trace(LOG_SIZE, 2);
+
+        assert_eq!(trace.len(), 2);
+        assert!(trace.iter().all(|col| col.len() == 1 << LOG_SIZE));
+        assert_eq!(trace[0][0], m31!(1));
+        assert_eq!(trace[0][1], m31!(8));
+        assert_eq!(trace[1][3], m31!(125));
+        assert_eq!(trace[1][(1 << LOG_SIZE) - 1], m31!(540018177));
+        assert_eq!(trace, deterministic_trace(LOG_SIZE, 2));
+    }
 }
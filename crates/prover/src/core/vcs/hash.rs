@@ -1,3 +1,4 @@
+use std::borrow::Cow;
 use std::fmt::{Debug, Display};
 
 use serde::{Deserialize, Serialize};
@@ -14,4 +15,49 @@ pub trait Hash:
     + Serialize
     + for<'de> Deserialize<'de>
 {
+    /// The length of [Hash::as_bytes].
+    const BYTE_LEN: usize;
+
+    /// Returns the bytes of the hash, e.g. to serialize it without knowing its concrete type.
+    ///
+    /// Borrowed for hashes stored as bytes, owned for hashes that need to be encoded.
+    fn as_bytes(&self) -> Cow<'_, [u8]>;
+
+    /// Compares two hashes, e.g. a computed Merkle root against a claimed one.
+    ///
+    /// Defaults to `==`. Hashes compared against values controlled by a malicious party should
+    /// override this with a comparison whose running time doesn't depend on the hashes.
+    fn ct_eq(&self, other: &Self) -> bool {
+        self == other
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::Hash;
+    use crate::core::fields::m31::BaseField;
+    use crate::core::vcs::poseidon31_merkle::Poseidon31Hash;
+    use crate::core::vcs::sha256_hash::Sha256Hasher;
+
+    fn serialize_hashes<H: Hash>(hashes: &[H]) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity(hashes.len() * H::BYTE_LEN);
+        for hash in hashes {
+            assert_eq!(hash.as_bytes().len(), H::BYTE_LEN);
+            bytes.extend_from_slice(&hash.as_bytes());
+        }
+        bytes
+    }
+
+    #[test]
+    fn test_as_bytes() {
+        let sha256_hashes = [Sha256Hasher::hash(b"a"), Sha256Hasher::hash(b"b")];
+        let poseidon31_hash = Poseidon31Hash(std::array::from_fn(|i| BaseField::from(i + 1)));
+
+        assert_eq!(
+            serialize_hashes(&sha256_hashes),
+            [sha256_hashes[0].as_ref(), sha256_hashes[1].as_ref()].concat()
+        );
+        let poseidon31_bytes = serialize_hashes(&[poseidon31_hash]);
+        assert_eq!(poseidon31_bytes[..8], [1, 0, 0, 0, 2, 0, 0, 0]);
+    }
 }
@@ -0,0 +1,233 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use serde::{Deserialize, Serialize};
+
+use crate::core::channel::{add_hashes, MerkleChannel, Sha256Channel};
+use crate::core::fields::m31::BaseField;
+use crate::core::vcs::hash::Hash;
+use crate::core::vcs::ops::MerkleHasher;
+use crate::core::vcs::sha256_hash::Sha256Hasher;
+use crate::core::vcs::utils::hash_node_bytes;
+
+// Wrapper for the keccak256 hash type.
+#[derive(Clone, Copy, PartialEq, Default, Eq, Deserialize, Serialize)]
+pub struct Keccak256Hash(pub [u8; 32]);
+
+impl AsRef<[u8]> for Keccak256Hash {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl fmt::Display for Keccak256Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&hex::encode(self.0))
+    }
+}
+
+impl fmt::Debug for Keccak256Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        <Keccak256Hash as fmt::Display>::fmt(self, f)
+    }
+}
+
+impl Hash for Keccak256Hash {
+    const BYTE_LEN: usize = 32;
+
+    fn as_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self.0)
+    }
+}
+
+/// A Merkle hasher using Keccak-256 (as in the EVM's `keccak256`, not the standardized SHA3-256).
+///
+/// Nodes have the same byte layout as [super::sha256_merkle::Sha256MerkleHasher], with
+/// `keccak256` in place of `sha256`, so a Solidity verifier can recompute them with
+/// `keccak256(abi.encodePacked(...))`.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub struct Keccak256MerkleHasher;
+impl MerkleHasher for Keccak256MerkleHasher {
+    type Hash = Keccak256Hash;
+    type Tag = ();
+
+    fn hash_node(
+        children_hashes: Option<(Self::Hash, Self::Hash)>,
+        column_values: &[BaseField],
+    ) -> Self::Hash {
+        Keccak256Hash(hash_node_bytes::<sha3::Keccak256>(
+            children_hashes.map(|(left, right)| (left.0, right.0)),
+            column_values,
+        ))
+    }
+}
+
+/// A [MerkleChannel] committing with [Keccak256MerkleHasher].
+///
+/// The Fiat-Shamir transcript is a [Sha256Channel], which the EVM supports through the SHA-256
+/// precompile. Roots are mixed as in [super::sha256_merkle::Sha256MerkleChannel].
+#[derive(Default)]
+pub struct Keccak256MerkleChannel;
+
+impl MerkleChannel for Keccak256MerkleChannel {
+    type C = Sha256Channel;
+    type H = Keccak256MerkleHasher;
+
+    fn mix_root(channel: &mut Self::C, root: <Self::H as MerkleHasher>::Hash) {
+        add_hashes(1);
+        let mut hasher = Sha256Hasher::new();
+        hasher.update(root.as_ref());
+        hasher.update(channel.digest().as_ref());
+        channel.update_digest(hasher.finalize());
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use itertools::Itertools;
+    use num_traits::Zero;
+    use sha3::{Digest, Keccak256};
+
+    use crate::core::backend::CpuBackend;
+    use crate::core::fields::m31::BaseField;
+    use crate::core::vcs::keccak256_merkle::{Keccak256Hash, Keccak256MerkleHasher};
+    use crate::core::vcs::ops::MerkleHasher;
+    use crate::core::vcs::prover::MerkleProver;
+    use crate::core::vcs::test_utils::prepare_merkle;
+    use crate::core::vcs::verifier::{MerkleVerificationError, MerkleVerifier};
+    use crate::m31;
+
+    #[test]
+    fn test_keccak256_is_not_sha3() {
+        assert_eq!(
+            hex::encode(Keccak256::digest([])),
+            "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+        );
+    }
+
+    #[test]
+    fn test_hash_node_known_answers() {
+        let leaf = Keccak256MerkleHasher::hash_node(None, &[m31!(0), m31!(1)]);
+        let node = Keccak256MerkleHasher::hash_node(Some((leaf, leaf)), &[]);
+        let node_with_columns = Keccak256MerkleHasher::hash_node(Some((leaf, node)), &[m31!(2)]);
+
+        assert_eq!(
+            leaf.to_string(),
+            "534afc8420bab6c013a3e42dc77ef141e8f16ee0206928dd007663e9c85068dd"
+        );
+        assert_eq!(
+            node.to_string(),
+            "10d0f8adce04c0249e204e7f1e2b1db121b39570a834c3df9157fb51c83ba1a4"
+        );
+        assert_eq!(
+            node_with_columns.to_string(),
+            "3b42da7661717f1c95581df88455915200ea2c91736d98a7c530fe54efba3782"
+        );
+        // Children only: keccak256(abi.encodePacked(left, right)).
+        assert_eq!(
+            node.0,
+            <[u8; 32]>::from(Keccak256::digest([leaf.0, leaf.0].concat()))
+        );
+    }
+
+    #[test]
+    fn test_merkle_root_known_answer() {
+        let cols = (2..5)
+            .map(|log_size| (0..1 << log_size).map(BaseField::from).collect_vec())
+            .collect_vec();
+        let log_sizes = cols.iter().map(|c| c.len().ilog2()).collect_vec();
+        let queries: BTreeMap<u32, Vec<usize>> = [(4, vec![0, 7]), (2, vec![3])].into();
+
+        let merkle =
+            MerkleProver::<CpuBackend, Keccak256MerkleHasher>::commit(cols.iter().collect_vec());
+        let (values, decommitment) = merkle.decommit(queries.clone(), cols.iter().collect_vec());
+
+        assert_eq!(
+            merkle.root().to_string(),
+            "92b55bc4da0917fd099fc90f6025a750778a09b794cbc41eb4c85f0a958eb489"
+        );
+        MerkleVerifier::<Keccak256MerkleHasher>::new(merkle.root(), log_sizes)
+            .verify(queries, values, decommitment)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_merkle_success() {
+        let (queries, decommitment, values, verifier) = prepare_merkle::<Keccak256MerkleHasher>();
+        verifier.verify(queries, values, decommitment).unwrap();
+    }
+
+    #[test]
+    fn test_merkle_invalid_witness() {
+        let (queries, mut decommitment, values, verifier) =
+            prepare_merkle::<Keccak256MerkleHasher>();
+        decommitment.hash_witness[4] = Keccak256Hash::default();
+
+        assert_eq!(
+            verifier.verify(queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::RootMismatch
+        );
+    }
+
+    #[test]
+    fn test_merkle_invalid_value() {
+        let (queries, decommitment, mut values, verifier) =
+            prepare_merkle::<Keccak256MerkleHasher>();
+        values[3][2] = BaseField::zero();
+
+        assert_eq!(
+            verifier.verify(queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::RootMismatch
+        );
+    }
+
+    #[test]
+    fn test_merkle_witness_too_short() {
+        let (queries, mut decommitment, values, verifier) =
+            prepare_merkle::<Keccak256MerkleHasher>();
+        decommitment.hash_witness.pop();
+
+        assert_eq!(
+            verifier.verify(queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::WitnessTooShort
+        );
+    }
+
+    #[test]
+    fn test_merkle_witness_too_long() {
+        let (queries, mut decommitment, values, verifier) =
+            prepare_merkle::<Keccak256MerkleHasher>();
+        decommitment.hash_witness.push(Keccak256Hash::default());
+
+        assert_eq!(
+            verifier.verify(queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::WitnessTooLong
+        );
+    }
+
+    #[test]
+    fn test_merkle_column_values_too_long() {
+        let (queries, decommitment, mut values, verifier) =
+            prepare_merkle::<Keccak256MerkleHasher>();
+        values[3].push(BaseField::zero());
+
+        assert_eq!(
+            verifier.verify(queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::ColumnValuesTooLong
+        );
+    }
+
+    #[test]
+    fn test_merkle_column_values_too_short() {
+        let (queries, decommitment, mut values, verifier) =
+            prepare_merkle::<Keccak256MerkleHasher>();
+        values[3].pop();
+
+        assert_eq!(
+            verifier.verify(queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::ColumnValuesTooShort
+        );
+    }
+}
@@ -1,14 +1,120 @@
 //! Vector commitment scheme (VCS) module.
 
 pub mod hash;
+#[cfg(feature = "keccak")]
+pub mod keccak256_merkle;
 pub mod ops;
 #[cfg(not(target_arch = "wasm32"))]
 pub mod poseidon252_merkle;
+pub mod poseidon31_merkle;
 pub mod prover;
 pub mod sha256_hash;
 pub mod sha256_merkle;
 mod utils;
 pub mod verifier;
 
+pub use utils::{deserialize_columns, serialize_columns};
+
 #[cfg(test)]
 mod test_utils;
+
+/// The production [MerkleChannel](crate::core::channel::MerkleChannel)s of this crate.
+///
+/// Not all of them are compiled into every build: see [available_merkle_channels].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MerkleChannelKind {
+    /// [sha256_merkle::Sha256MerkleChannel].
+    Sha256,
+    /// [sha256_merkle::Sha256StreamingMerkleChannel].
+    Sha256Streaming,
+    /// [sha256_merkle::Sha256BatchedMerkleChannel].
+    Sha256Batched,
+    /// `keccak256_merkle::Keccak256MerkleChannel`, behind the `keccak` feature.
+    Keccak256,
+    /// `poseidon252_merkle::Poseidon252MerkleChannel`, unavailable on `wasm32`, where
+    /// `starknet-crypto` is not supported. Only [CpuBackend](crate::core::backend::CpuBackend)
+    /// commits with it.
+    Poseidon252,
+}
+
+/// Returns the [MerkleChannelKind]s compiled into this build, so that code iterating over all
+/// Merkle channels can tell which ones it may use.
+pub const fn available_merkle_channels() -> &'static [MerkleChannelKind] {
+    use MerkleChannelKind::*;
+    match (cfg!(feature = "keccak"), cfg!(target_arch = "wasm32")) {
+        (true, false) => &[
+            Sha256,
+            Sha256Streaming,
+            Sha256Batched,
+            Keccak256,
+            Poseidon252,
+        ],
+        (false, false) => &[Sha256, Sha256Streaming, Sha256Batched, Poseidon252],
+        (true, true) => &[Sha256, Sha256Streaming, Sha256Batched, Keccak256],
+        (false, true) => &[Sha256, Sha256Streaming, Sha256Batched],
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{available_merkle_channels, MerkleChannelKind};
+    use crate::core::backend::simd::SimdBackend;
+    use crate::core::backend::{BackendForChannel, CpuBackend};
+    use crate::core::channel::MerkleChannel;
+    use crate::core::vcs::sha256_merkle::{
+        Sha256BatchedMerkleChannel, Sha256MerkleChannel, Sha256StreamingMerkleChannel,
+    };
+
+    fn assert_backend_for_channel<B: BackendForChannel<MC>, MC: MerkleChannel>() {}
+
+    /// Only references channels available on every target, so it also builds on `wasm32`.
+    #[test]
+    fn test_sha256_merkle_channels_are_always_available() {
+        assert_backend_for_channel::<CpuBackend, Sha256MerkleChannel>();
+        assert_backend_for_channel::<SimdBackend, Sha256MerkleChannel>();
+        assert_backend_for_channel::<CpuBackend, Sha256StreamingMerkleChannel>();
+        assert_backend_for_channel::<SimdBackend, Sha256StreamingMerkleChannel>();
+        assert_backend_for_channel::<CpuBackend, Sha256BatchedMerkleChannel>();
+        assert_backend_for_channel::<SimdBackend, Sha256BatchedMerkleChannel>();
+
+        assert_eq!(
+            available_merkle_channels()[..3],
+            [
+                MerkleChannelKind::Sha256,
+                MerkleChannelKind::Sha256Streaming,
+                MerkleChannelKind::Sha256Batched
+            ]
+        );
+    }
+
+    #[test]
+    fn test_available_merkle_channels_match_cfg() {
+        let available = available_merkle_channels();
+
+        assert_eq!(
+            available.contains(&MerkleChannelKind::Keccak256),
+            cfg!(feature = "keccak")
+        );
+        assert_eq!(
+            available.contains(&MerkleChannelKind::Poseidon252),
+            cfg!(not(target_arch = "wasm32"))
+        );
+    }
+
+    #[cfg(feature = "keccak")]
+    #[test]
+    fn test_keccak256_merkle_channel_backends() {
+        use crate::core::vcs::keccak256_merkle::Keccak256MerkleChannel;
+
+        assert_backend_for_channel::<CpuBackend, Keccak256MerkleChannel>();
+        assert_backend_for_channel::<SimdBackend, Keccak256MerkleChannel>();
+    }
+
+    #[cfg(not(target_arch = "wasm32"))]
+    #[test]
+    fn test_poseidon252_merkle_channel_backends() {
+        use crate::core::vcs::poseidon252_merkle::Poseidon252MerkleChannel;
+
+        assert_backend_for_channel::<CpuBackend, Poseidon252MerkleChannel>();
+    }
+}
@@ -13,13 +13,48 @@ use crate::core::vcs::hash::Hash;
 /// children hashes.
 /// At each layer, the tree may have multiple columns of the same length as the layer.
 /// Each node in that layer contains one value from each column.
+///
+/// Hashers may separate the domains of different kinds of nodes (e.g. leaves and internal nodes)
+/// with a tag, see [MerkleHasher::domain_tag].
 pub trait MerkleHasher: Debug + Default + Clone {
     type Hash: Hash;
+    /// Domain-separation tag, e.g. the initial capacity of a sponge.
+    type Tag: Copy + Debug + Default;
+
     /// Hashes a single Merkle node. See [MerkleHasher] for more details.
     fn hash_node(
         children_hashes: Option<(Self::Hash, Self::Hash)>,
         column_values: &[BaseField],
     ) -> Self::Hash;
+
+    /// Returns the tag of a node. `is_leaf` is true for nodes of the largest layer, which have no
+    /// children hashes. `n_columns` is the number of column values hashed in the node.
+    ///
+    /// Defaults to [Default::default], for hashers without domain separation.
+    fn domain_tag(_is_leaf: bool, _n_columns: usize) -> Self::Tag {
+        Self::Tag::default()
+    }
+
+    /// Hashes a single Merkle node in the domain of `tag`.
+    ///
+    /// Defaults to [MerkleHasher::hash_node], ignoring the tag.
+    fn hash_node_with_tag(
+        _tag: Self::Tag,
+        children_hashes: Option<(Self::Hash, Self::Hash)>,
+        column_values: &[BaseField],
+    ) -> Self::Hash {
+        Self::hash_node(children_hashes, column_values)
+    }
+
+    /// Hashes a single Merkle node, tagged with [MerkleHasher::domain_tag].
+    /// This is the hash used by [MerkleOps::commit_on_layer] implementations and the verifier.
+    fn hash_tagged_node(
+        children_hashes: Option<(Self::Hash, Self::Hash)>,
+        column_values: &[BaseField],
+    ) -> Self::Hash {
+        let tag = Self::domain_tag(children_hashes.is_none(), column_values.len());
+        Self::hash_node_with_tag(tag, children_hashes, column_values)
+    }
 }
 
 /// Trait for performing Merkle operations on a commitment scheme.
@@ -45,3 +80,136 @@ pub trait MerkleOps<H: MerkleHasher>:
         columns: &[&Col<Self, BaseField>],
     ) -> Col<Self, H::Hash>;
 }
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+
+    use itertools::Itertools;
+    use sha2::{Digest, Sha256};
+
+    use super::{MerkleHasher, MerkleOps};
+    use crate::core::backend::CpuBackend;
+    use crate::core::fields::m31::BaseField;
+    use crate::core::vcs::prover::MerkleProver;
+    use crate::core::vcs::sha256_hash::Sha256Hash;
+    use crate::core::vcs::sha256_merkle::Sha256MerkleHasher;
+    use crate::core::vcs::verifier::MerkleVerifier;
+    use crate::m31;
+
+    /// A [Sha256MerkleHasher] that separates leaves from internal nodes.
+    #[derive(Clone, Debug, Default)]
+    struct TaggedSha256MerkleHasher;
+    impl MerkleHasher for TaggedSha256MerkleHasher {
+        type Hash = Sha256Hash;
+        type Tag = u8;
+
+        fn hash_node(
+            children_hashes: Option<(Self::Hash, Self::Hash)>,
+            column_values: &[BaseField],
+        ) -> Self::Hash {
+            Sha256MerkleHasher::hash_node(children_hashes, column_values)
+        }
+
+        fn domain_tag(is_leaf: bool, _n_columns: usize) -> u8 {
+            if is_leaf {
+                0
+            } else {
+                1
+            }
+        }
+
+        fn hash_node_with_tag(
+            tag: u8,
+            children_hashes: Option<(Self::Hash, Self::Hash)>,
+            column_values: &[BaseField],
+        ) -> Self::Hash {
+            let node_hash = Self::hash_node(children_hashes, column_values);
+            Sha256::new()
+                .chain_update([tag])
+                .chain_update(node_hash)
+                .finalize()
+                .as_slice()
+                .into()
+        }
+    }
+
+    impl MerkleOps<TaggedSha256MerkleHasher> for CpuBackend {
+        fn commit_on_layer(
+            log_size: u32,
+            prev_layer: Option<&Vec<Sha256Hash>>,
+            columns: &[&Vec<BaseField>],
+        ) -> Vec<Sha256Hash> {
+            (0..(1 << log_size))
+                .map(|i| {
+                    TaggedSha256MerkleHasher::hash_tagged_node(
+                        prev_layer.map(|prev_layer| (prev_layer[2 * i], prev_layer[2 * i + 1])),
+                        &columns.iter().map(|column| column[i]).collect_vec(),
+                    )
+                })
+                .collect()
+        }
+    }
+
+    #[test]
+    fn test_default_tag_preserves_hash() {
+        let values = [m31!(1), m31!(2), m31!(3)];
+        let children = (
+            Sha256Hash::default(),
+            Sha256MerkleHasher::hash_node(None, &values),
+        );
+
+        for children_hashes in [None, Some(children)] {
+            assert_eq!(
+                Sha256MerkleHasher::hash_tagged_node(children_hashes, &values),
+                Sha256MerkleHasher::hash_node(children_hashes, &values)
+            );
+        }
+    }
+
+    #[test]
+    fn test_tagged_hasher_separates_leaves_from_internal_nodes() {
+        let values = [m31!(1), m31!(2), m31!(3)];
+
+        let leaf_hash = TaggedSha256MerkleHasher::hash_node_with_tag(
+            TaggedSha256MerkleHasher::domain_tag(true, values.len()),
+            None,
+            &values,
+        );
+        let internal_hash = TaggedSha256MerkleHasher::hash_node_with_tag(
+            TaggedSha256MerkleHasher::domain_tag(false, values.len()),
+            None,
+            &values,
+        );
+
+        assert_ne!(leaf_hash, internal_hash);
+    }
+
+    #[test]
+    fn test_tagged_hasher_commit_and_verify() {
+        let cols = (2..5)
+            .map(|log_size| (0..1 << log_size).map(BaseField::from).collect_vec())
+            .collect_vec();
+        let queries: BTreeMap<u32, Vec<usize>> = [(4, vec![3, 9]), (2, vec![1])].into();
+        let merkle =
+            MerkleProver::<CpuBackend, TaggedSha256MerkleHasher>::commit(cols.iter().collect_vec());
+        let untagged_root =
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(cols.iter().collect_vec())
+                .root();
+
+        let (values, decommitment) = merkle.decommit(queries.clone(), cols.iter().collect_vec());
+        let verifier = MerkleVerifier::<TaggedSha256MerkleHasher>::new(
+            merkle.root(),
+            cols.iter().map(|c| c.len().ilog2()).collect(),
+        );
+
+        assert_ne!(merkle.root(), untagged_root);
+        verifier
+            .verify(
+                [(4, vec![3, 9]), (2, vec![1])].into_iter().collect(),
+                values,
+                decommitment,
+            )
+            .unwrap();
+    }
+}
@@ -1,3 +1,5 @@
+use std::borrow::Cow;
+
 use itertools::Itertools;
 use num_traits::Zero;
 use serde::{Deserialize, Serialize};
@@ -6,7 +8,7 @@ use starknet_ff::FieldElement as FieldElement252;
 
 use super::ops::{MerkleHasher, MerkleOps};
 use crate::core::backend::CpuBackend;
-use crate::core::channel::{MerkleChannel, Poseidon252Channel};
+use crate::core::channel::{add_hashes, MerkleChannel, Poseidon252Channel};
 use crate::core::fields::m31::BaseField;
 use crate::core::vcs::hash::Hash;
 
@@ -16,6 +18,7 @@ const ELEMENTS_IN_BLOCK: usize = 8;
 pub struct Poseidon252MerkleHasher;
 impl MerkleHasher for Poseidon252MerkleHasher {
     type Hash = FieldElement252;
+    type Tag = ();
 
     fn hash_node(
         children_hashes: Option<(Self::Hash, Self::Hash)>,
@@ -54,7 +57,7 @@ impl MerkleOps<Poseidon252MerkleHasher> for CpuBackend {
     ) -> Vec<FieldElement252> {
         (0..(1 << log_size))
             .map(|i| {
-                Poseidon252MerkleHasher::hash_node(
+                Poseidon252MerkleHasher::hash_tagged_node(
                     prev_layer.map(|prev_layer| (prev_layer[2 * i], prev_layer[2 * i + 1])),
                     &columns.iter().map(|column| column[i]).collect_vec(),
                 )
@@ -63,7 +66,14 @@ impl MerkleOps<Poseidon252MerkleHasher> for CpuBackend {
     }
 }
 
-impl Hash for FieldElement252 {}
+impl Hash for FieldElement252 {
+    const BYTE_LEN: usize = 32;
+
+    /// The big-endian bytes of the field element.
+    fn as_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(self.to_bytes_be().to_vec())
+    }
+}
 
 #[derive(Default)]
 pub struct Poseidon252MerkleChannel;
@@ -73,6 +83,7 @@ impl MerkleChannel for Poseidon252MerkleChannel {
     type H = Poseidon252MerkleHasher;
 
     fn mix_root(channel: &mut Self::C, root: <Self::H as MerkleHasher>::Hash) {
+        add_hashes(1);
         channel.update_digest(poseidon_hash(channel.digest(), root));
     }
 }
@@ -83,6 +94,7 @@ mod tests {
     use starknet_ff::FieldElement as FieldElement252;
 
     use crate::core::fields::m31::BaseField;
+    use crate::core::vcs::hash::Hash;
     use crate::core::vcs::ops::MerkleHasher;
     use crate::core::vcs::poseidon252_merkle::Poseidon252MerkleHasher;
     use crate::core::vcs::test_utils::prepare_merkle;
@@ -111,6 +123,17 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_as_bytes() {
+        let hash = FieldElement252::from(0x1234u32);
+
+        let bytes = hash.as_bytes();
+
+        assert_eq!(bytes.len(), FieldElement252::BYTE_LEN);
+        assert_eq!(bytes[30..], [0x12, 0x34]);
+        assert!(bytes[..30].iter().all(|&b| b == 0));
+    }
+
     #[test]
     fn test_merkle_success() {
         let (queries, decommitment, values, verifier) = prepare_merkle::<Poseidon252MerkleHasher>();
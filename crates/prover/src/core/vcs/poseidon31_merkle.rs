@@ -0,0 +1,403 @@
+use std::borrow::Cow;
+use std::fmt;
+
+use itertools::Itertools;
+use num_traits::Zero;
+use serde::{Deserialize, Serialize};
+
+use super::ops::{MerkleHasher, MerkleOps};
+use crate::core::backend::CpuBackend;
+use crate::core::fields::m31::{BaseField, P};
+use crate::core::fields::FieldExpOps;
+use crate::core::vcs::hash::Hash;
+
+/// The width of the Poseidon2 state, in [BaseField] elements.
+pub const STATE_WIDTH: usize = 16;
+/// The number of elements absorbed per permutation. The rest of the state is the capacity.
+const RATE: usize = 8;
+const N_HALF_FULL_ROUNDS: usize = 4;
+const N_PARTIAL_ROUNDS: usize = 14;
+
+/// The diagonal of the internal linear layer, minus the all-ones matrix: small powers of two (and
+/// -2), so that the multiplications are cheap.
+const INTERNAL_DIAGONAL: [BaseField; STATE_WIDTH] = [
+    BaseField::from_u32_unchecked(P - 2),
+    BaseField::from_u32_unchecked(1),
+    BaseField::from_u32_unchecked(1 << 1),
+    BaseField::from_u32_unchecked(1 << 2),
+    BaseField::from_u32_unchecked(1 << 3),
+    BaseField::from_u32_unchecked(1 << 4),
+    BaseField::from_u32_unchecked(1 << 5),
+    BaseField::from_u32_unchecked(1 << 6),
+    BaseField::from_u32_unchecked(1 << 7),
+    BaseField::from_u32_unchecked(1 << 8),
+    BaseField::from_u32_unchecked(1 << 10),
+    BaseField::from_u32_unchecked(1 << 12),
+    BaseField::from_u32_unchecked(1 << 13),
+    BaseField::from_u32_unchecked(1 << 14),
+    BaseField::from_u32_unchecked(1 << 15),
+    BaseField::from_u32_unchecked(1 << 16),
+];
+
+/// Round constants of the first half of the full rounds.
+const EXTERNAL_INITIAL_ROUND_CONSTANTS: [[u32; STATE_WIDTH]; N_HALF_FULL_ROUNDS] = [
+    [
+        0x768bab52, 0x70e0ab7d, 0x3d266c8a, 0x6da42045, 0x600fef22, 0x41dace6b, 0x64f9bdd4,
+        0x5d42d4fe, 0x76b1516d, 0x6fc9a717, 0x70ac4fb6, 0x00194ef6, 0x22b644e2, 0x1f7916d5,
+        0x47581be2, 0x2710a123,
+    ],
+    [
+        0x6284e867, 0x018d3afe, 0x5df99ef3, 0x4c1e467b, 0x566f6abc, 0x2994e427, 0x538a6d42,
+        0x5d7bf2cf, 0x7fda2dab, 0x0fd854c4, 0x46922fca, 0x3d7763a1, 0x19fd05ca, 0x0a4bbb43,
+        0x15075851, 0x3d903d76,
+    ],
+    [
+        0x2d290ff7, 0x40809fa0, 0x59dac6ec, 0x127927a2, 0x6bbf0ea0, 0x0294140f, 0x24742976,
+        0x6e84c081, 0x22484f4a, 0x354cae59, 0x0453ffe1, 0x3f47a3cc, 0x0088204e, 0x6066e109,
+        0x3b7c4b80, 0x6b55665d,
+    ],
+    [
+        0x3bc4b897, 0x735bf378, 0x508daf42, 0x1884fc2b, 0x7214f24c, 0x7498be0a, 0x1a60e640,
+        0x3303f928, 0x29b46376, 0x5c96bb68, 0x65d097a5, 0x1d358e9f, 0x4a9a9017, 0x4724cf76,
+        0x347af70f, 0x1e77e59a,
+    ],
+];
+/// Round constants of the second half of the full rounds.
+const EXTERNAL_FINAL_ROUND_CONSTANTS: [[u32; STATE_WIDTH]; N_HALF_FULL_ROUNDS] = [
+    [
+        0x57090613, 0x1fa42108, 0x17bbef50, 0x1ff7e11c, 0x047b24ca, 0x4e140275, 0x4fa086f5,
+        0x079b309c, 0x1159bd47, 0x6d37e4e5, 0x075d8dce, 0x12121ca0, 0x7f6a7c40, 0x68e182ba,
+        0x5493201b, 0x0444a80e,
+    ],
+    [
+        0x0064f4c6, 0x6467abe6, 0x66975762, 0x2af68f9b, 0x345b33be, 0x1b70d47f, 0x053db717,
+        0x381189cb, 0x43b915f8, 0x20df3694, 0x0f459d26, 0x77a0e97b, 0x2f73e739, 0x1876c2f9,
+        0x65a0e29a, 0x4cabefbe,
+    ],
+    [
+        0x5abd1268, 0x4d34a760, 0x12771799, 0x69a0c9ac, 0x39091e55, 0x7f611cd0, 0x3af055da,
+        0x7ac0bbdf, 0x6e0f3a24, 0x41e3b6f7, 0x49b3756d, 0x568bc538, 0x20c079d8, 0x1701c72c,
+        0x7670dc6c, 0x5a439035,
+    ],
+    [
+        0x7c93e00e, 0x561fbb4d, 0x1178907b, 0x02737406, 0x32fb24f1, 0x6323b60a, 0x6ab12418,
+        0x42c99cea, 0x155a0b97, 0x53d1c6aa, 0x2bd20347, 0x279b3d73, 0x4f5f3c70, 0x0245af6c,
+        0x238359d3, 0x49966a59,
+    ],
+];
+/// Round constants of the partial rounds, one per round.
+const INTERNAL_ROUND_CONSTANTS: [u32; N_PARTIAL_ROUNDS] = [
+    0x7f7ec4bf, 0x0421926f, 0x5198e669, 0x34db3148, 0x4368bafd, 0x66685c7f, 0x78d3249a, 0x60187881,
+    0x76dad67a, 0x0690b437, 0x1ea95311, 0x40e5369a, 0x38f103fc, 0x1d226a21,
+];
+
+/// Applies the Poseidon2 permutation over M31 to `state`, with the S-box x^5, 8 full rounds and
+/// 14 partial rounds.
+///
+/// This is the width-16 instantiation of Plonky3 (`default_mersenne31_poseidon2_16`), whose round
+/// constants were generated with the Grain LFSR of the Poseidon paper.
+pub fn poseidon2_m31_permutation(state: &mut [BaseField; STATE_WIDTH]) {
+    apply_external_linear_layer(state);
+
+    for round_constants in &EXTERNAL_INITIAL_ROUND_CONSTANTS {
+        apply_full_round(state, round_constants);
+    }
+    for round_constant in INTERNAL_ROUND_CONSTANTS {
+        state[0] = sbox(state[0] + BaseField::from_u32_unchecked(round_constant));
+        apply_internal_linear_layer(state);
+    }
+    for round_constants in &EXTERNAL_FINAL_ROUND_CONSTANTS {
+        apply_full_round(state, round_constants);
+    }
+}
+
+fn sbox(x: BaseField) -> BaseField {
+    x.pow(5)
+}
+
+fn apply_full_round(state: &mut [BaseField; STATE_WIDTH], round_constants: &[u32; STATE_WIDTH]) {
+    for (x, &round_constant) in state.iter_mut().zip(round_constants) {
+        *x = sbox(*x + BaseField::from_u32_unchecked(round_constant));
+    }
+    apply_external_linear_layer(state);
+}
+
+/// Multiplies the state by circ(2 * M4, M4, M4, M4), where M4 is the circulant matrix
+/// circ(2, 3, 1, 1), as in Plonky3.
+fn apply_external_linear_layer(state: &mut [BaseField; STATE_WIDTH]) {
+    for chunk in state.chunks_exact_mut(4) {
+        let [a, b, c, d] = [chunk[0], chunk[1], chunk[2], chunk[3]];
+        let small = |v: u32| BaseField::from_u32_unchecked(v);
+        chunk[0] = small(2) * a + small(3) * b + c + d;
+        chunk[1] = a + small(2) * b + small(3) * c + d;
+        chunk[2] = a + b + small(2) * c + small(3) * d;
+        chunk[3] = small(3) * a + b + c + small(2) * d;
+    }
+
+    let sums: [BaseField; 4] =
+        std::array::from_fn(|i| state.iter().skip(i).step_by(4).copied().sum());
+    for (i, x) in state.iter_mut().enumerate() {
+        *x += sums[i % 4];
+    }
+}
+
+/// Multiplies the state by the all-ones matrix plus [INTERNAL_DIAGONAL].
+fn apply_internal_linear_layer(state: &mut [BaseField; STATE_WIDTH]) {
+    let sum: BaseField = state.iter().copied().sum();
+    for (x, &diagonal) in state.iter_mut().zip(&INTERNAL_DIAGONAL) {
+        *x = *x * diagonal + sum;
+    }
+}
+
+/// A hash of 8 [BaseField] elements, the rate of the sponge.
+#[derive(Clone, Copy, PartialEq, Default, Eq, Deserialize, Serialize)]
+pub struct Poseidon31Hash(pub [BaseField; RATE]);
+
+impl fmt::Display for Poseidon31Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for value in self.0 {
+            write!(f, "{:08x}", value.0)?;
+        }
+        Ok(())
+    }
+}
+
+impl fmt::Debug for Poseidon31Hash {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        <Poseidon31Hash as fmt::Display>::fmt(self, f)
+    }
+}
+
+impl Hash for Poseidon31Hash {
+    const BYTE_LEN: usize = 4 * RATE;
+
+    /// The little-endian bytes of each element, in order.
+    fn as_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Owned(
+            self.0
+                .iter()
+                .flat_map(|value| value.0.to_le_bytes())
+                .collect(),
+        )
+    }
+}
+
+const LEAF_TAG: BaseField = BaseField::from_u32_unchecked(1);
+const INTERNAL_NODE_TAG: BaseField = BaseField::from_u32_unchecked(2);
+
+/// A Merkle hasher over M31, for Merkle trees verified inside M31 STARKs.
+///
+/// A node is hashed with a sponge over [poseidon2_m31_permutation] with a rate of 8 elements.
+/// The inputs are the 8 elements of each child hash, if any, followed by the column values, padded
+/// with zeros to a multiple of the rate. Their number is put in the capacity before absorbing, so
+/// that the padding is unambiguous, followed by a tag that separates leaves from internal nodes.
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Default, Deserialize, Serialize)]
+pub struct Poseidon31MerkleHasher;
+impl MerkleHasher for Poseidon31MerkleHasher {
+    type Hash = Poseidon31Hash;
+    type Tag = BaseField;
+
+    fn hash_node(
+        children_hashes: Option<(Self::Hash, Self::Hash)>,
+        column_values: &[BaseField],
+    ) -> Self::Hash {
+        Self::hash_tagged_node(children_hashes, column_values)
+    }
+
+    fn domain_tag(is_leaf: bool, _n_columns: usize) -> BaseField {
+        if is_leaf {
+            LEAF_TAG
+        } else {
+            INTERNAL_NODE_TAG
+        }
+    }
+
+    fn hash_node_with_tag(
+        tag: BaseField,
+        children_hashes: Option<(Self::Hash, Self::Hash)>,
+        column_values: &[BaseField],
+    ) -> Self::Hash {
+        let children_values = children_hashes
+            .into_iter()
+            .flat_map(|(left, right)| left.0.into_iter().chain(right.0));
+        let n_inputs = 2 * RATE * children_hashes.iter().len() + column_values.len();
+
+        let mut state = [BaseField::zero(); STATE_WIDTH];
+        state[RATE] = BaseField::from(n_inputs);
+        state[RATE + 1] = tag;
+        let chunks = children_values
+            .chain(column_values.iter().copied())
+            .chunks(RATE);
+        let mut chunks = chunks.into_iter().peekable();
+        if chunks.peek().is_none() {
+            poseidon2_m31_permutation(&mut state);
+        }
+        for chunk in chunks {
+            for (x, value) in state.iter_mut().zip(chunk) {
+                *x += value;
+            }
+            poseidon2_m31_permutation(&mut state);
+        }
+        Poseidon31Hash(state[..RATE].try_into().unwrap())
+    }
+}
+
+impl MerkleOps<Poseidon31MerkleHasher> for CpuBackend {
+    fn commit_on_layer(
+        log_size: u32,
+        prev_layer: Option<&Vec<Poseidon31Hash>>,
+        columns: &[&Vec<BaseField>],
+    ) -> Vec<Poseidon31Hash> {
+        (0..(1 << log_size))
+            .map(|i| {
+                Poseidon31MerkleHasher::hash_tagged_node(
+                    prev_layer.map(|prev_layer| (prev_layer[2 * i], prev_layer[2 * i + 1])),
+                    &columns.iter().map(|column| column[i]).collect_vec(),
+                )
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::array;
+
+    use itertools::Itertools;
+    use num_traits::Zero;
+
+    use super::{poseidon2_m31_permutation, Poseidon31Hash, Poseidon31MerkleHasher};
+    use crate::core::backend::CpuBackend;
+    use crate::core::fields::m31::BaseField;
+    use crate::core::vcs::ops::MerkleHasher;
+    use crate::core::vcs::prover::MerkleProver;
+    use crate::core::vcs::test_utils::prepare_merkle;
+    use crate::core::vcs::verifier::MerkleVerificationError;
+    use crate::m31;
+
+    fn hash(values: [u32; 8]) -> Poseidon31Hash {
+        Poseidon31Hash(values.map(BaseField::from_u32_unchecked))
+    }
+
+    /// The test vector of `default_mersenne31_poseidon2_16` in Plonky3.
+    #[test]
+    fn test_permutation_vector() {
+        let mut state = array::from_fn(|i| m31!(i as u32));
+
+        poseidon2_m31_permutation(&mut state);
+
+        assert_eq!(
+            state,
+            [
+                0x0b2c803a, 0x5b1ee4d1, 0x49c6b1e3, 0x2cdc280c, 0x310a60c8, 0x530a729e, 0x4e61bcb4,
+                0x2e84d3c3, 0x58709c08, 0x7e82ac42, 0x2162bcef, 0x6d153ab6, 0x742cf0e3, 0x2f21632d,
+                0x61adce1e, 0x1973d6f1,
+            ]
+            .map(BaseField::from_u32_unchecked)
+        );
+    }
+
+    #[test]
+    fn test_vector() {
+        assert_eq!(
+            Poseidon31MerkleHasher::hash_node(None, &[m31!(0), m31!(1)]),
+            hash([
+                749555, 1357168713, 1606614197, 158694189, 294800696, 1138725501, 297315124,
+                1692330571,
+            ])
+        );
+
+        assert_eq!(
+            Poseidon31MerkleHasher::hash_node(Some((hash([1; 8]), hash([2; 8]))), &[m31!(3)]),
+            hash([
+                1881880145, 882247678, 1797859850, 485935945, 64127064, 864189028, 262661988,
+                910560014,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_leaf_and_internal_node_hashes_differ() {
+        let children_values = [m31!(1); 8].into_iter().chain([m31!(2); 8]);
+        let leaf_values = children_values.chain([m31!(3)]).collect_vec();
+
+        assert_ne!(
+            Poseidon31MerkleHasher::hash_node(None, &leaf_values),
+            Poseidon31MerkleHasher::hash_node(Some((hash([1; 8]), hash([2; 8]))), &[m31!(3)])
+        );
+    }
+
+    #[test]
+    fn test_tree_root_vector() {
+        let cols = [vec![0, 1, 2, 3], vec![4, 5, 6, 7], vec![8, 9]].map(|col| {
+            col.into_iter()
+                .map(BaseField::from_u32_unchecked)
+                .collect_vec()
+        });
+
+        let merkle =
+            MerkleProver::<CpuBackend, Poseidon31MerkleHasher>::commit(cols.iter().collect_vec());
+
+        assert_eq!(
+            merkle.root(),
+            hash([
+                1713850316, 359768733, 342147056, 537717298, 709058323, 1723838837, 1154085199,
+                174820431,
+            ])
+        );
+    }
+
+    #[test]
+    fn test_merkle_success() {
+        let (queries, decommitment, values, verifier) = prepare_merkle::<Poseidon31MerkleHasher>();
+        verifier.verify(queries, values, decommitment).unwrap();
+    }
+
+    #[test]
+    fn test_merkle_invalid_witness() {
+        let (queries, mut decommitment, values, verifier) =
+            prepare_merkle::<Poseidon31MerkleHasher>();
+        decommitment.hash_witness[4] = Poseidon31Hash::default();
+
+        assert_eq!(
+            verifier.verify(queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::RootMismatch
+        );
+    }
+
+    #[test]
+    fn test_merkle_invalid_value() {
+        let (queries, decommitment, mut values, verifier) =
+            prepare_merkle::<Poseidon31MerkleHasher>();
+        values[3][2] = BaseField::zero();
+
+        assert_eq!(
+            verifier.verify(queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::RootMismatch
+        );
+    }
+
+    #[test]
+    fn test_merkle_witness_too_short() {
+        let (queries, mut decommitment, values, verifier) =
+            prepare_merkle::<Poseidon31MerkleHasher>();
+        decommitment.hash_witness.pop();
+
+        assert_eq!(
+            verifier.verify(queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::WitnessTooShort
+        );
+    }
+
+    #[test]
+    fn test_merkle_witness_too_long() {
+        let (queries, mut decommitment, values, verifier) =
+            prepare_merkle::<Poseidon31MerkleHasher>();
+        decommitment.hash_witness.push(Poseidon31Hash::default());
+
+        assert_eq!(
+            verifier.verify(queries, values, decommitment).unwrap_err(),
+            MerkleVerificationError::WitnessTooLong
+        );
+    }
+}
@@ -1,13 +1,22 @@
+use std::borrow::Cow;
 use std::cmp::Reverse;
 use std::collections::BTreeMap;
 
-use itertools::Itertools;
+use itertools::{izip, Itertools};
+#[cfg(feature = "parallel")]
+use rayon::prelude::*;
 use serde::{Deserialize, Serialize};
 
 use super::ops::{MerkleHasher, MerkleOps};
 use super::utils::{next_decommitment_node, option_flatten_peekable};
-use crate::core::backend::{Col, Column};
+use crate::core::backend::simd::SimdBackend;
+use crate::core::backend::{Col, Column, ColumnOps, CpuBackend};
+use crate::core::channel::add_hashes;
+#[cfg(feature = "parallel")]
+use crate::core::channel::HashCounter;
 use crate::core::fields::m31::BaseField;
+use crate::core::fields::secure_column::SecureColumnByCoords;
+use crate::core::fields::FieldOps;
 use crate::core::utils::PeekableExt;
 use crate::core::ColumnVec;
 
@@ -38,6 +47,29 @@ impl<B: MerkleOps<H>, H: MerkleHasher> MerkleProver<B, H> {
     ///
     /// A new instance of `MerkleProver` with the committed layers.
     pub fn commit(columns: Vec<&Col<B, BaseField>>) -> Self {
+        Self::commit_with_progress(columns, |_| {})
+    }
+
+    /// Commits to base field and secure field columns in one tree. Each secure column is
+    /// committed as its 4 coordinate columns, in place, so the tree is
+    /// the same as [Self::commit] on [MixedColumn::flatten] of the columns. Decommitments refer to
+    /// the flattened columns too.
+    pub fn commit_mixed(columns: &[MixedColumn<'_, B>]) -> Self
+    where
+        B: FieldOps<BaseField>,
+    {
+        Self::commit(MixedColumn::flatten(columns))
+    }
+
+    /// Commits to columns, like [Self::commit], reporting progress along the way.
+    ///
+    /// `progress` is called once per committed layer, with the layer's index in commit order.
+    /// Layers are committed from the largest one to the root, so for a tree with a largest layer
+    /// of size 2^`max_log_size`, the callback receives `0, 1, ..., max_log_size`.
+    pub fn commit_with_progress(
+        columns: Vec<&Col<B, BaseField>>,
+        mut progress: impl FnMut(u32),
+    ) -> Self {
         assert!(!columns.is_empty());
 
         let columns = &mut columns
@@ -53,12 +85,125 @@ impl<B: MerkleOps<H>, H: MerkleHasher> MerkleProver<B, H> {
                 .peek_take_while(|column| column.len().ilog2() == log_size)
                 .collect_vec();
 
-            layers.push(B::commit_on_layer(log_size, layers.last(), &layer_columns));
+            layers.push(Self::commit_layer(log_size, layers.last(), &layer_columns));
+            progress(max_log_size - log_size);
+        }
+        layers.reverse();
+        Self { layers }
+    }
+
+    /// Commits to columns given one layer at a time, so that only the columns of a single layer
+    /// need to be in memory at once.
+    ///
+    /// `layer_columns` yields `(log_size, columns)` pairs in strictly decreasing `log_size` order,
+    /// each holding all the columns of that log size, in commitment order. Log sizes without
+    /// columns may be skipped. The columns of each layer are dropped once the layer is hashed. The
+    /// resulting tree is the same as [Self::commit] on all the columns, and its hash layers are
+    /// kept in memory as usual.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `layer_columns` is empty, or if the log sizes are not strictly
+    /// decreasing or don't match the sizes of their columns.
+    pub fn commit_streaming(
+        layer_columns: impl IntoIterator<Item = (u32, Vec<Col<B, BaseField>>)>,
+    ) -> Self {
+        let layer_columns = &mut layer_columns.into_iter().peekable();
+        let mut layers: Vec<Col<B, H::Hash>> = Vec::new();
+
+        let max_log_size = layer_columns.peek().expect("No columns to commit to.").0;
+        for log_size in (0..=max_log_size).rev() {
+            let columns = layer_columns
+                .next_if(|(layer_log_size, _)| *layer_log_size == log_size)
+                .map_or_else(Vec::new, |(_, columns)| columns);
+            assert!(
+                columns.iter().all(|column| column.len() == 1 << log_size),
+                "Column sizes don't match log size {log_size}."
+            );
+
+            let columns = columns.iter().collect_vec();
+            layers.push(Self::commit_layer(log_size, layers.last(), &columns));
         }
+        assert!(
+            layer_columns.next().is_none(),
+            "Layer log sizes are not strictly decreasing."
+        );
         layers.reverse();
         Self { layers }
     }
 
+    /// Computes the root of [Self::commit] on `columns`, without keeping the tree.
+    ///
+    /// Only the previous layer is kept while hashing the next one, so the memory footprint is
+    /// about that of the two largest layers instead of the whole tree.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the columns vector is empty.
+    pub fn root_only(columns: Vec<&Col<B, BaseField>>) -> H::Hash {
+        assert!(!columns.is_empty());
+
+        let columns = &mut columns
+            .into_iter()
+            .sorted_by_key(|c| Reverse(c.len()))
+            .peekable();
+        let mut prev_layer: Option<Col<B, H::Hash>> = None;
+
+        let max_log_size = columns.peek().unwrap().len().ilog2();
+        for log_size in (0..=max_log_size).rev() {
+            let layer_columns = columns
+                .peek_take_while(|column| column.len().ilog2() == log_size)
+                .collect_vec();
+
+            prev_layer = Some(Self::commit_layer(
+                log_size,
+                prev_layer.as_ref(),
+                &layer_columns,
+            ));
+        }
+        prev_layer.unwrap().at(0)
+    }
+
+    /// Hashes a single layer of the tree, exactly as [Self::commit] does, e.g. to compute the
+    /// layers of one tree on different machines.
+    ///
+    /// `prev_layer` is the hashes of the layer of size 2^(`log_size`+1), or `None` for the
+    /// largest layer, and `columns` are the columns of size 2^`log_size`. See
+    /// [MerkleOps::commit_on_layer] for more details.
+    pub fn commit_layer(
+        log_size: u32,
+        prev_layer: Option<&Col<B, H::Hash>>,
+        columns: &[&Col<B, BaseField>],
+    ) -> Col<B, H::Hash> {
+        add_hashes(1 << log_size);
+        B::commit_on_layer(log_size, prev_layer, columns)
+    }
+
+    /// Commits to several independent trees, each on its own thread.
+    ///
+    /// Returns the root and prover of each tree, in the order of `tree_columns`. Each tree is
+    /// committed exactly as [Self::commit] would, so the roots don't depend on the number of
+    /// threads. The hashes are counted by the [HashCounter] of the calling thread, if any.
+    #[cfg(feature = "parallel")]
+    pub fn commit_trees_parallel(tree_columns: &[Vec<&Col<B, BaseField>>]) -> Vec<(H::Hash, Self)>
+    where
+        Col<B, BaseField>: Sync,
+        Self: Send,
+    {
+        let counter = HashCounter::current();
+        tree_columns
+            .par_iter()
+            .map(|columns| {
+                let commit = || Self::commit(columns.clone());
+                let prover = match &counter {
+                    Some(counter) => counter.count(commit),
+                    None => commit(),
+                };
+                (prover.root(), prover)
+            })
+            .collect()
+    }
+
     /// Decommits to columns on the given queries.
     /// Queries are given as indices to the largest column.
     ///
@@ -78,132 +223,522 @@ impl<B: MerkleOps<H>, H: MerkleHasher> MerkleProver<B, H> {
         queries_per_log_size: BTreeMap<u32, Vec<usize>>,
         columns: Vec<&Col<B, BaseField>>,
     ) -> (ColumnVec<Vec<BaseField>>, MerkleDecommitment<H>) {
-        // Check that queries are sorted and deduped.
-        // TODO(andrew): Consider using a Queries struct to prevent this.
-        for queries in queries_per_log_size.values() {
-            assert!(
-                queries.windows(2).all(|w| w[0] < w[1]),
-                "Queries are not sorted."
-            );
+        self.decommit_batch(&[queries_per_log_size], columns)
+            .pop()
+            .unwrap()
+    }
+
+    /// Decommits to columns on the given queries, like [Self::decommit], and attaches the log
+    /// sizes of the columns to the decommitment, for verifiers that learn the layout of the tree
+    /// from the proof. See [super::verifier::MerkleVerifier::from_decommitment].
+    pub fn decommit_with_layout(
+        &self,
+        queries_per_log_size: BTreeMap<u32, Vec<usize>>,
+        columns: Vec<&Col<B, BaseField>>,
+    ) -> (ColumnVec<Vec<BaseField>>, DecommitmentWithLayout<H>) {
+        let column_log_sizes = columns.iter().map(|c| c.len().ilog2()).collect();
+        let (queried_values, decommitment) = self.decommit(queries_per_log_size, columns);
+        (
+            queried_values,
+            DecommitmentWithLayout {
+                decommitment,
+                column_log_sizes,
+            },
+        )
+    }
+
+    /// Decommits to columns on the queries given as a mask per log size, like [Self::decommit] on
+    /// the indices of the set bits of each mask.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the length of a mask is not 2^`log_size`.
+    pub fn decommit_masked(
+        &self,
+        masks: BTreeMap<u32, Vec<bool>>,
+        columns: Vec<&Col<B, BaseField>>,
+    ) -> (ColumnVec<Vec<BaseField>>, MerkleDecommitment<H>) {
+        let queries_per_log_size = masks
+            .into_iter()
+            .map(|(log_size, mask)| {
+                assert_eq!(mask.len(), 1 << log_size, "Invalid mask length.");
+                let queries = mask.into_iter().positions(|is_queried| is_queried);
+                (log_size, queries.collect_vec())
+            })
+            .collect();
+        self.decommit(queries_per_log_size, columns)
+    }
+
+    /// Returns the number of hashes in the `hash_witness` of the decommitment that
+    /// [Self::decommit] would return for `queries_per_log_size`, without computing it.
+    pub fn decommitment_size(&self, queries_per_log_size: &BTreeMap<u32, Vec<usize>>) -> usize {
+        assert_queries_sorted(queries_per_log_size);
+
+        let mut n_hashes = 0;
+        let mut last_layer_queries = vec![];
+        for layer_log_size in (0..self.layers.len() as u32).rev() {
+            let has_prev_layer = layer_log_size as usize + 1 < self.layers.len();
+            let mut prev_layer_queries = std::mem::take(&mut last_layer_queries)
+                .into_iter()
+                .peekable();
+            let mut layer_column_queries =
+                option_flatten_peekable(queries_per_log_size.get(&layer_log_size));
+
+            while let Some(node_index) =
+                next_decommitment_node(&mut prev_layer_queries, &mut layer_column_queries)
+            {
+                if has_prev_layer {
+                    // Each child that was not computed is a witness hash.
+                    for child_index in [2 * node_index, 2 * node_index + 1] {
+                        if prev_layer_queries.next_if_eq(&child_index).is_none() {
+                            n_hashes += 1;
+                        }
+                    }
+                }
+                layer_column_queries.next_if_eq(&node_index);
+                last_layer_queries.push(node_index);
+            }
         }
+        n_hashes
+    }
+
+    /// Proves the inclusion of a single value, the value of column `column_index` at row
+    /// `row_index`.
+    ///
+    /// # Arguments
+    ///
+    /// * `columns` - A vector of references to the committed columns.
+    /// * `column_index` - The index of the column in `columns`.
+    /// * `row_index` - The row of the value in the column.
+    ///
+    /// # Returns
+    ///
+    /// A tuple containing:
+    /// * The value.
+    /// * A `MerkleDecommitment` for the single query. The values of the other columns of the same
+    ///   size at `row_index` come first in the column witness, in column order.
+    ///
+    /// See [super::verifier::MerkleVerifier::verify_inclusion].
+    pub fn prove_inclusion(
+        &self,
+        columns: Vec<&Col<B, BaseField>>,
+        column_index: usize,
+        row_index: usize,
+    ) -> (BaseField, MerkleDecommitment<H>) {
+        let log_size = columns[column_index].len().ilog2();
+        let queries = BTreeMap::from([(log_size, vec![row_index])]);
+        let (queried_values, mut decommitment) = self.decommit(queries, columns);
+
+        let value = queried_values[column_index][0];
+        let sibling_values = queried_values
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| *i != column_index)
+            .flat_map(|(_, values)| values);
+        decommitment.column_witness = sibling_values.chain(decommitment.column_witness).collect();
+        (value, decommitment)
+    }
+
+    /// Decommits to columns on several independent query sets, walking the tree only once.
+    /// Each query set is decommitted exactly as in [Self::decommit].
+    ///
+    /// # Arguments
+    ///
+    /// * `query_sets` - A slice of maps from log_size to a vector of queries for columns of that
+    ///  log_size.
+    /// * `columns` - A vector of references to columns.
+    ///
+    /// # Returns
+    ///
+    /// For each query set, in order, the queried values and the `MerkleDecommitment` that
+    /// [Self::decommit] would return for it.
+    pub fn decommit_batch(
+        &self,
+        query_sets: &[BTreeMap<u32, Vec<usize>>],
+        columns: Vec<&Col<B, BaseField>>,
+    ) -> Vec<(ColumnVec<Vec<BaseField>>, MerkleDecommitment<H>)> {
+        query_sets.iter().for_each(assert_queries_sorted);
 
         // Prepare output buffers.
-        let mut queried_values_by_layer = vec![];
-        let mut decommitment = MerkleDecommitment::empty();
+        let column_log_sizes = columns.iter().map(|c| c.len().ilog2()).collect_vec();
+        let mut queried_values_by_layer_per_set = vec![vec![]; query_sets.len()];
+        let mut decommitments = (0..query_sets.len())
+            .map(|_| MerkleDecommitment::empty())
+            .collect_vec();
 
         // Sort columns by layer.
         let mut columns_by_layer = columns
             .iter()
+            .copied()
             .sorted_by_key(|c| Reverse(c.len()))
             .peekable();
 
-        let mut last_layer_queries = vec![];
+        let mut last_layer_queries_per_set = vec![vec![]; query_sets.len()];
         for layer_log_size in (0..self.layers.len() as u32).rev() {
-            // Prepare write buffer for queried values to the current layer.
-            let mut layer_queried_values = vec![];
-
-            // Prepare write buffer for queries to the current layer. This will propagate to the
-            // next layer.
-            let mut layer_total_queries = vec![];
-
-            // Each layer node is a hash of column values as previous layer hashes.
-            // Prepare the relevant columns and previous layer hashes to read from.
+            // The columns of the layer are shared by all query sets.
             let layer_columns = columns_by_layer
                 .peek_take_while(|column| column.len().ilog2() == layer_log_size)
                 .collect_vec();
-            let previous_layer_hashes = self.layers.get(layer_log_size as usize + 1);
 
-            // Queries to this layer come from queried node in the previous layer and queried
-            // columns in this one.
-            let mut prev_layer_queries = last_layer_queries.into_iter().peekable();
-            let mut layer_column_queries =
-                option_flatten_peekable(queries_per_log_size.get(&layer_log_size));
+            for (queries_per_log_size, decommitment, queried_values_by_layer, last_layer_queries) in izip!(
+                query_sets,
+                &mut decommitments,
+                &mut queried_values_by_layer_per_set,
+                &mut last_layer_queries_per_set
+            ) {
+                let (layer_decommitment, layer_total_queries) = self.decommit_layer(
+                    layer_log_size,
+                    &layer_columns,
+                    std::mem::take(last_layer_queries),
+                    queries_per_log_size.get(&layer_log_size),
+                );
+                decommitment
+                    .hash_witness
+                    .extend(layer_decommitment.hash_witness);
+                decommitment
+                    .column_witness
+                    .extend(layer_decommitment.column_witness);
+                queried_values_by_layer.push(layer_decommitment.queried_values);
 
-            // Merge previous layer queries and column queries.
-            while let Some(node_index) =
-                next_decommitment_node(&mut prev_layer_queries, &mut layer_column_queries)
-            {
-                if let Some(previous_layer_hashes) = previous_layer_hashes {
-                    // If the left child was not computed, add it to the witness.
-                    if prev_layer_queries.next_if_eq(&(2 * node_index)).is_none() {
-                        decommitment
-                            .hash_witness
-                            .push(previous_layer_hashes.at(2 * node_index));
-                    }
+                // Propagate queries to the next layer.
+                *last_layer_queries = layer_total_queries;
+            }
+        }
 
-                    // If the right child was not computed, add it to the witness.
-                    if prev_layer_queries
-                        .next_if_eq(&(2 * node_index + 1))
-                        .is_none()
-                    {
-                        decommitment
-                            .hash_witness
-                            .push(previous_layer_hashes.at(2 * node_index + 1));
-                    }
-                }
+        queried_values_by_layer_per_set
+            .into_iter()
+            .zip(decommitments)
+            .map(|(mut queried_values_by_layer, decommitment)| {
+                queried_values_by_layer.reverse();
+
+                // Rearrange returned queried values according to input, and not by layer.
+                let queried_values =
+                    rearrange_queried_values(queried_values_by_layer, &column_log_sizes);
+
+                (queried_values, decommitment)
+            })
+            .collect()
+    }
+
+    /// Decommits to columns on the given queries one layer at a time, starting from the largest
+    /// layer. Unlike [Self::decommit], layers are only computed when the iterator is advanced, so
+    /// the decommitment never has to be held in memory at once.
+    ///
+    /// The yielded layers can be reassembled with [MerkleDecommitment::from_layers].
+    ///
+    /// # Arguments
+    ///
+    /// * `queries_per_log_size` - A map from log_size to a vector of queries for columns of that
+    ///  log_size.
+    /// * `columns` - A vector of references to columns.
+    pub fn decommit_streaming<'a>(
+        &'a self,
+        queries_per_log_size: BTreeMap<u32, Vec<usize>>,
+        columns: Vec<&'a Col<B, BaseField>>,
+    ) -> impl Iterator<Item = LayerDecommitment<H>> + 'a {
+        assert_queries_sorted(&queries_per_log_size);
+
+        // Sort columns by layer.
+        let mut columns_by_layer = columns
+            .into_iter()
+            .sorted_by_key(|c| Reverse(c.len()))
+            .peekable();
+
+        let mut last_layer_queries = vec![];
+        (0..self.layers.len() as u32)
+            .rev()
+            .map(move |layer_log_size| {
+                let layer_columns = columns_by_layer
+                    .peek_take_while(|column| column.len().ilog2() == layer_log_size)
+                    .collect_vec();
+                let (layer_decommitment, layer_total_queries) = self.decommit_layer(
+                    layer_log_size,
+                    &layer_columns,
+                    std::mem::take(&mut last_layer_queries),
+                    queries_per_log_size.get(&layer_log_size),
+                );
+
+                // Propagate queries to the next layer.
+                last_layer_queries = layer_total_queries;
+                layer_decommitment
+            })
+    }
+
+    /// Decommits a single layer of the tree.
+    ///
+    /// `layer_columns` are the columns of size 2^`layer_log_size`, `prev_layer_queries` are the
+    /// node indices queried in the previous (larger) layer and `layer_queries` are the queries to
+    /// the columns of this layer.
+    ///
+    /// Returns the layer decommitment and the node indices queried in this layer.
+    fn decommit_layer(
+        &self,
+        layer_log_size: u32,
+        layer_columns: &[&Col<B, BaseField>],
+        prev_layer_queries: Vec<usize>,
+        layer_queries: Option<&Vec<usize>>,
+    ) -> (LayerDecommitment<H>, Vec<usize>) {
+        let mut layer_decommitment = LayerDecommitment {
+            log_size: layer_log_size,
+            hash_witness: vec![],
+            column_witness: vec![],
+            queried_values: vec![],
+        };
+
+        // Prepare write buffer for queries to the current layer. This will propagate to the next
+        // layer.
+        let mut layer_total_queries = vec![];
 
-                // If the column values were queried, return them.
-                let node_values = layer_columns.iter().map(|c| c.at(node_index));
-                if layer_column_queries.next_if_eq(&node_index).is_some() {
-                    layer_queried_values.push(node_values.collect_vec());
-                } else {
-                    // Otherwise, add them to the witness.
-                    decommitment.column_witness.extend(node_values);
+        // Each layer node is a hash of column values as previous layer hashes.
+        let previous_layer_hashes = self.layers.get(layer_log_size as usize + 1);
+
+        // Queries to this layer come from queried node in the previous layer and queried
+        // columns in this one.
+        let mut prev_layer_queries = prev_layer_queries.into_iter().peekable();
+        let mut layer_column_queries = option_flatten_peekable(layer_queries);
+
+        // Merge previous layer queries and column queries.
+        while let Some(node_index) =
+            next_decommitment_node(&mut prev_layer_queries, &mut layer_column_queries)
+        {
+            if let Some(previous_layer_hashes) = previous_layer_hashes {
+                // If the left child was not computed, add it to the witness.
+                if prev_layer_queries.next_if_eq(&(2 * node_index)).is_none() {
+                    layer_decommitment
+                        .hash_witness
+                        .push(previous_layer_hashes.at(2 * node_index));
                 }
 
-                layer_total_queries.push(node_index);
+                // If the right child was not computed, add it to the witness.
+                if prev_layer_queries
+                    .next_if_eq(&(2 * node_index + 1))
+                    .is_none()
+                {
+                    layer_decommitment
+                        .hash_witness
+                        .push(previous_layer_hashes.at(2 * node_index + 1));
+                }
             }
 
-            queried_values_by_layer.push(layer_queried_values);
+            // If the column values were queried, return them.
+            let node_values = layer_columns.iter().map(|c| c.at(node_index));
+            if layer_column_queries.next_if_eq(&node_index).is_some() {
+                layer_decommitment
+                    .queried_values
+                    .push(node_values.collect_vec());
+            } else {
+                // Otherwise, add them to the witness.
+                layer_decommitment.column_witness.extend(node_values);
+            }
 
-            // Propagate queries to the next layer.
-            last_layer_queries = layer_total_queries;
+            layer_total_queries.push(node_index);
         }
-        queried_values_by_layer.reverse();
 
-        // Rearrange returned queried values according to input, and not by layer.
-        let queried_values = Self::rearrange_queried_values(queried_values_by_layer, columns);
+        (layer_decommitment, layer_total_queries)
+    }
 
-        (queried_values, decommitment)
+    pub fn root(&self) -> H::Hash {
+        self.layers.first().unwrap().at(0)
     }
 
-    /// Given queried values by layer, rearranges in the order of input columns.
-    fn rearrange_queried_values(
-        queried_values_by_layer: Vec<Vec<Vec<BaseField>>>,
+    /// Returns the root, and a view of all the layers of the tree, from the root layer to the
+    /// largest one (see [Self::layers]). Layer `i` holds the `2^i` roots of the subtrees of that
+    /// depth.
+    pub fn root_and_layers(&self) -> (H::Hash, &[Col<B, H::Hash>]) {
+        (self.root(), &self.layers)
+    }
+
+    /// Returns true if both trees have the same root, i.e. they commit to the same columns.
+    pub fn same_commitment(&self, other: &Self) -> bool {
+        self.root() == other.root()
+    }
+
+    /// Returns true if [Self::commit] on `columns` and on `other_columns` commits to the same
+    /// data. Unlike [Self::same_commitment], this compares the column values themselves, for when
+    /// they are still at hand.
+    ///
+    /// Columns are compared in commitment order: by decreasing size, keeping the given order among
+    /// columns of the same size.
+    pub fn same_data(
         columns: Vec<&Col<B, BaseField>>,
-    ) -> Vec<Vec<BaseField>> {
-        // Turn each column queried values into an iterator.
-        let mut queried_values_by_layer = queried_values_by_layer
-            .into_iter()
-            .map(|layer_results| {
-                layer_results
-                    .into_iter()
-                    .map(|x| x.into_iter())
-                    .collect_vec()
-            })
-            .collect_vec();
+        other_columns: Vec<&Col<B, BaseField>>,
+    ) -> bool {
+        columns.len() == other_columns.len()
+            && columns
+                .into_iter()
+                .sorted_by_key(|c| Reverse(c.len()))
+                .zip(
+                    other_columns
+                        .into_iter()
+                        .sorted_by_key(|c| Reverse(c.len())),
+                )
+                .all(|(column, other_column)| column.to_cpu() == other_column.to_cpu())
+    }
+}
+
+/// A column committed by [MerkleProver::commit_mixed].
+#[derive(Debug)]
+pub enum MixedColumn<'a, B: FieldOps<BaseField>> {
+    Base(&'a Col<B, BaseField>),
+    Secure(&'a SecureColumnByCoords<B>),
+}
 
-        // For each input column, fetch the queried values from the corresponding layer.
-        let queried_values = columns
+impl<'a, B: FieldOps<BaseField>> MixedColumn<'a, B> {
+    /// Returns the base field columns that are committed for `columns`: base columns as is, and
+    /// the coordinate columns of each secure column in its place.
+    pub fn flatten(columns: &[Self]) -> Vec<&'a Col<B, BaseField>> {
+        columns
             .iter()
-            .map(|column| {
-                queried_values_by_layer
-                    .get_mut(column.len().ilog2() as usize)
-                    .unwrap()
-                    .iter_mut()
-                    .map(|x| x.next().unwrap())
-                    .collect_vec()
+            .flat_map(|column| match *column {
+                Self::Base(column) => vec![column],
+                Self::Secure(column) => column.columns.iter().collect(),
             })
-            .collect_vec();
-        queried_values
+            .collect()
     }
+}
 
-    pub fn root(&self) -> H::Hash {
-        self.layers.first().unwrap().at(0)
+/// A column committed by [MerkleProver::commit_heterogeneous], held by the backend that should hash
+/// it.
+#[derive(Clone, Copy, Debug)]
+pub enum HeterogeneousColumn<'a> {
+    Cpu(&'a Col<CpuBackend, BaseField>),
+    Simd(&'a Col<SimdBackend, BaseField>),
+}
+
+impl<'a> HeterogeneousColumn<'a> {
+    pub fn len(&self) -> usize {
+        match self {
+            Self::Cpu(column) => column.len(),
+            Self::Simd(column) => column.len(),
+        }
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+}
+
+impl<H: MerkleHasher> MerkleProver<CpuBackend, H>
+where
+    CpuBackend: MerkleOps<H> + ColumnOps<H::Hash, Column = Vec<H::Hash>>,
+    SimdBackend: MerkleOps<H> + ColumnOps<H::Hash, Column = Vec<H::Hash>>,
+{
+    /// Commits to columns held by different backends, e.g. to balance the load between the CPU and
+    /// SIMD. The tree is the same as [Self::commit] on the same data with a single backend.
+    ///
+    /// Each node hash covers all the columns of its layer, so a layer is hashed by a single
+    /// backend: the one holding the layer's first column in commitment order. The layer's columns
+    /// held by the other backend are copied over. Both backends store hash layers as `Vec`s, so
+    /// layers are passed between them as is.
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if the columns vector is empty.
+    pub fn commit_heterogeneous(columns: Vec<HeterogeneousColumn<'_>>) -> Self {
+        assert!(!columns.is_empty());
+
+        let columns = &mut columns
+            .into_iter()
+            .sorted_by_key(|c| Reverse(c.len()))
+            .peekable();
+        let mut layers: Vec<Vec<H::Hash>> = Vec::new();
+
+        let max_log_size = columns.peek().unwrap().len().ilog2();
+        for log_size in (0..=max_log_size).rev() {
+            let layer_columns = columns
+                .peek_take_while(|column| column.len().ilog2() == log_size)
+                .collect_vec();
+
+            let layer = match layer_columns.first() {
+                Some(HeterogeneousColumn::Simd(_)) => {
+                    let layer_columns = layer_columns
+                        .iter()
+                        .map(|column| match column {
+                            HeterogeneousColumn::Cpu(column) => {
+                                Cow::Owned(column.iter().copied().collect())
+                            }
+                            HeterogeneousColumn::Simd(column) => Cow::Borrowed(*column),
+                        })
+                        .collect_vec();
+                    <SimdBackend as MerkleOps<H>>::commit_on_layer(
+                        log_size,
+                        layers.last(),
+                        &layer_columns.iter().map(|c| c.as_ref()).collect_vec(),
+                    )
+                }
+                _ => {
+                    let layer_columns = layer_columns
+                        .iter()
+                        .map(|column| match column {
+                            HeterogeneousColumn::Cpu(column) => Cow::Borrowed(*column),
+                            HeterogeneousColumn::Simd(column) => Cow::Owned(column.to_cpu()),
+                        })
+                        .collect_vec();
+                    Self::commit_layer(
+                        log_size,
+                        layers.last(),
+                        &layer_columns.iter().map(|c| c.as_ref()).collect_vec(),
+                    )
+                }
+            };
+            layers.push(layer);
+        }
+        layers.reverse();
+        Self { layers }
     }
 }
 
+/// Checks that queries are sorted and deduped.
+// TODO(andrew): Consider using a Queries struct to prevent this.
+fn assert_queries_sorted(queries_per_log_size: &BTreeMap<u32, Vec<usize>>) {
+    for queries in queries_per_log_size.values() {
+        assert!(
+            queries.windows(2).all(|w| w[0] < w[1]),
+            "Queries are not sorted."
+        );
+    }
+}
+
+/// Given queried values by layer, rearranges in the order of input columns.
+fn rearrange_queried_values(
+    queried_values_by_layer: Vec<Vec<Vec<BaseField>>>,
+    column_log_sizes: &[u32],
+) -> Vec<Vec<BaseField>> {
+    // Turn each column queried values into an iterator.
+    let mut queried_values_by_layer = queried_values_by_layer
+        .into_iter()
+        .map(|layer_results| {
+            layer_results
+                .into_iter()
+                .map(|x| x.into_iter())
+                .collect_vec()
+        })
+        .collect_vec();
+
+    // For each input column, fetch the queried values from the corresponding layer.
+    let queried_values = column_log_sizes
+        .iter()
+        .map(|&log_size| {
+            queried_values_by_layer
+                .get_mut(log_size as usize)
+                .unwrap()
+                .iter_mut()
+                .map(|x| x.next().unwrap())
+                .collect_vec()
+        })
+        .collect_vec();
+    queried_values
+}
+
+/// A decommitment of a [MerkleProver] on a set of queries.
+///
+/// Serializes as its two fields, `hash_witness` and `column_witness`, in order. Only `H::Hash` is
+/// required to be serializable, not the hasher itself.
 #[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq, PartialOrd)]
+#[serde(bound(
+    serialize = "H::Hash: Serialize",
+    deserialize = "H::Hash: Deserialize<'de>"
+))]
 pub struct MerkleDecommitment<H: MerkleHasher> {
     /// Hash values that the verifier needs but cannot deduce from previous computations, in the
     /// order they are needed.
@@ -221,4 +756,586 @@ impl<H: MerkleHasher> MerkleDecommitment<H> {
             column_witness: Vec::new(),
         }
     }
+
+    /// Reassembles the layers yielded by [MerkleProver::decommit_streaming] into the queried
+    /// values and decommitment that [MerkleProver::decommit] returns.
+    ///
+    /// `column_log_sizes` are the log sizes of the decommitted columns, in the order they were
+    /// passed to the prover.
+    pub fn from_layers(
+        layers: impl IntoIterator<Item = LayerDecommitment<H>>,
+        column_log_sizes: &[u32],
+    ) -> (ColumnVec<Vec<BaseField>>, Self) {
+        let mut decommitment = Self::empty();
+        let mut queried_values_by_layer = vec![];
+        for layer in layers {
+            let log_size = layer.log_size as usize;
+            if queried_values_by_layer.len() <= log_size {
+                queried_values_by_layer.resize(log_size + 1, vec![]);
+            }
+            decommitment.hash_witness.extend(layer.hash_witness);
+            decommitment.column_witness.extend(layer.column_witness);
+            queried_values_by_layer[log_size] = layer.queried_values;
+        }
+        let queried_values = rearrange_queried_values(queried_values_by_layer, column_log_sizes);
+        (queried_values, decommitment)
+    }
+}
+
+/// A [MerkleDecommitment] with the log sizes of the committed columns, for verifiers that learn
+/// the layout of the tree from the proof. See [MerkleProver::decommit_with_layout].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound(
+    serialize = "H::Hash: Serialize",
+    deserialize = "H::Hash: Deserialize<'de>"
+))]
+pub struct DecommitmentWithLayout<H: MerkleHasher> {
+    pub decommitment: MerkleDecommitment<H>,
+    /// The log sizes of the committed columns, in the order they were passed to the prover.
+    pub column_log_sizes: Vec<u32>,
+}
+
+/// The part of a [MerkleDecommitment] that belongs to a single layer of the tree.
+/// See [MerkleProver::decommit_streaming].
+#[derive(Clone, Debug, Serialize, Deserialize, PartialEq, Eq)]
+#[serde(bound(
+    serialize = "H::Hash: Serialize",
+    deserialize = "H::Hash: Deserialize<'de>"
+))]
+pub struct LayerDecommitment<H: MerkleHasher> {
+    /// The log size of the layer.
+    pub log_size: u32,
+    /// Hash values of the previous layer that the verifier needs, in the order they are needed.
+    pub hash_witness: Vec<H::Hash>,
+    /// Column values of this layer that the verifier needs, in the order they are needed.
+    pub column_witness: Vec<BaseField>,
+    /// The values of this layer's columns at each queried node, in increasing node order.
+    pub queried_values: Vec<Vec<BaseField>>,
+}
+
+#[cfg(test)]
+mod tests {
+    use std::collections::BTreeMap;
+    use std::ops::Range;
+
+    use itertools::Itertools;
+    use num_traits::One;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use crate::core::backend::simd::column::BaseColumn;
+    use crate::core::backend::simd::SimdBackend;
+    use crate::core::backend::CpuBackend;
+    use crate::core::fields::m31::BaseField;
+    use crate::core::fields::qm31::SecureField;
+    use crate::core::fields::secure_column::SecureColumnByCoords;
+    use crate::core::vcs::prover::{
+        HeterogeneousColumn, MerkleDecommitment, MerkleProver, MixedColumn,
+    };
+    use crate::core::vcs::sha256_merkle::Sha256MerkleHasher;
+    use crate::core::vcs::test_utils::{random_columns, random_queries};
+    use crate::core::vcs::verifier::{MerkleVerificationError, MerkleVerifier};
+
+    const LOG_SIZE_RANGE: Range<u32> = 3..6;
+    const N_QUERIES: usize = 3;
+
+    #[test]
+    fn test_root_and_layers() {
+        let rng = &mut SmallRng::seed_from_u64(0);
+        let columns = random_columns(rng, 10, LOG_SIZE_RANGE);
+        let max_log_size = columns.iter().map(|c| c.len().ilog2()).max().unwrap();
+        let prover =
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(columns.iter().collect_vec());
+
+        let (root, layers) = prover.root_and_layers();
+
+        assert_eq!(root, prover.root());
+        assert_eq!(layers[0], [root]);
+        assert_eq!(layers.len(), max_log_size as usize + 1);
+        for (log_size, layer) in layers.iter().enumerate() {
+            assert_eq!(layer.len(), 1 << log_size);
+        }
+    }
+
+    #[test]
+    fn test_commit_streaming() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cols = [12, 10, 10, 7, 7, 7, 3, 0]
+            .map(|log_size| {
+                (0..1 << log_size)
+                    .map(|_| BaseField::from(rng.gen_range(0..(1 << 30))))
+                    .collect_vec()
+            })
+            .to_vec();
+        let layer_columns = cols
+            .iter()
+            .group_by(|column| column.len().ilog2())
+            .into_iter()
+            .map(|(log_size, columns)| (log_size, columns.cloned().collect_vec()))
+            .collect_vec();
+
+        let merkle =
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit_streaming(layer_columns);
+
+        let expected =
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(cols.iter().collect_vec());
+        assert_eq!(merkle.root(), expected.root());
+        assert_eq!(merkle.layers, expected.layers);
+    }
+
+    #[test]
+    fn test_commit_layer_by_layer() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cols = [8, 6, 6, 3, 0]
+            .map(|log_size| {
+                (0..1 << log_size)
+                    .map(|_| BaseField::from(rng.gen_range(0..(1 << 30))))
+                    .collect_vec()
+            })
+            .to_vec();
+
+        let mut layers = Vec::new();
+        for log_size in (0..=8).rev() {
+            let columns = cols
+                .iter()
+                .filter(|column| column.len() == 1 << log_size)
+                .collect_vec();
+            let layer = MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit_layer(
+                log_size,
+                layers.last(),
+                &columns,
+            );
+            layers.push(layer);
+        }
+        layers.reverse();
+
+        let expected =
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(cols.iter().collect_vec());
+        let merkle = MerkleProver::<CpuBackend, Sha256MerkleHasher> { layers };
+        assert_eq!(merkle.root(), expected.root());
+        assert_eq!(merkle.layers, expected.layers);
+    }
+
+    #[test]
+    fn test_root_only() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        for _ in 0..5 {
+            let cols = random_columns(&mut rng, 10, LOG_SIZE_RANGE);
+
+            let root = MerkleProver::<CpuBackend, Sha256MerkleHasher>::root_only(
+                cols.iter().collect_vec(),
+            );
+
+            let expected =
+                MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(cols.iter().collect_vec());
+            assert_eq!(root, expected.root());
+        }
+    }
+
+    #[test]
+    fn test_commit_heterogeneous() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        for _ in 0..5 {
+            let cols = random_columns(&mut rng, 10, LOG_SIZE_RANGE);
+            let simd_cols = cols
+                .iter()
+                .map(|col| col.iter().copied().collect::<BaseColumn>())
+                .collect_vec();
+            let columns = cols
+                .iter()
+                .zip(&simd_cols)
+                .map(|(col, simd_col)| match rng.gen() {
+                    true => HeterogeneousColumn::Cpu(col),
+                    false => HeterogeneousColumn::Simd(simd_col),
+                })
+                .collect_vec();
+
+            let merkle =
+                MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit_heterogeneous(columns);
+
+            let cpu_merkle =
+                MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(cols.iter().collect_vec());
+            let simd_merkle = MerkleProver::<SimdBackend, Sha256MerkleHasher>::commit(
+                simd_cols.iter().collect_vec(),
+            );
+            assert_eq!(merkle.root(), cpu_merkle.root());
+            assert_eq!(merkle.root(), simd_merkle.root());
+            assert_eq!(merkle.layers, cpu_merkle.layers);
+        }
+    }
+
+    #[test]
+    fn test_commit_mixed() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let base_cols = random_columns(&mut rng, 4, LOG_SIZE_RANGE);
+        let secure_cols = [4, 6]
+            .map(|log_size| {
+                (0..1 << log_size)
+                    .map(|_| rng.gen::<SecureField>())
+                    .collect::<SecureColumnByCoords<CpuBackend>>()
+            })
+            .to_vec();
+        let columns = vec![
+            MixedColumn::Base(&base_cols[0]),
+            MixedColumn::Secure(&secure_cols[0]),
+            MixedColumn::Base(&base_cols[1]),
+            MixedColumn::Base(&base_cols[2]),
+            MixedColumn::Secure(&secure_cols[1]),
+            MixedColumn::Base(&base_cols[3]),
+        ];
+
+        let merkle = MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit_mixed(&columns);
+
+        let coordinate_cols = vec![
+            &base_cols[0],
+            &secure_cols[0].columns[0],
+            &secure_cols[0].columns[1],
+            &secure_cols[0].columns[2],
+            &secure_cols[0].columns[3],
+            &base_cols[1],
+            &base_cols[2],
+            &secure_cols[1].columns[0],
+            &secure_cols[1].columns[1],
+            &secure_cols[1].columns[2],
+            &secure_cols[1].columns[3],
+            &base_cols[3],
+        ];
+        let expected = MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(coordinate_cols);
+        assert_eq!(merkle.root(), expected.root());
+
+        // The flattened columns decommit against the mixed tree.
+        let queries = random_queries(&mut rng, N_QUERIES, LOG_SIZE_RANGE);
+        let flattened = MixedColumn::flatten(&columns);
+        let log_sizes = flattened.iter().map(|c| c.len().ilog2()).collect_vec();
+        let (values, decommitment) = merkle.decommit(queries.clone(), flattened);
+        MerkleVerifier::new(merkle.root(), log_sizes)
+            .verify(queries, values, decommitment)
+            .unwrap();
+    }
+
+    #[test]
+    fn test_same_commitment_and_data() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cols = random_columns(&mut rng, 10, LOG_SIZE_RANGE);
+        let mut other_cols = cols.clone();
+        other_cols[3][1] += BaseField::one();
+        let commit = |cols: &[Vec<BaseField>]| {
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(cols.iter().collect_vec())
+        };
+
+        let merkle = commit(&cols);
+
+        assert!(merkle.same_commitment(&commit(&cols)));
+        assert!(!merkle.same_commitment(&commit(&other_cols)));
+        assert!(MerkleProver::<CpuBackend, Sha256MerkleHasher>::same_data(
+            cols.iter().collect(),
+            cols.iter().collect()
+        ));
+        assert!(!MerkleProver::<CpuBackend, Sha256MerkleHasher>::same_data(
+            cols.iter().collect(),
+            other_cols.iter().collect()
+        ));
+        assert!(!MerkleProver::<CpuBackend, Sha256MerkleHasher>::same_data(
+            cols.iter().collect(),
+            cols[1..].iter().collect()
+        ));
+    }
+
+    #[test]
+    fn test_decommit_masked() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cols = random_columns(&mut rng, 10, LOG_SIZE_RANGE);
+        let merkle =
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(cols.iter().collect_vec());
+        let masks: BTreeMap<u32, Vec<bool>> = LOG_SIZE_RANGE
+            .map(|log_size| {
+                (
+                    log_size,
+                    (0..1 << log_size).map(|_| rng.gen_bool(0.2)).collect(),
+                )
+            })
+            .collect();
+        let queries = masks
+            .iter()
+            .map(|(&log_size, mask)| (log_size, mask.iter().positions(|&bit| bit).collect()))
+            .collect();
+
+        let masked_decommitment = merkle.decommit_masked(masks, cols.iter().collect_vec());
+
+        let expected_decommitment = merkle.decommit(queries, cols.iter().collect_vec());
+        assert_eq!(masked_decommitment, expected_decommitment);
+    }
+
+    #[test]
+    fn test_verifier_from_decommitment() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cols = random_columns(&mut rng, 10, LOG_SIZE_RANGE);
+        let log_sizes = cols.iter().map(|c| c.len().ilog2()).collect_vec();
+        let merkle =
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(cols.iter().collect_vec());
+        let queries = random_queries(&mut rng, N_QUERIES, LOG_SIZE_RANGE);
+        let (values, decommitment) =
+            merkle.decommit_with_layout(queries.clone(), cols.iter().collect_vec());
+
+        let verifier =
+            MerkleVerifier::<Sha256MerkleHasher>::from_decommitment(merkle.root(), &decommitment)
+                .unwrap();
+        assert_eq!(verifier.column_log_sizes, log_sizes);
+        verifier
+            .verify_with_layout(queries.clone(), values.clone(), decommitment.clone())
+            .unwrap();
+
+        // A layout that disagrees with the verifier's.
+        let mut tampered = decommitment.clone();
+        let i = log_sizes.iter().position(|&s| s != log_sizes[0]).unwrap();
+        tampered.column_log_sizes.swap(0, i);
+        let known_layout_verifier =
+            MerkleVerifier::<Sha256MerkleHasher>::new(merkle.root(), log_sizes.clone());
+        assert_eq!(
+            known_layout_verifier
+                .verify_with_layout(queries.clone(), values.clone(), tampered.clone())
+                .unwrap_err(),
+            MerkleVerificationError::InconsistentLayout
+        );
+        // A verifier that trusts the tampered layout fails to recompute the root.
+        let tampered_verifier =
+            MerkleVerifier::<Sha256MerkleHasher>::from_decommitment(merkle.root(), &tampered)
+                .unwrap();
+        assert!(tampered_verifier
+            .verify_with_layout(queries, values, tampered)
+            .is_err());
+
+        // Absent and impossible layouts.
+        let mut tampered = decommitment.clone();
+        tampered.column_log_sizes.clear();
+        assert!(MerkleVerifier::from_decommitment(merkle.root(), &tampered).is_err());
+        let mut tampered = decommitment;
+        tampered.column_log_sizes[0] = 64;
+        assert_eq!(
+            MerkleVerifier::from_decommitment(merkle.root(), &tampered).err(),
+            Some(MerkleVerificationError::InconsistentLayout)
+        );
+    }
+
+    #[test]
+    fn test_verify_with_partial_columns() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cols = random_columns(&mut rng, 10, LOG_SIZE_RANGE);
+        let log_sizes = cols.iter().map(|c| c.len().ilog2()).collect_vec();
+        let merkle =
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(cols.iter().collect_vec());
+        let max_log_size = LOG_SIZE_RANGE.end - 1;
+        let queries = BTreeMap::from([(max_log_size, vec![1, 6])]);
+        let (values, decommitment) = merkle.decommit(queries.clone(), cols.iter().collect_vec());
+        let verifier = MerkleVerifier::<Sha256MerkleHasher>::new(merkle.root(), log_sizes.clone());
+
+        // Only the columns of the queried log size are present.
+        let mut partial_values: BTreeMap<usize, Vec<BaseField>> = values
+            .into_iter()
+            .enumerate()
+            .filter(|(i, _)| log_sizes[*i] == max_log_size)
+            .collect();
+        verifier
+            .verify_with_partial_columns(
+                queries.clone(),
+                partial_values.clone(),
+                decommitment.clone(),
+            )
+            .unwrap();
+
+        let mut out_of_range_values = partial_values.clone();
+        out_of_range_values.insert(log_sizes.len(), vec![]);
+        assert_eq!(
+            verifier
+                .verify_with_partial_columns(
+                    queries.clone(),
+                    out_of_range_values,
+                    decommitment.clone()
+                )
+                .unwrap_err(),
+            MerkleVerificationError::InvalidColumnIndex(log_sizes.len())
+        );
+
+        partial_values.pop_first();
+        assert_eq!(
+            verifier
+                .verify_with_partial_columns(queries, partial_values, decommitment)
+                .unwrap_err(),
+            MerkleVerificationError::MissingColumn
+        );
+    }
+
+    #[test]
+    fn test_decommit_batch() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cols = random_columns(&mut rng, 10, LOG_SIZE_RANGE);
+        let log_sizes = cols.iter().map(|c| c.len().ilog2()).collect_vec();
+        let merkle =
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(cols.iter().collect_vec());
+        let query_sets = (0..4)
+            .map(|_| random_queries(&mut rng, N_QUERIES, LOG_SIZE_RANGE))
+            .collect_vec();
+
+        let batch_results = merkle.decommit_batch(&query_sets, cols.iter().collect_vec());
+
+        let verifier = MerkleVerifier::<Sha256MerkleHasher>::new(merkle.root(), log_sizes);
+        for (queries, (values, decommitment)) in query_sets.into_iter().zip_eq(batch_results) {
+            let (expected_values, expected_decommitment) =
+                merkle.decommit(queries.clone(), cols.iter().collect_vec());
+            assert_eq!(values, expected_values);
+            assert_eq!(decommitment, expected_decommitment);
+            verifier
+                .verify(queries.clone(), values.clone(), decommitment.clone())
+                .unwrap();
+
+            let mut tampered_values = values;
+            let tampered_column = tampered_values
+                .iter_mut()
+                .find(|column| !column.is_empty())
+                .unwrap();
+            tampered_column[0] += BaseField::one();
+            assert_eq!(
+                verifier
+                    .verify(queries, tampered_values, decommitment)
+                    .unwrap_err(),
+                MerkleVerificationError::RootMismatch
+            );
+        }
+    }
+
+    #[test]
+    fn test_decommitment_serde_round_trip() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cols = random_columns(&mut rng, 10, LOG_SIZE_RANGE);
+        let log_sizes = cols.iter().map(|c| c.len().ilog2()).collect_vec();
+        let merkle =
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(cols.iter().collect_vec());
+        let queries = random_queries(&mut rng, N_QUERIES, LOG_SIZE_RANGE);
+        let (values, decommitment) = merkle.decommit(queries.clone(), cols.iter().collect_vec());
+        let verifier = MerkleVerifier::<Sha256MerkleHasher>::new(merkle.root(), log_sizes);
+
+        let json = serde_json::to_string(&(&values, &decommitment)).unwrap();
+        let bincode = bincode::serialize(&(&values, &decommitment)).unwrap();
+        let from_json: (Vec<Vec<BaseField>>, MerkleDecommitment<Sha256MerkleHasher>) =
+            serde_json::from_str(&json).unwrap();
+        let from_bincode: (Vec<Vec<BaseField>>, MerkleDecommitment<Sha256MerkleHasher>) =
+            bincode::deserialize(&bincode).unwrap();
+
+        for (deserialized_values, deserialized_decommitment) in [from_json, from_bincode] {
+            assert_eq!(deserialized_values, values);
+            assert_eq!(
+                deserialized_decommitment.hash_witness,
+                decommitment.hash_witness
+            );
+            assert_eq!(
+                deserialized_decommitment.column_witness,
+                decommitment.column_witness
+            );
+            verifier
+                .verify(
+                    queries.clone(),
+                    deserialized_values,
+                    deserialized_decommitment,
+                )
+                .unwrap();
+        }
+    }
+
+    #[test]
+    fn test_decommitment_size() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cols = random_columns(&mut rng, 10, LOG_SIZE_RANGE);
+        let merkle =
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(cols.iter().collect_vec());
+
+        for _ in 0..10 {
+            let queries = random_queries(&mut rng, N_QUERIES, LOG_SIZE_RANGE);
+
+            let size = merkle.decommitment_size(&queries);
+
+            let (_, decommitment) = merkle.decommit(queries, cols.iter().collect_vec());
+            assert_eq!(size, decommitment.hash_witness.len());
+        }
+        assert_eq!(merkle.decommitment_size(&BTreeMap::new()), 0);
+    }
+
+    #[test]
+    fn test_decommit_streaming() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cols = random_columns(&mut rng, 10, LOG_SIZE_RANGE);
+        let log_sizes = cols.iter().map(|c| c.len().ilog2()).collect_vec();
+        let merkle =
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(cols.iter().collect_vec());
+        let queries = random_queries(&mut rng, N_QUERIES, LOG_SIZE_RANGE);
+
+        let layers = merkle.decommit_streaming(queries.clone(), cols.iter().collect_vec());
+        let (values, decommitment) = MerkleDecommitment::from_layers(layers, &log_sizes);
+
+        let verifier = MerkleVerifier::<Sha256MerkleHasher>::new(merkle.root(), log_sizes);
+        verifier.verify(queries, values, decommitment).unwrap();
+    }
+
+    #[test]
+    fn test_commit_with_progress() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cols = random_columns(&mut rng, 10, LOG_SIZE_RANGE);
+        let max_log_size = cols.iter().map(|c| c.len().ilog2()).max().unwrap();
+        let mut reported_layers = vec![];
+
+        let merkle = MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit_with_progress(
+            cols.iter().collect_vec(),
+            |layer| reported_layers.push(layer),
+        );
+
+        assert_eq!(reported_layers, (0..=max_log_size).collect_vec());
+        assert_eq!(merkle.layers.len(), reported_layers.len());
+    }
+
+    #[test]
+    fn test_prove_inclusion() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let cols = random_columns(&mut rng, 10, LOG_SIZE_RANGE);
+        let log_sizes = cols.iter().map(|c| c.len().ilog2()).collect_vec();
+        let merkle =
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(cols.iter().collect_vec());
+        let verifier = MerkleVerifier::<Sha256MerkleHasher>::new(merkle.root(), log_sizes);
+        let (column_index, row_index) = (3, 5);
+
+        let (value, decommitment) =
+            merkle.prove_inclusion(cols.iter().collect_vec(), column_index, row_index);
+
+        assert_eq!(value, cols[column_index][row_index]);
+        verifier
+            .verify_inclusion(column_index, row_index, value, decommitment.clone())
+            .unwrap();
+        assert_eq!(
+            verifier
+                .verify_inclusion(column_index, row_index, value + value, decommitment)
+                .unwrap_err(),
+            MerkleVerificationError::RootMismatch
+        );
+    }
+
+    #[cfg(feature = "parallel")]
+    #[test]
+    fn test_commit_trees_parallel() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let trees = (1..=3)
+            .map(|n_cols| random_columns(&mut rng, n_cols, LOG_SIZE_RANGE))
+            .collect_vec();
+        let tree_columns = trees
+            .iter()
+            .map(|tree| tree.iter().collect_vec())
+            .collect_vec();
+
+        let committed =
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit_trees_parallel(&tree_columns);
+
+        assert_eq!(committed.len(), trees.len());
+        for ((root, prover), columns) in committed.iter().zip(tree_columns) {
+            let expected = MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(columns);
+            assert_eq!(*root, expected.root());
+            assert_eq!(prover.layers, expected.layers);
+        }
+    }
 }
@@ -1,13 +1,67 @@
+use std::borrow::Cow;
 use std::fmt;
 
 use serde::{Deserialize, Serialize};
 use sha2::Digest;
+use thiserror::Error;
 
 // Wrapper for the sha256 hash type.
 #[repr(align(32))]
 #[derive(Clone, Copy, PartialEq, Default, Eq, Deserialize, Serialize)]
 pub struct Sha256Hash(pub(crate) [u8; 32]);
 
+impl Sha256Hash {
+    /// Parses a hash from its hex encoding, as produced by [Sha256Hash::to_hex].
+    pub fn from_hex(s: &str) -> Result<Self, HashParseError> {
+        if s.len() != 64 {
+            return Err(HashParseError::InvalidLength(s.len()));
+        }
+        let mut bytes = [0u8; 32];
+        hex::decode_to_slice(s, &mut bytes).map_err(|err| match err {
+            hex::FromHexError::InvalidHexCharacter { c, index } => {
+                HashParseError::InvalidHexCharacter { c, index }
+            }
+            // The length was checked above.
+            hex::FromHexError::OddLength | hex::FromHexError::InvalidStringLength => {
+                unreachable!()
+            }
+        })?;
+        Ok(Self(bytes))
+    }
+
+    /// Parses a hash from its 32 bytes, e.g. from untrusted proof bytes.
+    ///
+    /// This is the fallible version of the `From<&[u8]>` and `From<Vec<u8>>` conversions, which
+    /// panic on a wrong length. `TryFrom` can't be implemented alongside them, as it is already
+    /// implemented through `From`.
+    pub fn try_from_bytes(bytes: impl AsRef<[u8]>) -> Result<Self, HashParseError> {
+        let bytes = bytes.as_ref();
+        let bytes = bytes
+            .try_into()
+            .map_err(|_| HashParseError::InvalidByteLength {
+                expected: 32,
+                actual: bytes.len(),
+            })?;
+        Ok(Self(bytes))
+    }
+
+    /// Returns the hex encoding of the hash.
+    pub fn to_hex(&self) -> String {
+        hex::encode(self.0)
+    }
+
+    /// Compares two hashes in constant time, without exiting early on the first differing byte.
+    /// Prefer this over `==` when one of the hashes is controlled by a malicious party.
+    pub fn ct_eq(&self, other: &Self) -> bool {
+        let diff = self
+            .0
+            .iter()
+            .zip(other.0.iter())
+            .fold(0, |acc, (a, b)| acc | (a ^ b));
+        std::hint::black_box(diff) == 0
+    }
+}
+
 impl From<Sha256Hash> for Vec<u8> {
     fn from(value: Sha256Hash) -> Self {
         Vec::from(value.0)
@@ -48,7 +102,7 @@ impl From<Sha256Hash> for [u8; 32] {
 
 impl fmt::Display for Sha256Hash {
     fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
-        f.write_str(&hex::encode(self.0))
+        f.write_str(&self.to_hex())
     }
 }
 
@@ -58,7 +112,36 @@ impl fmt::Debug for Sha256Hash {
     }
 }
 
-impl super::hash::Hash for Sha256Hash {}
+impl super::hash::Hash for Sha256Hash {
+    const BYTE_LEN: usize = 32;
+
+    fn as_bytes(&self) -> Cow<'_, [u8]> {
+        Cow::Borrowed(&self.0)
+    }
+
+    fn ct_eq(&self, other: &Self) -> bool {
+        Sha256Hash::ct_eq(self, other)
+    }
+}
+
+/// Hashes are [Copy], so they can't be wiped on drop: wipe each copy holding a secret-derived
+/// value explicitly.
+#[cfg(feature = "zeroize")]
+impl zeroize::Zeroize for Sha256Hash {
+    fn zeroize(&mut self) {
+        self.0.zeroize();
+    }
+}
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum HashParseError {
+    #[error("Expected 64 hex characters, got {0}.")]
+    InvalidLength(usize),
+    #[error("Invalid hex character {c:?} at index {index}.")]
+    InvalidHexCharacter { c: char, index: usize },
+    #[error("Expected {expected} bytes, got {actual}.")]
+    InvalidByteLength { expected: usize, actual: usize },
+}
 
 // Wrapper for the sha256 Hashing functionalities.
 #[derive(Clone, Debug, Default)]
@@ -104,7 +187,10 @@ impl Sha256Hasher {
 
 #[cfg(test)]
 mod tests {
-    use super::Sha256Hasher;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::{HashParseError, Sha256Hash, Sha256Hasher};
     use crate::core::vcs::sha256_hash;
 
     #[test]
@@ -127,4 +213,70 @@ mod tests {
         assert_eq!(hash.to_string(), Sha256Hasher::hash(b"ab").to_string());
         assert_eq!(hash_empty.to_string(), Sha256Hasher::hash(b"").to_string());
     }
+
+    #[test]
+    fn from_hex_test() {
+        let hash = Sha256Hasher::hash(b"a");
+
+        let parsed = Sha256Hash::from_hex(&hash.to_hex()).unwrap();
+
+        assert_eq!(parsed, hash);
+    }
+
+    #[test]
+    fn from_hex_wrong_length_test() {
+        let hex = Sha256Hasher::hash(b"a").to_hex();
+
+        assert_eq!(
+            Sha256Hash::from_hex(&hex[..62]).unwrap_err(),
+            HashParseError::InvalidLength(62)
+        );
+    }
+
+    #[test]
+    fn from_hex_invalid_character_test() {
+        let hex = format!("{}zz", &Sha256Hasher::hash(b"a").to_hex()[..62]);
+
+        assert_eq!(
+            Sha256Hash::from_hex(&hex).unwrap_err(),
+            HashParseError::InvalidHexCharacter { c: 'z', index: 62 }
+        );
+    }
+
+    #[test]
+    fn ct_eq_test() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        for _ in 0..100 {
+            let hash = Sha256Hash(rng.gen());
+            let mut other = hash;
+            // Flip a random byte half of the time.
+            if rng.gen() {
+                other.0[rng.gen_range(0..32)] ^= rng.gen_range(1..=u8::MAX);
+            }
+
+            assert_eq!(hash.ct_eq(&other), hash == other);
+            assert!(hash.ct_eq(&hash));
+        }
+    }
+
+    #[test]
+    fn try_from_bytes_test() {
+        let hash = Sha256Hasher::hash(b"a");
+
+        assert_eq!(Sha256Hash::try_from_bytes(hash.as_ref()), Ok(hash));
+        assert_eq!(Sha256Hash::try_from_bytes(Vec::from(hash)), Ok(hash));
+    }
+
+    #[test]
+    fn try_from_bytes_too_short_test() {
+        let hash = Sha256Hasher::hash(b"a");
+
+        assert_eq!(
+            Sha256Hash::try_from_bytes(&hash.as_ref()[..31]).unwrap_err(),
+            HashParseError::InvalidByteLength {
+                expected: 32,
+                actual: 31
+            }
+        );
+    }
 }
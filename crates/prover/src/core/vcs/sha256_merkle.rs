@@ -1,77 +1,236 @@
 use serde::{Deserialize, Serialize};
-use sha2::Digest;
-
-use crate::core::channel::{MerkleChannel, Sha256Channel};
-use crate::core::fields::m31::BaseField;
-use crate::core::utils::bws_num_to_bytes;
+use thiserror::Error;
+
+use crate::core::channel::{
+    add_hashes, MerkleChannel, Sha256BatchedChannel, Sha256Channel, Sha256StreamingChannel,
+};
+use crate::core::fields::m31::{BaseField, P};
+use crate::core::fields::qm31::SecureField;
+use crate::core::fields::secure_column::SECURE_EXTENSION_DEGREE;
+use crate::core::fri::FriLayerProof;
 use crate::core::vcs::ops::MerkleHasher;
-use crate::core::vcs::sha256_hash::{Sha256Hash, Sha256Hasher};
+use crate::core::vcs::prover::MerkleDecommitment;
+use crate::core::vcs::sha256_hash::{HashParseError, Sha256Hash, Sha256Hasher};
+use crate::core::vcs::utils::hash_node_bytes;
+use crate::core::vcs::verifier::MerkleVerifier;
 
 #[derive(Copy, Clone, Debug, PartialEq, Eq, Default, Deserialize, Serialize)]
 pub struct Sha256MerkleHasher;
 impl MerkleHasher for Sha256MerkleHasher {
     type Hash = Sha256Hash;
+    type Tag = ();
 
     fn hash_node(
         children_hashes: Option<(Self::Hash, Self::Hash)>,
         column_values: &[BaseField],
     ) -> Self::Hash {
-        // There are three possibilities:
-        // - children only
-        // - children and column elements
-        // - column elements only
-        //
-        // They are handled as follows.
-        // - left | right (32 bytes | 32 bytes)
-        // - left | [column hash] | right (32 bytes | 32 bytes | 32 bytes)
-        // - [column hash] (32 bytes)
-
-        let column_hash = if column_values.is_empty() {
-            None
-        } else {
-            let len = column_values.len();
-
-            let mut hash = [0u8; 32];
-            let mut sha256 = sha2::Sha256::new();
-            Digest::update(&mut sha256, bws_num_to_bytes(column_values[len - 1]));
-            hash.copy_from_slice(sha256.finalize().as_slice());
-
-            for i in 1..len {
-                let mut sha256 = sha2::Sha256::new();
-                Digest::update(&mut sha256, bws_num_to_bytes(column_values[len - 1 - i]));
-                Digest::update(&mut sha256, hash);
-                hash.copy_from_slice(sha256.finalize().as_slice());
-            }
+        Sha256Hash(hash_node_bytes::<sha2::Sha256>(
+            children_hashes.map(|(left, right)| (left.0, right.0)),
+            column_values,
+        ))
+    }
+}
 
-            Some(hash)
-        };
+impl MerkleDecommitment<Sha256MerkleHasher> {
+    /// Serializes the decommitment to a compact binary form:
+    ///   n_hashes (u32) | hash_witness (32 bytes each) | n_values (u32) | column_witness (u32 each)
+    /// All integers are little-endian.
+    pub fn serialize_compact(&self) -> Vec<u8> {
+        let mut bytes =
+            Vec::with_capacity(8 + 32 * self.hash_witness.len() + 4 * self.column_witness.len());
+        bytes.extend((self.hash_witness.len() as u32).to_le_bytes());
+        for hash in &self.hash_witness {
+            bytes.extend(hash.as_ref());
+        }
+        bytes.extend((self.column_witness.len() as u32).to_le_bytes());
+        for value in &self.column_witness {
+            bytes.extend(value.0.to_le_bytes());
+        }
+        bytes
+    }
 
-        let mut sha256 = sha2::Sha256::new();
-        match (children_hashes, column_hash) {
-            (Some(children_hashes), Some(column_hash)) => {
-                Digest::update(&mut sha256, children_hashes.0);
-                Digest::update(&mut sha256, column_hash);
-                Digest::update(&mut sha256, children_hashes.1);
-            }
-            (Some(children_hashes), None) => {
-                Digest::update(&mut sha256, children_hashes.0);
-                Digest::update(&mut sha256, children_hashes.1);
-            }
-            (None, Some(column_hash)) => {
-                Digest::update(&mut sha256, column_hash);
-            }
-            (None, None) => {
-                // do nothing if both are None
+    /// Deserializes a decommitment serialized with [Self::serialize_compact].
+    pub fn deserialize_compact(bytes: &[u8]) -> Result<Self, CompactDeserializationError> {
+        let mut reader = CompactReader(bytes);
+        let decommitment = Self::read_compact(&mut reader)?;
+        reader.finish()?;
+        Ok(decommitment)
+    }
+
+    /// Serializes the decommitment with a header, so that it can be told apart from other data:
+    ///   magic (4 bytes) | version (u8) | [Self::serialize_compact]
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut bytes = Vec::from(DECOMMITMENT_MAGIC);
+        bytes.push(SERIALIZATION_VERSION);
+        bytes.extend(self.serialize_compact());
+        bytes
+    }
+
+    /// Deserializes a decommitment serialized with [Self::to_bytes].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CompactDeserializationError> {
+        let mut reader = CompactReader(bytes);
+        reader.read_header(DECOMMITMENT_MAGIC)?;
+        let decommitment = Self::read_compact(&mut reader)?;
+        reader.finish()?;
+        Ok(decommitment)
+    }
+
+    fn read_compact(reader: &mut CompactReader<'_>) -> Result<Self, CompactDeserializationError> {
+        let n_hashes = reader.read_u32()? as usize;
+        let hash_witness = (0..n_hashes)
+            .map(|_| Ok(Sha256Hash::from(reader.read(32)?)))
+            .collect::<Result<_, _>>()?;
+
+        let n_values = reader.read_u32()? as usize;
+        let column_witness = (0..n_values)
+            .map(|_| reader.read_base_field())
+            .collect::<Result<_, _>>()?;
+
+        Ok(Self {
+            hash_witness,
+            column_witness,
+        })
+    }
+}
+
+impl MerkleVerifier<Sha256MerkleHasher> {
+    /// Creates a verifier for a root given as 64 hex characters, e.g. on the command line.
+    pub fn from_hex_root(root: &str, column_log_sizes: Vec<u32>) -> Result<Self, HashParseError> {
+        Ok(Self::new(Sha256Hash::from_hex(root)?, column_log_sizes))
+    }
+
+    /// Creates a verifier for a root given as 32 bytes.
+    pub fn from_bytes_root(
+        root: &[u8],
+        column_log_sizes: Vec<u32>,
+    ) -> Result<Self, HashParseError> {
+        Ok(Self::new(
+            Sha256Hash::try_from_bytes(root)?,
+            column_log_sizes,
+        ))
+    }
+}
+
+impl FriLayerProof<Sha256MerkleHasher> {
+    /// Serializes the layer proof with a header, like [MerkleDecommitment::to_bytes]:
+    ///   magic (4 bytes) | version (u8) | commitment (32 bytes) | n_evals (u32) |
+    ///   evals_subset (4 u32 each) | decommitment length (u32) | [MerkleDecommitment::to_bytes]
+    /// All integers are little-endian.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let decommitment = self.decommitment.to_bytes();
+        let mut bytes = Vec::from(FRI_LAYER_MAGIC);
+        bytes.push(SERIALIZATION_VERSION);
+        bytes.extend(self.commitment.as_ref());
+        bytes.extend((self.evals_subset.len() as u32).to_le_bytes());
+        for eval in &self.evals_subset {
+            for value in eval.to_m31_array() {
+                bytes.extend(value.0.to_le_bytes());
             }
         }
+        bytes.extend((decommitment.len() as u32).to_le_bytes());
+        bytes.extend(decommitment);
+        bytes
+    }
+
+    /// Deserializes a layer proof serialized with [Self::to_bytes].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Self, CompactDeserializationError> {
+        let mut reader = CompactReader(bytes);
+        reader.read_header(FRI_LAYER_MAGIC)?;
+        let commitment = Sha256Hash::from(reader.read(32)?);
+
+        let n_evals = reader.read_u32()? as usize;
+        let evals_subset = (0..n_evals)
+            .map(|_| {
+                let mut values = [BaseField::default(); SECURE_EXTENSION_DEGREE];
+                for value in &mut values {
+                    *value = reader.read_base_field()?;
+                }
+                Ok(SecureField::from_m31_array(values))
+            })
+            .collect::<Result<_, _>>()?;
 
-        let mut hash_result = [0u8; 32];
-        hash_result.copy_from_slice(sha256.finalize().as_slice());
+        let decommitment_len = reader.read_u32()? as usize;
+        let decommitment = MerkleDecommitment::from_bytes(reader.read(decommitment_len)?)?;
+        reader.finish()?;
 
-        hash_result.to_vec().into()
+        Ok(Self {
+            evals_subset,
+            decommitment,
+            commitment,
+        })
     }
 }
 
+/// Identifies bytes serialized with [MerkleDecommitment::to_bytes].
+const DECOMMITMENT_MAGIC: [u8; 4] = *b"SWMD";
+/// Identifies bytes serialized with [FriLayerProof::to_bytes].
+const FRI_LAYER_MAGIC: [u8; 4] = *b"SWFL";
+pub(super) const SERIALIZATION_VERSION: u8 = 1;
+
+/// Reads consecutive chunks of a byte slice.
+pub(super) struct CompactReader<'a>(pub(super) &'a [u8]);
+
+impl<'a> CompactReader<'a> {
+    fn read(&mut self, len: usize) -> Result<&'a [u8], CompactDeserializationError> {
+        if self.0.len() < len {
+            return Err(CompactDeserializationError::UnexpectedEnd);
+        }
+        let (chunk, rest) = self.0.split_at(len);
+        self.0 = rest;
+        Ok(chunk)
+    }
+
+    pub(super) fn read_u32(&mut self) -> Result<u32, CompactDeserializationError> {
+        Ok(u32::from_le_bytes(self.read(4)?.try_into().unwrap()))
+    }
+
+    pub(super) fn read_base_field(&mut self) -> Result<BaseField, CompactDeserializationError> {
+        match self.read_u32()? {
+            value if value < P => Ok(BaseField::from_u32_unchecked(value)),
+            value => Err(CompactDeserializationError::InvalidFieldElement(value)),
+        }
+    }
+
+    pub(super) fn read_header(
+        &mut self,
+        magic: [u8; 4],
+    ) -> Result<(), CompactDeserializationError> {
+        let actual_magic: [u8; 4] = self.read(4)?.try_into().unwrap();
+        if actual_magic != magic {
+            return Err(CompactDeserializationError::InvalidMagic {
+                expected: magic,
+                actual: actual_magic,
+            });
+        }
+        match self.read(1)?[0] {
+            SERIALIZATION_VERSION => Ok(()),
+            version => Err(CompactDeserializationError::UnsupportedVersion(version)),
+        }
+    }
+
+    /// Checks that all the bytes were read.
+    pub(super) fn finish(&self) -> Result<(), CompactDeserializationError> {
+        match self.0.len() {
+            0 => Ok(()),
+            len => Err(CompactDeserializationError::TrailingBytes(len)),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+pub enum CompactDeserializationError {
+    #[error("Unexpected end of input.")]
+    UnexpectedEnd,
+    #[error("{0} unexpected trailing bytes.")]
+    TrailingBytes(usize),
+    #[error("{0} is not a valid field element.")]
+    InvalidFieldElement(u32),
+    #[error("Expected magic bytes {expected:?}, got {actual:?}.")]
+    InvalidMagic { expected: [u8; 4], actual: [u8; 4] },
+    #[error("Unsupported serialization version {0}.")]
+    UnsupportedVersion(u8),
+}
+
 #[derive(Default)]
 pub struct Sha256MerkleChannel;
 
@@ -80,10 +239,37 @@ impl MerkleChannel for Sha256MerkleChannel {
     type H = Sha256MerkleHasher;
 
     fn mix_root(channel: &mut Self::C, root: <Self::H as MerkleHasher>::Hash) {
+        add_hashes(1);
         channel.update_digest(Sha256Hasher::concat_and_hash(&root, &channel.digest()));
     }
 }
 
+/// Like [Sha256MerkleChannel], with a [Sha256StreamingChannel] transcript.
+#[derive(Default)]
+pub struct Sha256StreamingMerkleChannel;
+
+impl MerkleChannel for Sha256StreamingMerkleChannel {
+    type C = Sha256StreamingChannel;
+    type H = Sha256MerkleHasher;
+
+    fn mix_root(channel: &mut Self::C, root: <Self::H as MerkleHasher>::Hash) {
+        Sha256MerkleChannel::mix_root(&mut channel.0, root);
+    }
+}
+
+/// Like [Sha256MerkleChannel], with a [Sha256BatchedChannel] transcript.
+#[derive(Default)]
+pub struct Sha256BatchedMerkleChannel;
+
+impl MerkleChannel for Sha256BatchedMerkleChannel {
+    type C = Sha256BatchedChannel;
+    type H = Sha256MerkleHasher;
+
+    fn mix_root(channel: &mut Self::C, root: <Self::H as MerkleHasher>::Hash) {
+        Sha256MerkleChannel::mix_root(&mut channel.0, root);
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use std::collections::BTreeMap;
@@ -94,11 +280,13 @@ mod tests {
     use rand::{Rng, SeedableRng};
 
     use crate::core::backend::CpuBackend;
-    use crate::core::fields::m31::BaseField;
+    use crate::core::fields::m31::{BaseField, P};
+    use crate::core::fri::FriLayerProof;
     use crate::core::vcs::prover::{MerkleDecommitment, MerkleProver};
-    use crate::core::vcs::sha256_hash::Sha256Hash;
-    use crate::core::vcs::sha256_merkle::Sha256MerkleHasher;
+    use crate::core::vcs::sha256_hash::{HashParseError, Sha256Hash};
+    use crate::core::vcs::sha256_merkle::{CompactDeserializationError, Sha256MerkleHasher};
     use crate::core::vcs::verifier::{MerkleVerificationError, MerkleVerifier};
+    use crate::qm31;
 
     type TestData = (
         BTreeMap<u32, Vec<usize>>,
@@ -145,6 +333,37 @@ mod tests {
         (queries, decommitment, values, verifier)
     }
 
+    #[test]
+    fn test_verifier_from_serialized_root() {
+        let (queries, decommitment, values, verifier) = prepare_merkle();
+        let log_sizes = verifier.column_log_sizes.clone();
+        let hex_root = verifier.root.to_hex();
+
+        let from_hex = MerkleVerifier::from_hex_root(&hex_root, log_sizes.clone()).unwrap();
+        let from_bytes =
+            MerkleVerifier::from_bytes_root(verifier.root.as_ref(), log_sizes.clone()).unwrap();
+        from_hex
+            .verify(queries.clone(), values.clone(), decommitment.clone())
+            .unwrap();
+        from_bytes.verify(queries, values, decommitment).unwrap();
+
+        assert_eq!(
+            MerkleVerifier::from_hex_root(&hex_root[1..], log_sizes.clone()).err(),
+            Some(HashParseError::InvalidLength(63))
+        );
+        assert_eq!(
+            MerkleVerifier::from_hex_root(&format!("x{}", &hex_root[1..]), log_sizes.clone()).err(),
+            Some(HashParseError::InvalidHexCharacter { c: 'x', index: 0 })
+        );
+        assert_eq!(
+            MerkleVerifier::from_bytes_root(&verifier.root.as_ref()[..31], log_sizes).err(),
+            Some(HashParseError::InvalidByteLength {
+                expected: 32,
+                actual: 31
+            })
+        );
+    }
+
     #[test]
     fn test_merkle_success() {
         let (queries, decommitment, values, verifier) = prepare_merkle();
@@ -152,6 +371,26 @@ mod tests {
         verifier.verify(queries, values, decommitment).unwrap();
     }
 
+    #[test]
+    fn test_merkle_verify_against_root() {
+        let (queries, decommitment, values, verifier) = prepare_merkle();
+        let root = verifier.root;
+        let stale_verifier = MerkleVerifier {
+            root: Sha256Hash::default(),
+            column_log_sizes: verifier.column_log_sizes.clone(),
+        };
+
+        stale_verifier
+            .verify_against_root(root, queries.clone(), values.clone(), decommitment.clone())
+            .unwrap();
+        assert_eq!(
+            verifier
+                .verify_against_root(Sha256Hash::default(), queries, values, decommitment)
+                .unwrap_err(),
+            MerkleVerificationError::RootMismatch
+        );
+    }
+
     #[test]
     fn test_merkle_invalid_witness() {
         let (queries, mut decommitment, values, verifier) = prepare_merkle();
@@ -217,4 +456,142 @@ mod tests {
             MerkleVerificationError::WitnessTooLong
         );
     }
+
+    #[test]
+    fn test_merkle_detailed_invalid_value() {
+        let (queries, decommitment, mut values, verifier) = prepare_merkle();
+        values[3][6] = BaseField::zero();
+
+        let error = verifier
+            .verify_detailed(queries, values, decommitment)
+            .unwrap_err();
+
+        assert_eq!(error.error, MerkleVerificationError::RootMismatch);
+        assert_eq!((error.layer_log_size, error.node_index), (0, Some(0)));
+        let hash_mismatch = error.hash_mismatch.unwrap();
+        assert_eq!(hash_mismatch.expected, verifier.root);
+        assert_ne!(hash_mismatch.actual, verifier.root);
+    }
+
+    #[test]
+    fn test_merkle_detailed_column_values_too_short() {
+        let (queries, decommitment, mut values, verifier) = prepare_merkle();
+        values[3].pop();
+        let log_size = verifier.column_log_sizes[3];
+
+        let error = verifier
+            .verify_detailed(queries.clone(), values, decommitment)
+            .unwrap_err();
+
+        assert_eq!(error.error, MerkleVerificationError::ColumnValuesTooShort);
+        assert_eq!(error.layer_log_size, log_size);
+        // The values of each layer are read in query order, so the last query is missing.
+        assert_eq!(error.node_index, queries[&log_size].last().copied());
+        assert_eq!(error.hash_mismatch, None);
+    }
+
+    #[test]
+    fn test_merkle_compact_serialization() {
+        let (queries, decommitment, values, verifier) = prepare_merkle();
+
+        let bytes = decommitment.serialize_compact();
+        let deserialized = MerkleDecommitment::deserialize_compact(&bytes).unwrap();
+
+        assert_eq!(deserialized, decommitment);
+        verifier.verify(queries, values, deserialized).unwrap();
+    }
+
+    #[test]
+    fn test_merkle_compact_deserialization_errors() {
+        let (_, decommitment, ..) = prepare_merkle();
+        let bytes = decommitment.serialize_compact();
+
+        assert_eq!(
+            MerkleDecommitment::<Sha256MerkleHasher>::deserialize_compact(
+                &bytes[..bytes.len() - 1]
+            )
+            .unwrap_err(),
+            CompactDeserializationError::UnexpectedEnd
+        );
+        assert_eq!(
+            MerkleDecommitment::<Sha256MerkleHasher>::deserialize_compact(
+                &[&bytes[..], &[0]].concat()
+            )
+            .unwrap_err(),
+            CompactDeserializationError::TrailingBytes(1)
+        );
+    }
+
+    #[test]
+    fn test_merkle_framed_serialization() {
+        let (queries, decommitment, values, verifier) = prepare_merkle();
+
+        let bytes = decommitment.to_bytes();
+        let deserialized = MerkleDecommitment::from_bytes(&bytes).unwrap();
+
+        assert_eq!(deserialized, decommitment);
+        verifier.verify(queries, values, deserialized).unwrap();
+    }
+
+    #[test]
+    fn test_merkle_framed_deserialization_errors() {
+        let (_, decommitment, ..) = prepare_merkle();
+        let bytes = decommitment.to_bytes();
+        let mut wrong_version = bytes.clone();
+        wrong_version[4] = 2;
+
+        for len in 0..bytes.len() {
+            assert_eq!(
+                MerkleDecommitment::<Sha256MerkleHasher>::from_bytes(&bytes[..len]).unwrap_err(),
+                CompactDeserializationError::UnexpectedEnd
+            );
+        }
+        assert_eq!(
+            MerkleDecommitment::<Sha256MerkleHasher>::from_bytes(&decommitment.serialize_compact())
+                .unwrap_err(),
+            CompactDeserializationError::InvalidMagic {
+                expected: *b"SWMD",
+                actual: bytes[5..9].try_into().unwrap(),
+            }
+        );
+        assert_eq!(
+            MerkleDecommitment::<Sha256MerkleHasher>::from_bytes(&wrong_version).unwrap_err(),
+            CompactDeserializationError::UnsupportedVersion(2)
+        );
+    }
+
+    #[test]
+    fn test_fri_layer_proof_serialization() {
+        let (_, decommitment, _, verifier) = prepare_merkle();
+        let proof = FriLayerProof {
+            evals_subset: vec![qm31!(1, 2, 3, 4), qm31!(5, 6, 7, P - 1)],
+            decommitment,
+            commitment: verifier.root,
+        };
+        let bytes = proof.to_bytes();
+
+        let deserialized = FriLayerProof::from_bytes(&bytes).unwrap();
+
+        assert_eq!(deserialized.evals_subset, proof.evals_subset);
+        assert_eq!(deserialized.decommitment, proof.decommitment);
+        assert_eq!(deserialized.commitment, proof.commitment);
+        for len in 0..bytes.len() {
+            assert!(FriLayerProof::<Sha256MerkleHasher>::from_bytes(&bytes[..len]).is_err());
+        }
+    }
+
+    #[test]
+    fn test_merkle_root_regression() {
+        let cols = (2..5)
+            .map(|log_size| (0..1 << log_size).map(BaseField::from).collect_vec())
+            .collect_vec();
+
+        let merkle =
+            MerkleProver::<CpuBackend, Sha256MerkleHasher>::commit(cols.iter().collect_vec());
+
+        assert_eq!(
+            merkle.root().to_hex(),
+            "b7504a8131f1c0e6c2cc9d2bf5b570c0fe74bfa87769ada8ba32b12f8659e485"
+        );
+    }
 }
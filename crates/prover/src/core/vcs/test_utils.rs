@@ -1,4 +1,5 @@
 use std::collections::BTreeMap;
+use std::ops::Range;
 
 use itertools::Itertools;
 use rand::rngs::SmallRng;
@@ -29,34 +30,56 @@ where
     let log_size_range = 3..5;
 
     let mut rng = SmallRng::seed_from_u64(0);
-    let log_sizes = (0..N_COLS)
+    let cols = random_columns(&mut rng, N_COLS, log_size_range.clone());
+    let log_sizes = cols.iter().map(|col| col.len().ilog2()).collect_vec();
+    let merkle = MerkleProver::<CpuBackend, H>::commit(cols.iter().collect_vec());
+    let queries = random_queries(&mut rng, N_QUERIES, log_size_range);
+
+    let (values, decommitment) = merkle.decommit(queries.clone(), cols.iter().collect_vec());
+
+    let verifier = MerkleVerifier {
+        root: merkle.root(),
+        column_log_sizes: log_sizes,
+    };
+    (queries, decommitment, values, verifier)
+}
+
+/// Generates `n_cols` columns of random values, with random log sizes in `log_size_range`.
+#[allow(dead_code)]
+pub fn random_columns(
+    rng: &mut SmallRng,
+    n_cols: usize,
+    log_size_range: Range<u32>,
+) -> Vec<Vec<BaseField>> {
+    let log_sizes = (0..n_cols)
         .map(|_| rng.gen_range(log_size_range.clone()))
         .collect_vec();
-    let cols = log_sizes
+    log_sizes
         .iter()
         .map(|&log_size| {
             (0..(1 << log_size))
                 .map(|_| BaseField::from(rng.gen_range(0..(1 << 30))))
                 .collect_vec()
         })
-        .collect_vec();
-    let merkle = MerkleProver::<CpuBackend, H>::commit(cols.iter().collect_vec());
+        .collect_vec()
+}
 
-    let mut queries = BTreeMap::<u32, Vec<usize>>::new();
+/// Generates up to `n_queries` sorted and deduplicated random queries for each log size in
+/// `log_size_range`.
+#[allow(dead_code)]
+pub fn random_queries(
+    rng: &mut SmallRng,
+    n_queries: usize,
+    log_size_range: Range<u32>,
+) -> BTreeMap<u32, Vec<usize>> {
+    let mut queries = BTreeMap::new();
     for log_size in log_size_range.rev() {
-        let layer_queries = (0..N_QUERIES)
+        let layer_queries = (0..n_queries)
             .map(|_| rng.gen_range(0..(1 << log_size)))
             .sorted()
             .dedup()
             .collect_vec();
         queries.insert(log_size, layer_queries);
     }
-
-    let (values, decommitment) = merkle.decommit(queries.clone(), cols.iter().collect_vec());
-
-    let verifier = MerkleVerifier {
-        root: merkle.root(),
-        column_log_sizes: log_sizes,
-    };
-    (queries, decommitment, values, verifier)
+    queries
 }
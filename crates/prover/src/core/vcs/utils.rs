@@ -1,5 +1,11 @@
 use std::iter::Peekable;
 
+use sha2::Digest;
+
+use super::sha256_merkle::{CompactDeserializationError, CompactReader, SERIALIZATION_VERSION};
+use crate::core::fields::m31::BaseField;
+use crate::core::utils::bws_num_to_bytes;
+
 /// Fetches the next node that needs to be decommited in the current Merkle layer.
 pub fn next_decommitment_node(
     prev_queries: &mut Peekable<impl Iterator<Item = usize>>,
@@ -18,3 +24,127 @@ pub fn option_flatten_peekable<'a, I: IntoIterator<Item = &'a usize>>(
 ) -> Peekable<std::iter::Copied<std::iter::Flatten<<Option<I> as IntoIterator>::IntoIter>>> {
     a.into_iter().flatten().copied().peekable()
 }
+
+/// Hashes a Merkle node with a 32-byte digest `D`. See [super::ops::MerkleHasher].
+///
+/// There are three possibilities:
+/// - children only
+/// - children and column elements
+/// - column elements only
+///
+/// They are handled as follows.
+/// - left | right (32 bytes | 32 bytes)
+/// - left | [column hash] | right (32 bytes | 32 bytes | 32 bytes)
+/// - [column hash] (32 bytes)
+///
+/// The column hash chains the column values from last to first:
+/// `D(bytes(v0) | D(bytes(v1) | ... D(bytes(v_last))))`, where `bytes` is [bws_num_to_bytes].
+pub fn hash_node_bytes<D: Digest>(
+    children_hashes: Option<([u8; 32], [u8; 32])>,
+    column_values: &[BaseField],
+) -> [u8; 32] {
+    let column_hash = column_values.split_last().map(|(last, rest)| {
+        let mut hash = [0u8; 32];
+        hash.copy_from_slice(&D::digest(bws_num_to_bytes(*last)));
+        for value in rest.iter().rev() {
+            let mut hasher = D::new();
+            hasher.update(bws_num_to_bytes(*value));
+            hasher.update(hash);
+            hash.copy_from_slice(&hasher.finalize());
+        }
+        hash
+    });
+
+    let mut hasher = D::new();
+    match (children_hashes, column_hash) {
+        (Some((left, right)), Some(column_hash)) => {
+            hasher.update(left);
+            hasher.update(column_hash);
+            hasher.update(right);
+        }
+        (Some((left, right)), None) => {
+            hasher.update(left);
+            hasher.update(right);
+        }
+        (None, Some(column_hash)) => {
+            hasher.update(column_hash);
+        }
+        (None, None) => {
+            // do nothing if both are None
+        }
+    }
+
+    let mut hash_result = [0u8; 32];
+    hash_result.copy_from_slice(&hasher.finalize());
+    hash_result
+}
+
+/// Serializes columns, e.g. the committed columns of a [super::prover::MerkleProver], to be
+/// decommitted later. The format is framed like [super::prover::MerkleDecommitment::to_bytes], and
+/// doesn't depend on the Merkle hasher:
+///   magic (4 bytes) | version (u8) | n_columns (u32) | for each column: len (u32) | values (u32)
+/// All integers are little-endian.
+pub fn serialize_columns(columns: &[&Vec<BaseField>]) -> Vec<u8> {
+    let n_values = columns.iter().map(|column| column.len()).sum::<usize>();
+    let mut bytes = Vec::with_capacity(9 + 4 * columns.len() + 4 * n_values);
+    bytes.extend(COLUMNS_MAGIC);
+    bytes.push(SERIALIZATION_VERSION);
+    bytes.extend((columns.len() as u32).to_le_bytes());
+    for column in columns {
+        bytes.extend((column.len() as u32).to_le_bytes());
+        for value in column.iter() {
+            bytes.extend(value.0.to_le_bytes());
+        }
+    }
+    bytes
+}
+
+/// Deserializes columns serialized with [serialize_columns].
+pub fn deserialize_columns(
+    bytes: &[u8],
+) -> Result<Vec<Vec<BaseField>>, CompactDeserializationError> {
+    let mut reader = CompactReader(bytes);
+    reader.read_header(COLUMNS_MAGIC)?;
+    let n_columns = reader.read_u32()?;
+    let columns = (0..n_columns)
+        .map(|_| {
+            let len = reader.read_u32()?;
+            (0..len).map(|_| reader.read_base_field()).collect()
+        })
+        .collect::<Result<_, _>>()?;
+    reader.finish()?;
+    Ok(columns)
+}
+
+/// Identifies bytes serialized with [serialize_columns].
+const COLUMNS_MAGIC: [u8; 4] = *b"SWCL";
+
+#[cfg(test)]
+mod tests {
+    use itertools::Itertools;
+    use rand::rngs::SmallRng;
+    use rand::{Rng, SeedableRng};
+
+    use super::{deserialize_columns, serialize_columns};
+    use crate::core::vcs::sha256_merkle::CompactDeserializationError;
+
+    #[test]
+    fn test_columns_serialization() {
+        let mut rng = SmallRng::seed_from_u64(0);
+        let columns = [5, 0, 3, 3, 8]
+            .map(|log_size| (0..1 << log_size).map(|_| rng.gen()).collect_vec())
+            .to_vec();
+        let bytes = serialize_columns(&columns.iter().collect_vec());
+
+        let deserialized = deserialize_columns(&bytes).unwrap();
+
+        assert_eq!(deserialized, columns);
+        assert!(deserialize_columns(&serialize_columns(&[]))
+            .unwrap()
+            .is_empty());
+        assert_eq!(
+            deserialize_columns(&bytes[..bytes.len() - 1]).unwrap_err(),
+            CompactDeserializationError::UnexpectedEnd
+        );
+    }
+}
@@ -4,10 +4,12 @@ use std::collections::BTreeMap;
 use itertools::Itertools;
 use thiserror::Error;
 
+use super::hash::Hash;
 use super::ops::MerkleHasher;
-use super::prover::MerkleDecommitment;
+use super::prover::{DecommitmentWithLayout, MerkleDecommitment};
 use super::utils::{next_decommitment_node, option_flatten_peekable};
 use crate::core::fields::m31::BaseField;
+use crate::core::poly::circle::MAX_CIRCLE_DOMAIN_LOG_SIZE;
 use crate::core::utils::PeekableExt;
 use crate::core::ColumnVec;
 
@@ -23,6 +25,31 @@ impl<H: MerkleHasher> MerkleVerifier<H> {
             column_log_sizes,
         }
     }
+
+    /// Creates a verifier for the tree with root `root`, learning the log sizes of its columns
+    /// from the layout attached to `decommitment`, instead of knowing them upfront. The layout
+    /// isn't trusted: it only determines how the root is recomputed, so a wrong layout fails
+    /// verification.
+    ///
+    /// # Errors
+    ///
+    /// Returns [MerkleVerificationError::InconsistentLayout] if the layout is empty or a log size
+    /// is larger than any committed column can be.
+    pub fn from_decommitment(
+        root: H::Hash,
+        decommitment: &DecommitmentWithLayout<H>,
+    ) -> Result<Self, MerkleVerificationError> {
+        let column_log_sizes = &decommitment.column_log_sizes;
+        if column_log_sizes.is_empty()
+            || column_log_sizes
+                .iter()
+                .any(|&log_size| log_size > MAX_CIRCLE_DOMAIN_LOG_SIZE)
+        {
+            return Err(MerkleVerificationError::InconsistentLayout);
+        }
+        Ok(Self::new(root, column_log_sizes.clone()))
+    }
+
     /// Verifies the decommitment of the columns.
     ///
     /// # Arguments
@@ -57,6 +84,87 @@ impl<H: MerkleHasher> MerkleVerifier<H> {
         queried_values: ColumnVec<Vec<BaseField>>,
         decommitment: MerkleDecommitment<H>,
     ) -> Result<(), MerkleVerificationError> {
+        self.verify_detailed(queries_per_log_size, queried_values, decommitment)
+            .map_err(|detailed_error| detailed_error.error)
+    }
+
+    /// Verifies a decommitment with an attached column layout, like [Self::verify].
+    ///
+    /// # Errors
+    ///
+    /// Returns [MerkleVerificationError::InconsistentLayout] if the attached layout differs from
+    /// the verifier's. Otherwise, see [Self::verify].
+    pub fn verify_with_layout(
+        &self,
+        queries_per_log_size: BTreeMap<u32, Vec<usize>>,
+        queried_values: ColumnVec<Vec<BaseField>>,
+        decommitment: DecommitmentWithLayout<H>,
+    ) -> Result<(), MerkleVerificationError> {
+        if decommitment.column_log_sizes != self.column_log_sizes {
+            return Err(MerkleVerificationError::InconsistentLayout);
+        }
+        self.verify(
+            queries_per_log_size,
+            queried_values,
+            decommitment.decommitment,
+        )
+    }
+
+    /// Verifies the decommitment of the columns, like [Self::verify], reporting where verification
+    /// failed.
+    ///
+    /// The verifier only knows the root, so a wrong hash or value can only be detected at the
+    /// root, and is reported as a [MerkleVerificationError::RootMismatch] with the expected and
+    /// computed roots. Malformed decommitments are reported at the node where they were detected.
+    ///
+    /// # Errors
+    ///
+    /// See [Self::verify].
+    pub fn verify_detailed(
+        &self,
+        queries_per_log_size: BTreeMap<u32, Vec<usize>>,
+        queried_values: ColumnVec<Vec<BaseField>>,
+        decommitment: MerkleDecommitment<H>,
+    ) -> Result<(), DetailedMerkleError<H::Hash>> {
+        self.verify_detailed_against_root(
+            self.root,
+            queries_per_log_size,
+            queried_values,
+            decommitment,
+        )
+    }
+
+    /// Verifies the decommitment of the columns, like [Self::verify], against `expected_root`
+    /// instead of the verifier's root, e.g. when the root is received separately from the column
+    /// layout.
+    ///
+    /// # Errors
+    ///
+    /// Returns [MerkleVerificationError::RootMismatch] if the computed root is not
+    /// `expected_root`. Otherwise, see [Self::verify].
+    pub fn verify_against_root(
+        &self,
+        expected_root: H::Hash,
+        queries_per_log_size: BTreeMap<u32, Vec<usize>>,
+        queried_values: ColumnVec<Vec<BaseField>>,
+        decommitment: MerkleDecommitment<H>,
+    ) -> Result<(), MerkleVerificationError> {
+        self.verify_detailed_against_root(
+            expected_root,
+            queries_per_log_size,
+            queried_values,
+            decommitment,
+        )
+        .map_err(|detailed_error| detailed_error.error)
+    }
+
+    fn verify_detailed_against_root(
+        &self,
+        expected_root: H::Hash,
+        queries_per_log_size: BTreeMap<u32, Vec<usize>>,
+        queried_values: ColumnVec<Vec<BaseField>>,
+        decommitment: MerkleDecommitment<H>,
+    ) -> Result<(), DetailedMerkleError<H::Hash>> {
         let max_log_size = self.column_log_sizes.iter().max().copied().unwrap_or(0);
 
         // Prepare read buffers.
@@ -103,6 +211,7 @@ impl<H: MerkleHasher> MerkleVerifier<H> {
             while let Some(node_index) =
                 next_decommitment_node(&mut prev_layer_queries, &mut layer_column_queries)
             {
+                let at_node = |error| DetailedMerkleError::at(error, layer_log_size, node_index);
                 prev_layer_queries
                     .peek_take_while(|q| q / 2 == node_index)
                     .for_each(drop);
@@ -133,7 +242,8 @@ impl<H: MerkleHasher> MerkleVerifier<H> {
                             Ok((left_hash, right_hash))
                         }
                     })
-                    .transpose()?;
+                    .transpose()
+                    .map_err(at_node)?;
 
                 // If the column values were queried, read them from `queried_value`.
                 let node_values = if layer_column_queries.next_if_eq(&node_index).is_some() {
@@ -144,39 +254,146 @@ impl<H: MerkleHasher> MerkleVerifier<H> {
                                 .next()
                                 .ok_or(MerkleVerificationError::ColumnValuesTooShort)
                         })
-                        .collect::<Result<Vec<_>, _>>()?
+                        .collect::<Result<Vec<_>, _>>()
+                        .map_err(at_node)?
                 } else {
                     // Otherwise, read them from the witness.
                     (&mut column_witness).take(n_columns_in_layer).collect_vec()
                 };
                 if node_values.len() != n_columns_in_layer {
-                    return Err(MerkleVerificationError::WitnessTooShort);
+                    return Err(at_node(MerkleVerificationError::WitnessTooShort));
                 }
 
-                layer_total_queries.push((node_index, H::hash_node(node_hashes, &node_values)));
+                layer_total_queries
+                    .push((node_index, H::hash_tagged_node(node_hashes, &node_values)));
             }
 
             if !layer_queried_values.iter().all(|(_, c)| c.is_empty()) {
-                return Err(MerkleVerificationError::ColumnValuesTooLong);
+                return Err(DetailedMerkleError::in_layer(
+                    MerkleVerificationError::ColumnValuesTooLong,
+                    layer_log_size,
+                ));
             }
             last_layer_hashes = Some(layer_total_queries);
         }
 
         // Check that all witnesses and values have been consumed.
-        if !hash_witness.is_empty() {
-            return Err(MerkleVerificationError::WitnessTooLong);
-        }
-        if !column_witness.is_empty() {
-            return Err(MerkleVerificationError::WitnessTooLong);
+        if !hash_witness.is_empty() || !column_witness.is_empty() {
+            return Err(DetailedMerkleError::in_layer(
+                MerkleVerificationError::WitnessTooLong,
+                0,
+            ));
         }
 
         let [(_, computed_root)] = last_layer_hashes.unwrap().try_into().unwrap();
-        if computed_root != self.root {
-            return Err(MerkleVerificationError::RootMismatch);
+        if !computed_root.ct_eq(&expected_root) {
+            return Err(DetailedMerkleError {
+                hash_mismatch: Some(HashMismatch {
+                    expected: expected_root,
+                    actual: computed_root,
+                }),
+                ..DetailedMerkleError::at(MerkleVerificationError::RootMismatch, 0, 0)
+            });
         }
 
         Ok(())
     }
+
+    /// Verifies the decommitment of a subset of the columns, like [Self::verify].
+    ///
+    /// `queried_values` maps the index of each present column to its queried values. Nodes hash
+    /// the values of all the columns of their layer, so a column may only be absent if no
+    /// queries are made to its log size.
+    ///
+    /// # Errors
+    ///
+    /// Returns [MerkleVerificationError::InvalidColumnIndex] if a column index is out of range,
+    /// and [MerkleVerificationError::MissingColumn] if a column is absent while its log size is
+    /// queried. Otherwise, see [Self::verify].
+    pub fn verify_with_partial_columns(
+        &self,
+        queries_per_log_size: BTreeMap<u32, Vec<usize>>,
+        mut queried_values: BTreeMap<usize, Vec<BaseField>>,
+        decommitment: MerkleDecommitment<H>,
+    ) -> Result<(), MerkleVerificationError> {
+        if let Some((&column_index, _)) = queried_values.last_key_value() {
+            if column_index >= self.column_log_sizes.len() {
+                return Err(MerkleVerificationError::InvalidColumnIndex(column_index));
+            }
+        }
+
+        let all_queried_values = self
+            .column_log_sizes
+            .iter()
+            .enumerate()
+            .map(
+                |(column_index, log_size)| match queried_values.remove(&column_index) {
+                    Some(values) => Ok(values),
+                    None if queries_per_log_size
+                        .get(log_size)
+                        .map_or(true, |queries| queries.is_empty()) =>
+                    {
+                        Ok(vec![])
+                    }
+                    None => Err(MerkleVerificationError::MissingColumn),
+                },
+            )
+            .collect::<Result<_, _>>()?;
+        self.verify(queries_per_log_size, all_queried_values, decommitment)
+    }
+
+    /// Verifies the inclusion of a single value, the value of column `column_index` at row
+    /// `row_index`, as proven by [super::prover::MerkleProver::prove_inclusion].
+    ///
+    /// # Errors
+    ///
+    /// Returns an error if the decommitment is invalid. See [Self::verify].
+    ///
+    /// # Panics
+    ///
+    /// This function will panic if `column_index` is out of range.
+    pub fn verify_inclusion(
+        &self,
+        column_index: usize,
+        row_index: usize,
+        value: BaseField,
+        mut decommitment: MerkleDecommitment<H>,
+    ) -> Result<(), MerkleVerificationError> {
+        let log_size = self.column_log_sizes[column_index];
+
+        // The values of the other columns of the same size come first in the column witness.
+        let n_siblings = self
+            .column_log_sizes
+            .iter()
+            .filter(|&&column_log_size| column_log_size == log_size)
+            .count()
+            - 1;
+        if decommitment.column_witness.len() < n_siblings {
+            return Err(MerkleVerificationError::WitnessTooShort);
+        }
+        let mut sibling_values = decommitment
+            .column_witness
+            .drain(..n_siblings)
+            .collect_vec();
+        sibling_values.reverse();
+
+        let queried_values = self
+            .column_log_sizes
+            .iter()
+            .enumerate()
+            .map(|(i, &column_log_size)| {
+                if i == column_index {
+                    vec![value]
+                } else if column_log_size == log_size {
+                    vec![sibling_values.pop().unwrap()]
+                } else {
+                    vec![]
+                }
+            })
+            .collect();
+        let queries = BTreeMap::from([(log_size, vec![row_index])]);
+        self.verify(queries, queried_values, decommitment)
+    }
 }
 
 #[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
@@ -191,4 +408,49 @@ pub enum MerkleVerificationError {
     ColumnValuesTooShort,
     #[error("Root mismatch.")]
     RootMismatch,
+    #[error("Values of a queried column are missing.")]
+    MissingColumn,
+    #[error("Column index {0} is out of range.")]
+    InvalidColumnIndex(usize),
+    #[error("Column layout is inconsistent.")]
+    InconsistentLayout,
+}
+
+/// A [MerkleVerificationError] with the location at which it was detected.
+/// See [MerkleVerifier::verify_detailed].
+#[derive(Clone, Copy, Debug, Error, PartialEq, Eq)]
+#[error("{error} Layer log size: {layer_log_size}, node index: {node_index:?}.")]
+pub struct DetailedMerkleError<Hash> {
+    pub error: MerkleVerificationError,
+    /// The log size of the layer in which the error was detected.
+    pub layer_log_size: u32,
+    /// The index of the node in the layer at which the error was detected, if it was detected
+    /// while verifying a node.
+    pub node_index: Option<usize>,
+    /// The expected and computed hashes, for a [MerkleVerificationError::RootMismatch].
+    pub hash_mismatch: Option<HashMismatch<Hash>>,
+}
+
+impl<Hash> DetailedMerkleError<Hash> {
+    fn at(error: MerkleVerificationError, layer_log_size: u32, node_index: usize) -> Self {
+        Self {
+            node_index: Some(node_index),
+            ..Self::in_layer(error, layer_log_size)
+        }
+    }
+
+    fn in_layer(error: MerkleVerificationError, layer_log_size: u32) -> Self {
+        Self {
+            error,
+            layer_log_size,
+            node_index: None,
+            hash_mismatch: None,
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct HashMismatch<Hash> {
+    pub expected: Hash,
+    pub actual: Hash,
 }
@@ -135,6 +135,7 @@ mod tests {
     use crate::core::fields::m31::BaseField;
     use crate::core::fields::qm31::SecureField;
     use crate::core::fields::IntoSlice;
+    use crate::core::fri::FriConfig;
     use crate::core::pcs::{PcsConfig, TreeVec};
     use crate::core::poly::circle::CanonicCoset;
     use crate::core::prover::{StarkProof, VerificationError};
@@ -146,7 +147,8 @@ mod tests {
     use crate::examples::fibonacci::air::FibonacciAirGenerator;
     use crate::examples::fibonacci::component::FibonacciInput;
     use crate::trace_generation::{
-        commit_and_prove, commit_and_verify, AirTraceGenerator, BASE_TRACE,
+        commit_and_prove, commit_and_prove_with_stats, commit_and_verify, AirTraceGenerator,
+        BASE_TRACE,
     };
     use crate::{m31, qm31};
 
@@ -254,6 +256,47 @@ mod tests {
         fib.verify(proof).unwrap();
     }
 
+    #[test]
+    fn test_fib_proof_stats() {
+        const FIB_LOG_SIZE: u32 = 5;
+        const CLAIM: BaseField = m31!(443693538);
+        let fib = Fibonacci::new(FIB_LOG_SIZE, CLAIM);
+        let config_with_n_queries = |n_queries| PcsConfig {
+            pow_bits: 10,
+            fri_config: FriConfig::new(0, 1, n_queries),
+        };
+        let prove_with_n_queries = |n_queries, collect_stats| {
+            commit_and_prove_with_stats::<_, Sha256MerkleChannel>(
+                &fib.air,
+                &mut Sha256Channel::default(),
+                vec![fib.get_trace()],
+                config_with_n_queries(n_queries),
+                collect_stats,
+            )
+            .unwrap()
+        };
+
+        let (proof, stats) = prove_with_n_queries(3, true);
+        let stats = stats.unwrap();
+        let n_hashes =
+            [10, 30].map(|n_queries| prove_with_n_queries(n_queries, true).1.unwrap().n_hashes);
+
+        assert!(stats.n_fft_butterflies > 0);
+        assert!(stats.n_hashes > 0);
+        assert!(stats.n_hashes < n_hashes[0]);
+        assert!(n_hashes[0] < n_hashes[1]);
+        let (uncounted_proof, no_stats) = prove_with_n_queries(3, false);
+        assert!(no_stats.is_none());
+        assert_eq!(proof.commitments.0, uncounted_proof.commitments.0);
+        commit_and_verify::<Sha256MerkleChannel>(
+            proof,
+            &fib.air,
+            &mut Sha256Channel::default(),
+            config_with_n_queries(3),
+        )
+        .unwrap();
+    }
+
     #[test]
     fn test_fib_air_generator() {
         const FIB_LOG_SIZE: u32 = 5;
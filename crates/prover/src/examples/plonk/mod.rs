@@ -16,7 +16,7 @@ use crate::core::pcs::{CommitmentSchemeProver, PcsConfig};
 use crate::core::poly::circle::{CanonicCoset, CircleEvaluation, PolyOps};
 use crate::core::poly::BitReversedOrder;
 use crate::core::prover::{prove, StarkProof};
-use crate::core::vcs::sha256_merkle::Sha256MerkleHasher;
+use crate::core::vcs::sha256_merkle::{Sha256MerkleChannel, Sha256MerkleHasher};
 use crate::core::{ColumnVec, InteractionElements};
 
 #[derive(Clone)]
@@ -175,7 +175,8 @@ pub fn prove_fibonacci_plonk(
 
     // Setup protocol.
     let channel = &mut Sha256Channel::default();
-    let commitment_scheme = &mut CommitmentSchemeProver::new(config, &twiddles);
+    let commitment_scheme =
+        &mut CommitmentSchemeProver::<_, Sha256MerkleChannel>::new(config, &twiddles);
 
     // Trace.
     let span = span!(Level::INFO, "Trace").entered();
@@ -2,7 +2,7 @@ pub(crate) mod prove;
 pub mod registry;
 
 use downcast_rs::{impl_downcast, Downcast};
-pub use prove::{commit_and_prove, commit_and_verify};
+pub use prove::{commit_and_prove, commit_and_prove_with_stats, commit_and_verify};
 use registry::ComponentGenerationRegistry;
 
 use crate::core::air::{AirProver, Component};
@@ -4,15 +4,15 @@ use tracing::{span, Level};
 
 use super::{AirTraceGenerator, AirTraceVerifier, BASE_TRACE, INTERACTION_TRACE};
 use crate::core::air::{Air, AirProver, ComponentProvers, Components};
-use crate::core::backend::BackendForChannel;
-use crate::core::channel::{Channel, MerkleChannel};
+use crate::core::backend::{BackendForChannel, Column};
+use crate::core::channel::{Channel, HashCounter, MerkleChannel};
 use crate::core::fields::m31::BaseField;
 use crate::core::fields::qm31::SecureField;
 use crate::core::pcs::{CommitmentSchemeProver, CommitmentSchemeVerifier, PcsConfig};
 use crate::core::poly::circle::{CanonicCoset, CircleEvaluation, MAX_CIRCLE_DOMAIN_LOG_SIZE};
 use crate::core::poly::twiddles::TwiddleTree;
 use crate::core::poly::BitReversedOrder;
-use crate::core::prover::{prove, verify, ProvingError, StarkProof, VerificationError};
+use crate::core::prover::{prove, verify, ProofStats, ProvingError, StarkProof, VerificationError};
 use crate::core::{ColumnVec, InteractionElements};
 
 pub fn commit_and_prove<B: BackendForChannel<MC>, MC: MerkleChannel>(
@@ -21,6 +21,41 @@ pub fn commit_and_prove<B: BackendForChannel<MC>, MC: MerkleChannel>(
     trace: ColumnVec<CircleEvaluation<B, BaseField, BitReversedOrder>>,
     config: PcsConfig,
 ) -> Result<StarkProof<MC::H>, CommitAndProveError> {
+    commit_and_prove_with_stats(air, channel, trace, config, false).map(|(proof, _)| proof)
+}
+
+/// Commits and proves like [commit_and_prove]. If `collect_stats` is set, also returns the
+/// [ProofStats] measured while proving. The proof does not depend on `collect_stats`.
+pub fn commit_and_prove_with_stats<B: BackendForChannel<MC>, MC: MerkleChannel>(
+    air: &impl AirTraceGenerator<B>,
+    channel: &mut MC::C,
+    trace: ColumnVec<CircleEvaluation<B, BaseField, BitReversedOrder>>,
+    config: PcsConfig,
+    collect_stats: bool,
+) -> Result<(StarkProof<MC::H>, Option<ProofStats>), CommitAndProveError> {
+    if !collect_stats {
+        let (proof, _) = commit_and_prove_and_count_butterflies(air, channel, trace, config)?;
+        return Ok((proof, None));
+    }
+
+    let counter = HashCounter::default();
+    let (proof, n_fft_butterflies) =
+        counter.count(|| commit_and_prove_and_count_butterflies(air, channel, trace, config))?;
+    let stats = ProofStats {
+        n_hashes: counter.n_hashes(),
+        n_fft_butterflies,
+    };
+    Ok((proof, Some(stats)))
+}
+
+/// Commits and proves like [commit_and_prove], also returning the estimated number of butterflies
+/// of the FFTs that evaluate the committed polynomials. See [ProofStats::n_fft_butterflies].
+fn commit_and_prove_and_count_butterflies<B: BackendForChannel<MC>, MC: MerkleChannel>(
+    air: &impl AirTraceGenerator<B>,
+    channel: &mut MC::C,
+    trace: ColumnVec<CircleEvaluation<B, BaseField, BitReversedOrder>>,
+    config: PcsConfig,
+) -> Result<(StarkProof<MC::H>, usize), CommitAndProveError> {
     let max_degree = trace
         .iter()
         .map(|x| x.domain.log_size())
@@ -76,12 +111,24 @@ pub fn commit_and_prove<B: BackendForChannel<MC>, MC: MerkleChannel>(
             .collect_vec(),
     );
 
-    Ok(prove(
+    let proof = prove(
         &components.0,
         channel,
         &interaction_elements,
         &mut commitment_scheme,
-    )?)
+    )?;
+
+    // A circle FFT on a domain of size 2^n has n layers of 2^(n-1) butterflies.
+    let n_fft_butterflies = commitment_scheme
+        .trees
+        .iter()
+        .flat_map(|tree| &tree.evaluations)
+        .map(|evaluation| {
+            let log_size = evaluation.domain.log_size() as usize;
+            log_size * (evaluation.values.len() / 2)
+        })
+        .sum();
+    Ok((proof, n_fft_butterflies))
 }
 
 pub fn evaluate_and_commit_on_trace<'a, B: BackendForChannel<MC>, MC: MerkleChannel>(